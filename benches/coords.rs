@@ -0,0 +1,177 @@
+//! Compares ways of computing the coordinates of every tiling of a point, without the IHT lookup.
+//!
+//! Run with `cargo bench --bench coords`. As with the `keys` bench, each line of output is a JSON object describing
+//! one measurement.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+const POINTS: usize = 2_000;
+const ROUNDS: usize = 20;
+
+// a tiny deterministic generator so every run benchmarks the same points
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> f64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+trait Coords {
+    fn name(&self) -> &'static str;
+    // call `visit` with the coordinates of every tiling in turn
+    fn each(&self, num_tilings: usize, floats: &[f64], buffer: &mut Vec<isize>, visit: &mut dyn FnMut(&[isize]));
+}
+
+/// Every tiling divided out from scratch, one float at a time (tiles3's loop)
+struct Scalar;
+
+impl Coords for Scalar {
+    fn name(&self) -> &'static str {
+        "scalar"
+    }
+
+    fn each(&self, num_tilings: usize, floats: &[f64], buffer: &mut Vec<isize>, visit: &mut dyn FnMut(&[isize])) {
+        let n = num_tilings as isize;
+        let q_floats: Vec<isize> = floats.iter().map(|&x| (x * num_tilings as f64).floor() as isize).collect();
+        for tiling in 0..num_tilings {
+            buffer.clear();
+            buffer.push(tiling as isize);
+            let mut b = tiling as isize;
+            for q in q_floats.iter() {
+                buffer.push((q + b) / n);
+                b += tiling as isize * 2;
+            }
+            visit(buffer);
+        }
+    }
+}
+
+/// Every tiling divided out from scratch, in chunks of four lanes which the compiler can vectorize, as the crate does
+struct Chunked;
+
+const LANES: usize = 4;
+
+impl Coords for Chunked {
+    fn name(&self) -> &'static str {
+        "chunked"
+    }
+
+    fn each(&self, num_tilings: usize, floats: &[f64], buffer: &mut Vec<isize>, visit: &mut dyn FnMut(&[isize])) {
+        let n = num_tilings as isize;
+        let mut q_floats = Vec::with_capacity(floats.len());
+        let mut chunks = floats.chunks_exact(LANES);
+        for chunk in &mut chunks {
+            let mut lanes = [0.0; LANES];
+            for (lane, &x) in lanes.iter_mut().zip(chunk) {
+                *lane = (x * num_tilings as f64).floor();
+            }
+            q_floats.extend(lanes.iter().map(|&q| q as isize));
+        }
+        q_floats.extend(chunks.remainder().iter().map(|&x| (x * num_tilings as f64).floor() as isize));
+
+        for tiling in 0..num_tilings {
+            let tiling_x2 = tiling as isize * 2;
+            buffer.clear();
+            buffer.push(tiling as isize);
+            let mut b = tiling as isize;
+            let mut chunks = q_floats.chunks_exact(LANES);
+            for chunk in &mut chunks {
+                let mut lanes = [0; LANES];
+                for (i, (lane, q)) in lanes.iter_mut().zip(chunk).enumerate() {
+                    *lane = (q + b + tiling_x2 * i as isize) / n;
+                }
+                buffer.extend_from_slice(&lanes);
+                b += tiling_x2 * LANES as isize;
+            }
+            for q in chunks.remainder() {
+                buffer.push((q + b) / n);
+                b += tiling_x2;
+            }
+            visit(buffer);
+        }
+    }
+}
+
+/// The tilings walked in order, carrying each coordinate forward with additions and a comparison instead of dividing
+struct Walk;
+
+impl Coords for Walk {
+    fn name(&self) -> &'static str {
+        "walk"
+    }
+
+    fn each(&self, num_tilings: usize, floats: &[f64], buffer: &mut Vec<isize>, visit: &mut dyn FnMut(&[isize])) {
+        let n = num_tilings as isize;
+        // (quotient, remainder, step quotient, step remainder) of each float's numerator
+        let mut lanes: Vec<(isize, isize, isize, isize)> = floats
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| {
+                let q = (x * num_tilings as f64).floor() as isize;
+                let step = 2 * i as isize + 1;
+                (q.div_euclid(n), q.rem_euclid(n), step / n, step % n)
+            })
+            .collect();
+        for tiling in 0..num_tilings {
+            buffer.clear();
+            buffer.push(tiling as isize);
+            for &(quotient, remainder, _, _) in lanes.iter() {
+                buffer.push(quotient + (quotient < 0 && remainder != 0) as isize);
+            }
+            visit(buffer);
+            for lane in lanes.iter_mut() {
+                lane.0 += lane.2;
+                lane.1 += lane.3;
+                if lane.1 >= n {
+                    lane.1 -= n;
+                    lane.0 += 1;
+                }
+            }
+        }
+    }
+}
+
+fn bench(coords: &dyn Coords, num_tilings: usize, points: &[Vec<f64>]) -> f64 {
+    let mut buffer = Vec::new();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for point in points {
+            coords.each(num_tilings, black_box(point), &mut buffer, &mut |c| {
+                black_box(c);
+            });
+        }
+    }
+    start.elapsed().as_nanos() as f64 / (ROUNDS * points.len()) as f64
+}
+
+fn main() {
+    let candidates: [&dyn Coords; 3] = [&Scalar, &Chunked, &Walk];
+
+    // every candidate must agree before any of them is timed
+    let mut rng = Lcg(0);
+    let point: Vec<f64> = (0..9).map(|_| rng.next() * 20.0 - 10.0).collect();
+    let mut expected = Vec::new();
+    Scalar.each(16, &point, &mut Vec::new(), &mut |c| expected.push(c.to_vec()));
+    for candidate in candidates.iter() {
+        let mut found = Vec::new();
+        candidate.each(16, &point, &mut Vec::new(), &mut |c| found.push(c.to_vec()));
+        assert_eq!(found, expected, "{} disagrees with scalar", candidate.name());
+    }
+
+    for &(num_tilings, dims) in &[(8, 1), (8, 2), (16, 4), (32, 8), (64, 16)] {
+        let mut rng = Lcg(dims as u64);
+        let points: Vec<Vec<f64>> = (0..POINTS).map(|_| (0..dims).map(|_| rng.next() * 20.0 - 10.0).collect()).collect();
+        for candidate in candidates.iter() {
+            println!(
+                "{{\"implementation\":\"{}\",\"dims\":{},\"num_tilings\":{},\"ns_per_call\":{:.1}}}",
+                candidate.name(),
+                dims,
+                num_tilings,
+                bench(*candidate, num_tilings, &points)
+            );
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Compares alternative key representations for the IHT dictionary.
+//!
+//! Run with `cargo bench --bench keys`. Each line of output is a JSON object describing one
+//! measurement, so the report can be piped straight into other tooling:
+//!
+//! ```text
+//! cargo bench --bench keys > keys.jsonl
+//! ```
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::hint::black_box;
+use std::time::Instant;
+
+use tilecoding::{PackedIHT, IHT};
+
+const NUM_TILINGS: usize = 8;
+const POINTS: usize = 2_000;
+const ROUNDS: usize = 10;
+
+// a tiny deterministic generator so every run benchmarks the same points
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> f64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+// mirrors the coordinate computation used by `IHT::tiles`
+fn coords(tiling: usize, q_floats: &[isize], out: &mut Vec<isize>) {
+    out.clear();
+    out.push(tiling as isize);
+    let mut b = tiling as isize;
+    for q in q_floats {
+        out.push((q + b) / NUM_TILINGS as isize);
+        b += tiling as isize * 2;
+    }
+}
+
+fn hash_slice(coords: &[isize]) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    coords.hash(&mut hasher);
+    hasher.finish()
+}
+
+trait Keys {
+    fn name(&self) -> &'static str;
+    fn index(&mut self, coords: &[isize]) -> usize;
+}
+
+/// The representation currently used by the IHT: one owned `Vec<isize>` per key
+struct VecKeys {
+    size: usize,
+    map: HashMap<Vec<isize>, usize>,
+}
+
+impl Keys for VecKeys {
+    fn name(&self) -> &'static str {
+        "vec"
+    }
+
+    fn index(&mut self, coords: &[isize]) -> usize {
+        if let Some(&i) = self.map.get(coords) {
+            return i;
+        }
+        let count = self.map.len();
+        if count >= self.size {
+            return hash_slice(coords) as usize % self.size;
+        }
+        self.map.insert(coords.to_vec(), count);
+        count
+    }
+}
+
+/// Coordinates packed into a single `u128`, 16 bits per coordinate
+struct PackedKeys {
+    size: usize,
+    map: HashMap<u128, usize>,
+}
+
+impl Keys for PackedKeys {
+    fn name(&self) -> &'static str {
+        "packed"
+    }
+
+    fn index(&mut self, coords: &[isize]) -> usize {
+        assert!(coords.len() <= 8, "packed keys hold at most 8 coordinates");
+        let key = coords.iter().fold(coords.len() as u128, |key, &c| (key << 16) | (c as u16 as u128));
+        let count = self.map.len();
+        if count >= self.size {
+            if let Some(&i) = self.map.get(&key) {
+                return i;
+            }
+            return hash_slice(coords) as usize % self.size;
+        }
+        *self.map.entry(key).or_insert(count)
+    }
+}
+
+/// All coordinates stored contiguously in one arena, indexed by an open-addressing table of entry ids
+struct ArenaKeys {
+    size: usize,
+    dims: usize,
+    arena: Vec<isize>,
+    slots: Vec<u32>,
+}
+
+impl ArenaKeys {
+    fn new(size: usize, dims: usize) -> ArenaKeys {
+        ArenaKeys {
+            size,
+            dims,
+            arena: Vec::with_capacity(size * dims),
+            slots: vec![0; (size * 2).next_power_of_two()],
+        }
+    }
+}
+
+impl Keys for ArenaKeys {
+    fn name(&self) -> &'static str {
+        "arena"
+    }
+
+    fn index(&mut self, coords: &[isize]) -> usize {
+        let mask = self.slots.len() - 1;
+        let hash = hash_slice(coords);
+        let mut slot = hash as usize & mask;
+        loop {
+            match self.slots[slot] {
+                0 => break,
+                id => {
+                    let start = (id as usize - 1) * self.dims;
+                    if &self.arena[start..start + self.dims] == coords {
+                        return id as usize - 1;
+                    }
+                }
+            }
+            slot = (slot + 1) & mask;
+        }
+
+        let count = self.arena.len() / self.dims;
+        if count >= self.size {
+            return hash as usize % self.size;
+        }
+        self.arena.extend_from_slice(coords);
+        self.slots[slot] = count as u32 + 1;
+        count
+    }
+}
+
+fn bench_keys(keys: &mut dyn Keys, points: &[Vec<f64>]) -> f64 {
+    let mut q_floats = Vec::new();
+    let mut buffer = Vec::new();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for point in points {
+            q_floats.clear();
+            q_floats.extend(point.iter().map(|&x| (x * NUM_TILINGS as f64).floor() as isize));
+            for tiling in 0..NUM_TILINGS {
+                coords(tiling, &q_floats, &mut buffer);
+                black_box(keys.index(&buffer));
+            }
+        }
+    }
+    start.elapsed().as_nanos() as f64 / (ROUNDS * points.len()) as f64
+}
+
+fn bench_iht(size: usize, points: &[Vec<f64>]) -> f64 {
+    let mut iht = IHT::new(size);
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for point in points {
+            black_box(iht.tiles(NUM_TILINGS, point, None));
+        }
+    }
+    start.elapsed().as_nanos() as f64 / (ROUNDS * points.len()) as f64
+}
+
+fn bench_packed_iht(size: usize, points: &[Vec<f64>]) -> f64 {
+    let mut iht = PackedIHT::new(size);
+    let mut tiles = [0; NUM_TILINGS];
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for point in points {
+            iht.tiles_into(&mut tiles, point, None);
+            black_box(&tiles);
+        }
+    }
+    start.elapsed().as_nanos() as f64 / (ROUNDS * points.len()) as f64
+}
+
+fn report(representation: &str, dims: usize, size: usize, ns_per_call: f64) {
+    println!(
+        "{{\"representation\":\"{}\",\"dims\":{},\"size\":{},\"num_tilings\":{},\"ns_per_call\":{:.1}}}",
+        representation, dims, size, NUM_TILINGS, ns_per_call
+    );
+}
+
+fn main() {
+    for &dims in &[1, 2, 4, 7] {
+        let mut rng = Lcg(dims as u64);
+        let points: Vec<Vec<f64>> = (0..POINTS).map(|_| (0..dims).map(|_| rng.next() * 20.0 - 10.0).collect()).collect();
+
+        for &size in &[1 << 10, 1 << 14, 1 << 18] {
+            report("iht", dims, size, bench_iht(size, &points));
+            report("packed_iht", dims, size, bench_packed_iht(size, &points));
+
+            let mut candidates: Vec<Box<dyn Keys>> = vec![
+                Box::new(VecKeys { size, map: HashMap::with_capacity(size) }),
+                Box::new(PackedKeys { size, map: HashMap::with_capacity(size) }),
+                Box::new(ArenaKeys::new(size, dims + 1)),
+            ];
+            for keys in candidates.iter_mut() {
+                let ns = bench_keys(keys.as_mut(), &points);
+                report(keys.name(), dims, size, ns);
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+"""Python bindings for the tilecoding crate, over its C interface (include/tilecoding.h) using ctypes.
+
+A drop-in replacement for Sutton's tiles3.py: `IHT`, `tiles`, and `tileswrap` take the same arguments and give
+exactly the same indices, including below zero and once an IHT is full, through the crate's tiles3 parity functions.
+Code using tiles3 can switch by changing its import:
+
+    from tilecoding import IHT, tiles, tileswrap
+
+    iht = IHT(1024)
+    indices = tiles(iht, 8, [3.6, 7.21])
+
+Build the shared library with `cargo build --release` first. It is looked for in the crate's target/release and
+target/debug directories, unless the TILECODING_LIB environment variable gives its path.
+
+The one difference from tiles3 is that `ihtORsize` can't be None, which asks tiles3 for raw unbounded hashes.
+"""
+
+import ctypes
+import os
+import sys
+
+__all__ = ["IHT", "tiles", "tileswrap"]
+
+_NULL_POINTER = -1
+
+
+def _library_path():
+    if "TILECODING_LIB" in os.environ:
+        return os.environ["TILECODING_LIB"]
+    if sys.platform == "win32":
+        name = "tilecoding.dll"
+    elif sys.platform == "darwin":
+        name = "libtilecoding.dylib"
+    else:
+        name = "libtilecoding.so"
+    root = os.path.join(os.path.dirname(os.path.abspath(__file__)), os.pardir, os.pardir, "target")
+    for profile in ("release", "debug"):
+        path = os.path.join(root, profile, name)
+        if os.path.exists(path):
+            return path
+    raise OSError("couldn't find {} in {}; build the crate or set TILECODING_LIB".format(name, os.path.normpath(root)))
+
+
+_lib = ctypes.CDLL(_library_path())
+
+_ptrdiff_t = ctypes.c_ssize_t
+_size_p = ctypes.POINTER(ctypes.c_size_t)
+_double_p = ctypes.POINTER(ctypes.c_double)
+_ptrdiff_p = ctypes.POINTER(_ptrdiff_t)
+# every encoding function takes the floats, wrap widths, and ints, then the output array
+_ENCODE = [ctypes.c_size_t, _double_p, _ptrdiff_p, ctypes.c_size_t, _ptrdiff_p, ctypes.c_size_t]
+
+_lib.tilecoding_iht_new.argtypes = [ctypes.c_size_t]
+_lib.tilecoding_iht_new.restype = ctypes.c_void_p
+_lib.tilecoding_iht_free.argtypes = [ctypes.c_void_p]
+_lib.tilecoding_iht_free.restype = None
+_lib.tilecoding_iht_count.argtypes = [ctypes.c_void_p]
+_lib.tilecoding_iht_count.restype = ctypes.c_size_t
+_lib.tilecoding_tiles_python.argtypes = [ctypes.c_void_p] + _ENCODE + [_size_p]
+_lib.tilecoding_tiles_python.restype = ctypes.c_int32
+_lib.tilecoding_tiles_read_only_python.argtypes = [ctypes.c_void_p] + _ENCODE + [_ptrdiff_p]
+_lib.tilecoding_tiles_read_only_python.restype = ctypes.c_int32
+_lib.tilecoding_hash_tiles_python.argtypes = [ctypes.c_size_t] + _ENCODE + [_size_p]
+_lib.tilecoding_hash_tiles_python.restype = ctypes.c_int32
+
+
+class IHT:
+    """An index hash table, handing out indices below `size` to tiles in the order they are first seen"""
+
+    def __init__(self, sizeval):
+        if sizeval <= 0:
+            raise ValueError("the IHT size must be positive")
+        self.size = sizeval
+        self._iht = _lib.tilecoding_iht_new(sizeval)
+        if not self._iht:
+            raise MemoryError("couldn't create an IHT")
+
+    def __del__(self):
+        iht, self._iht = getattr(self, "_iht", None), None
+        # the library may already have been unloaded when the interpreter shuts down
+        if iht and _lib is not None:
+            _lib.tilecoding_iht_free(iht)
+
+    def count(self):
+        """The number of tiles which have been given indices"""
+        return _lib.tilecoding_iht_count(self._iht)
+
+    def fullp(self):
+        """Whether every index has been handed out, after which new tiles are hashed and may collide"""
+        return self.count() >= self.size
+
+    def __str__(self):
+        return "Collision table: size: {} count: {}".format(self.size, self.count())
+
+
+def _array(ctype, values):
+    values = list(values)
+    # never a zero-length array, whose pointer ctypes may make null
+    return (ctype * max(len(values), 1))(*values), len(values)
+
+
+def _encode(ihtORsize, numtilings, floats, wrapwidths, ints, readonly):
+    floats, num_floats = _array(ctypes.c_double, floats)
+    ints, num_ints = _array(_ptrdiff_t, ints)
+    # null wrap widths select tiles3's `tiles` rather than `tileswrap`, which reduces its offsets differently
+    widths = None
+    if wrapwidths is not None:
+        if len(wrapwidths) != num_floats:
+            raise ValueError("there must be one wrap width per float")
+        widths, _ = _array(_ptrdiff_t, (width or 0 for width in wrapwidths))
+    args = (numtilings, floats, widths, num_floats, ints, num_ints)
+
+    if isinstance(ihtORsize, IHT):
+        if readonly:
+            out = (_ptrdiff_t * numtilings)()
+            status = _lib.tilecoding_tiles_read_only_python(ihtORsize._iht, *args, out)
+        else:
+            out = (ctypes.c_size_t * numtilings)()
+            status = _lib.tilecoding_tiles_python(ihtORsize._iht, *args, out)
+    elif isinstance(ihtORsize, int) and ihtORsize > 0:
+        out = (ctypes.c_size_t * numtilings)()
+        status = _lib.tilecoding_hash_tiles_python(ihtORsize, *args, out)
+    else:
+        raise TypeError("ihtORsize must be an IHT or a positive int")
+    if status == _NULL_POINTER:
+        raise ValueError("the tilecoding library was passed a null pointer")
+    return [None if index < 0 else index for index in out]
+
+
+def tiles(ihtORsize, numtilings, floats, ints=[], readonly=False):
+    """The indices of the tiles containing `floats` (and `ints`), one per tiling. With `readonly`, tiles which aren't
+    in the IHT are None rather than being added."""
+    return _encode(ihtORsize, numtilings, floats, None, ints, readonly)
+
+
+def tileswrap(ihtORsize, numtilings, floats, wrapwidths, ints=[], readonly=False):
+    """The wrap-around version of `tiles`, with one wrap width per float, where False (or None, or 0) means that the
+    float doesn't wrap"""
+    return _encode(ihtORsize, numtilings, floats, wrapwidths, ints, readonly)
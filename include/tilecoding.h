@@ -0,0 +1,94 @@
+/* The C interface to the tilecoding crate, see src/ffi.rs. Link against the crate's cdylib (libtilecoding). */
+
+#ifndef TILECODING_H
+#define TILECODING_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+/* The call succeeded */
+#define TILECODING_OK 0
+/* A required pointer was null */
+#define TILECODING_NULL_POINTER -1
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* An index hash table, only ever used through a pointer */
+typedef struct IHT IHT;
+
+/* Create an IHT with the given size, which must later be freed with tilecoding_iht_free */
+IHT *tilecoding_iht_new(size_t size);
+
+/* Free an IHT created by tilecoding_iht_new; NULL is ignored */
+void tilecoding_iht_free(IHT *iht);
+
+/* The number of entries in the IHT, or 0 if it is NULL */
+size_t tilecoding_iht_count(const IHT *iht);
+
+/* Encode a point as num_tilings indices written to out. ints may be NULL when num_ints is 0. */
+int32_t tilecoding_tiles(IHT *iht,
+                         size_t num_tilings,
+                         const double *floats,
+                         size_t num_floats,
+                         const ptrdiff_t *ints,
+                         size_t num_ints,
+                         size_t *out);
+
+/* The wrap-around version of tilecoding_tiles, with one wrap width per float (0 or less for no wrapping) */
+int32_t tilecoding_tiles_wrap(IHT *iht,
+                              size_t num_tilings,
+                              const double *floats,
+                              const ptrdiff_t *wrap_widths,
+                              size_t num_floats,
+                              const ptrdiff_t *ints,
+                              size_t num_ints,
+                              size_t *out);
+
+/* The read-only version of tilecoding_tiles, which writes -1 for tiles that aren't in the IHT */
+int32_t tilecoding_tiles_read_only(const IHT *iht,
+                                   size_t num_tilings,
+                                   const double *floats,
+                                   size_t num_floats,
+                                   const ptrdiff_t *ints,
+                                   size_t num_ints,
+                                   ptrdiff_t *out);
+
+/* The tiles3 parity version of tilecoding_tiles_wrap, matching Sutton's Python tileswrap(iht, ...), or
+   tiles(iht, ...) when wrap_widths is NULL */
+int32_t tilecoding_tiles_python(IHT *iht,
+                                size_t num_tilings,
+                                const double *floats,
+                                const ptrdiff_t *wrap_widths,
+                                size_t num_floats,
+                                const ptrdiff_t *ints,
+                                size_t num_ints,
+                                size_t *out);
+
+/* The read-only version of tilecoding_tiles_python, which writes -1 for tiles that aren't in the IHT */
+int32_t tilecoding_tiles_read_only_python(const IHT *iht,
+                                          size_t num_tilings,
+                                          const double *floats,
+                                          const ptrdiff_t *wrap_widths,
+                                          size_t num_floats,
+                                          const ptrdiff_t *ints,
+                                          size_t num_ints,
+                                          ptrdiff_t *out);
+
+/* The tiles3 parity version of encoding without an IHT, matching Python's tileswrap(size, ...), or
+   tiles(size, ...) when wrap_widths is NULL */
+int32_t tilecoding_hash_tiles_python(size_t size,
+                                     size_t num_tilings,
+                                     const double *floats,
+                                     const ptrdiff_t *wrap_widths,
+                                     size_t num_floats,
+                                     const ptrdiff_t *ints,
+                                     size_t num_ints,
+                                     size_t *out);
+
+#ifdef __cplusplus
+}  /* extern "C" */
+#endif
+
+#endif  /* TILECODING_H */
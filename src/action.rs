@@ -0,0 +1,94 @@
+use crate::TileCoder;
+
+/// A tile coder for state-action pairs with a fixed number of discrete actions, which passes the action to the
+/// coder as an int so that every action has its own tiles.
+///
+/// Encoding a state for every action at once only quantizes the floats a single time, rather than once per action.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{ActionTileCoder, Dim, TileCoder};
+/// let mut coder = ActionTileCoder::new(TileCoder::new(8, 4096, vec![Dim::bins(-1.2, 0.6, 8)]), 3);
+///
+/// let all = coder.tiles_for_all_actions(&[-0.5]);
+/// assert_eq!(all.len(), 3);
+/// assert_eq!(all[2], coder.tiles_for_action(&[-0.5], 2));
+///
+/// // the same as passing the action as an int by hand
+/// let mut plain = TileCoder::new(8, 4096, vec![Dim::bins(-1.2, 0.6, 8)]);
+/// assert_eq!(plain.tiles(&[-0.5], Some(&[0])), all[0]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActionTileCoder {
+    coder: TileCoder,
+    num_actions: usize,
+}
+
+impl ActionTileCoder {
+    /// Wrap a coder for states, with actions numbered `0..num_actions`
+    pub fn new(coder: TileCoder, num_actions: usize) -> ActionTileCoder {
+        assert!(num_actions > 0, "there must be at least one action");
+        ActionTileCoder { coder, num_actions }
+    }
+
+    /// The number of actions
+    pub fn num_actions(&self) -> usize {
+        self.num_actions
+    }
+
+    /// The underlying coder
+    pub fn coder(&self) -> &TileCoder {
+        &self.coder
+    }
+
+    /// Encode a raw state along with an action
+    pub fn tiles_for_action(&mut self, floats: &[f64], action: usize) -> Vec<usize> {
+        assert!(action < self.num_actions, "action out of range");
+        self.coder.tiles(floats, Some(&[action as isize]))
+    }
+
+    /// Encode a raw state along with every action, indexed by action
+    pub fn tiles_for_all_actions(&mut self, floats: &[f64]) -> Vec<Vec<usize>> {
+        // the action is always the last coordinate, so the rest can be shared
+        let coords = self.coder.coords(floats, None);
+        let iht = self.coder.iht_mut();
+        (0..self.num_actions)
+            .map(|action| {
+                coords
+                    .iter()
+                    .map(|coords| {
+                        let mut coords = coords.clone();
+                        coords.push(action as isize);
+                        iht.get_index(coords)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The read-only version of `tiles_for_action`
+    pub fn tiles_for_action_read_only(&self, floats: &[f64], action: usize) -> Vec<Option<usize>> {
+        assert!(action < self.num_actions, "action out of range");
+        self.coder.tiles_read_only(floats, Some(&[action as isize]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dim;
+
+    #[test]
+    fn all_actions_match_single_actions() {
+        let coder = TileCoder::new(4, 1024, vec![Dim::width(0.5), Dim::wrapping(0.0, 1.0, 4)]);
+        let mut a = ActionTileCoder::new(coder.clone(), 4);
+        let mut b = ActionTileCoder::new(coder, 4);
+        let all = a.tiles_for_all_actions(&[1.3, 0.9]);
+        for (action, tiles) in all.iter().enumerate() {
+            assert_eq!(&b.tiles_for_action(&[1.3, 0.9], action), tiles);
+            assert_eq!(a.tiles_for_action_read_only(&[1.3, 0.9], action), tiles.iter().map(|&i| Some(i)).collect::<Vec<_>>());
+        }
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use crate::{calculate_coords, calculate_q_floats, Dim, IHT};
+
+// what is known about a tile that has been encoded
+#[derive(Clone, Debug, Default, PartialEq)]
+struct TileStats {
+    depth: usize,
+    reports: u64,
+    total_error: f64,
+    split: bool,
+}
+
+/// A tile coder with non-uniform resolution, which splits the tiles where the value function is hard to fit into
+/// finer sub-tiles. After each update, report the TD error for the active tiles with `report`; once a tile has
+/// been reported on enough times with a large enough mean absolute error, it is split in two along every dimension.
+///
+/// A split tile stays active, with the sub-tile containing the point (and so on down) active beneath it, so the
+/// value learned by the coarse tile is kept and the sub-tiles only have to learn the detail. Points encode as one
+/// chain of tiles per tiling, coarsest first, so the number of indices grows with the resolution around the point.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{AdaptiveCoder, Dim};
+/// let mut coder = AdaptiveCoder::new(4, 4096, vec![Dim::bins(0.0, 1.0, 4)]).split_after(10, 0.5);
+/// assert_eq!(coder.tiles(&[0.3], None).len(), 4);
+///
+/// // the value function is hard to fit around 0.3...
+/// for _ in 0..10 {
+///     let tiles = coder.tiles(&[0.3], None);
+///     coder.report(&tiles, 2.0);
+/// }
+/// // ...so every tile containing it has been split
+/// assert_eq!(coder.tiles(&[0.3], None).len(), 8);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdaptiveCoder {
+    num_tilings: usize,
+    dims: Vec<Dim>,
+    min_reports: u64,
+    threshold: f64,
+    max_depth: usize,
+    stats: HashMap<usize, TileStats>,
+    iht: IHT,
+}
+
+impl AdaptiveCoder {
+    /// Create a coder with `num_tilings` tilings over the given dimensions, backed by an IHT with the given size. By
+    /// default tiles are split once they have had 100 reports with a mean absolute error above 1, up to 4 times.
+    /// Wrapping dimensions aren't supported, since sub-tiles can't wrap around independently of their parents.
+    pub fn new(num_tilings: usize, size: usize, dims: Vec<Dim>) -> AdaptiveCoder {
+        assert!(dims.iter().all(|d| d.wrap_width().is_none()), "adaptive coders don't support wrapping dimensions");
+        AdaptiveCoder {
+            num_tilings,
+            dims,
+            min_reports: 100,
+            threshold: 1.0,
+            max_depth: 4,
+            stats: HashMap::new(),
+            iht: IHT::new(size),
+        }
+    }
+
+    /// Split tiles once they have had `min_reports` reports with a mean absolute error above `threshold`
+    pub fn split_after(mut self, min_reports: u64, threshold: f64) -> AdaptiveCoder {
+        assert!(min_reports > 0, "tiles must be reported on at least once before splitting");
+        self.min_reports = min_reports;
+        self.threshold = threshold;
+        self
+    }
+
+    /// Limit how many times a tile can be split, so that sub-tiles are never less than `1 / 2^max_depth` of the base
+    /// tile width
+    pub fn max_depth(mut self, max_depth: usize) -> AdaptiveCoder {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// The underlying IHT, shared by the tiles at every depth
+    pub fn iht(&self) -> &IHT {
+        &self.iht
+    }
+
+    /// The number of times the tile with the given index has been split below the base resolution, if it has been
+    /// encoded
+    pub fn depth(&self, index: usize) -> Option<usize> {
+        self.stats.get(&index).map(|stats| stats.depth)
+    }
+
+    /// The number of tiles that have been split
+    pub fn splits(&self) -> usize {
+        self.stats.values().filter(|stats| stats.split).count()
+    }
+
+    /// Encode a raw point as the chain of active tiles in each tiling, coarsest first
+    pub fn tiles(&mut self, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        assert_eq!(floats.len(), self.dims.len(), "expected one float per dimension");
+        let scaled: Vec<f64> = floats.iter().zip(self.dims.iter()).map(|(&x, d)| d.scale(x)).collect();
+        let mut tiles = Vec::with_capacity(self.num_tilings);
+        for tiling in 0..self.num_tilings {
+            let mut parent: Option<usize> = None;
+            for depth in 0..=self.max_depth {
+                // the sub-tiles at each depth are the tiles of a grid twice as fine as the one above, keyed by their
+                // parent so that they are nested inside it
+                let refined: Vec<f64> = scaled.iter().map(|&x| x * (1 << depth) as f64).collect();
+                let q_floats = calculate_q_floats(&refined, self.num_tilings);
+                let mut coords = calculate_coords(tiling, self.num_tilings, &q_floats, &ints);
+                if let Some(parent) = parent {
+                    coords.push(depth as isize);
+                    coords.push(parent as isize);
+                }
+                let index = self.iht.get_index(coords);
+                tiles.push(index);
+
+                let stats = self.stats.entry(index).or_insert_with(|| TileStats { depth, ..TileStats::default() });
+                if !stats.split {
+                    break;
+                }
+                parent = Some(index);
+            }
+        }
+        tiles
+    }
+
+    /// Report the TD error of an update to the given (active) tiles, splitting any that have had enough reports of
+    /// large errors. Returns the number of tiles that were split.
+    pub fn report(&mut self, tiles: &[usize], error: f64) -> usize {
+        let mut splits = 0;
+        for index in tiles {
+            if let Some(stats) = self.stats.get_mut(index) {
+                if stats.split || stats.depth >= self.max_depth {
+                    continue;
+                }
+                stats.reports += 1;
+                stats.total_error += error.abs();
+                if stats.reports >= self.min_reports && stats.total_error / stats.reports as f64 > self.threshold {
+                    stats.split = true;
+                    splits += 1;
+                }
+            }
+        }
+        splits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_are_local() {
+        let mut coder = AdaptiveCoder::new(2, 1024, vec![Dim::bins(0.0, 1.0, 2)]).split_after(5, 1.0);
+        for _ in 0..5 {
+            let tiles = coder.tiles(&[0.1], None);
+            assert_eq!(coder.report(&tiles, 0.5), 0);
+        }
+        assert_eq!(coder.splits(), 0);
+        for _ in 0..5 {
+            let tiles = coder.tiles(&[0.1], None);
+            coder.report(&tiles, 3.0);
+        }
+        assert_eq!(coder.splits(), 2);
+
+        let fine = coder.tiles(&[0.1], None);
+        assert_eq!(fine.len(), 4);
+        assert_eq!(coder.depth(fine[1]), Some(1));
+        // points far from the split tiles are encoded at the base resolution
+        assert_eq!(coder.tiles(&[0.9], None).len(), 2);
+    }
+
+    #[test]
+    fn depth_is_limited() {
+        let mut coder = AdaptiveCoder::new(1, 1024, vec![Dim::width(1.0)]).split_after(1, 0.0).max_depth(3);
+        for _ in 0..10 {
+            let tiles = coder.tiles(&[0.3], None);
+            coder.report(&tiles, 1.0);
+        }
+        let tiles = coder.tiles(&[0.3], None);
+        assert_eq!(tiles.len(), 4);
+        assert_eq!(tiles.iter().map(|&i| coder.depth(i).unwrap()).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::json::Json;
+use crate::IHT;
+
+/// The number of most-visited indices reported by [`IHT::audit`]
+const HOTSPOTS: usize = 10;
+
+/// A summary of the state of an IHT, intended to be attached to experiment tracking after a training run.
+/// See [`IHT::audit`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Audit {
+    /// The size the IHT was created with
+    pub size: usize,
+    /// The number of tiles that have been assigned an index
+    pub count: usize,
+    /// `count / size`
+    pub fill: f64,
+    /// The number of indices that have not been assigned to any tile yet
+    pub unused: usize,
+    /// The number of lookups that had to fall back to a hashed (colliding) index because the IHT was full
+    pub collisions: usize,
+    /// The total number of (non-read-only) lookups
+    pub total_visits: u64,
+    /// The most-visited indices, as `(index, visits)` pairs in descending order of visits
+    pub hotspots: Vec<(usize, u64)>,
+    /// A histogram of how often indices have been visited: bucket `0` counts the indices that were never visited,
+    /// and bucket `k > 0` counts the indices visited between `2^(k-1)` and `2^k - 1` times
+    pub visit_histogram: Vec<usize>,
+}
+
+impl Audit {
+    fn to_json_value(&self) -> Json {
+        Json::object(vec![
+            ("config", Json::object(vec![("size", Json::from(self.size))])),
+            ("count", Json::from(self.count)),
+            ("fill", Json::from(self.fill)),
+            ("unused", Json::from(self.unused)),
+            ("collisions", Json::from(self.collisions)),
+            ("total_visits", Json::from(self.total_visits)),
+            (
+                "hotspots",
+                Json::Array(
+                    self.hotspots
+                        .iter()
+                        .map(|&(index, visits)| Json::object(vec![("index", Json::from(index)), ("visits", Json::from(visits))]))
+                        .collect(),
+                ),
+            ),
+            ("visit_histogram", Json::from(self.visit_histogram.clone())),
+        ])
+    }
+
+    /// Serialize the report as a single-line JSON object
+    pub fn to_json(&self) -> String {
+        self.to_json_value().to_string()
+    }
+}
+
+impl IHT {
+    /// Produce a report of the fill, collisions, hotspot indices, visit distribution, and unused capacity of the IHT.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(16);
+    /// iht.tiles(4, &[0.0], None);
+    /// iht.tiles(4, &[0.0], None);
+    ///
+    /// let audit = iht.audit();
+    /// assert_eq!(audit.count, 4);
+    /// assert_eq!(audit.unused, 12);
+    /// assert_eq!(audit.hotspots[0], (0, 2));
+    /// assert!(audit.to_json().starts_with(r#"{"config":{"size":16},"count":4,"fill":0.25,"#));
+    /// ```
+    pub fn audit(&self) -> Audit {
+        let mut hotspots: Vec<(usize, u64)> = self.visits.iter().copied().enumerate().filter(|&(_, v)| v > 0).collect();
+        hotspots.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hotspots.truncate(HOTSPOTS);
+
+        let mut visit_histogram = vec![0usize];
+        for index in 0..self.size {
+            let bucket = match self.visits(index) {
+                0 => 0,
+                v => 64 - v.leading_zeros() as usize,
+            };
+            if bucket >= visit_histogram.len() {
+                visit_histogram.resize(bucket + 1, 0);
+            }
+            visit_histogram[bucket] += 1;
+        }
+
+        Audit {
+            size: self.size,
+            count: self.count(),
+            fill: if self.size == 0 { 0.0 } else { self.count() as f64 / self.size as f64 },
+            unused: self.size.saturating_sub(self.count()),
+            collisions: self.overfull_count,
+            total_visits: self.visits.iter().sum(),
+            hotspots,
+            visit_histogram,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_by_powers_of_two() {
+        let mut iht = IHT::new(8);
+        for _ in 0..3 {
+            iht.tiles(1, &[0.0], None);
+        }
+        iht.tiles(1, &[5.0], None);
+        let audit = iht.audit();
+        assert_eq!(audit.visit_histogram, vec![6, 1, 1]);
+        assert_eq!(audit.total_visits, 4);
+        assert_eq!(audit.hotspots, vec![(0, 3), (1, 1)]);
+    }
+
+    #[test]
+    fn collisions_are_reported() {
+        let mut iht = IHT::new(4);
+        for i in 0..8 {
+            iht.tiles(1, &[i as f64], None);
+        }
+        let audit = iht.audit();
+        assert_eq!(audit.fill, 1.0);
+        assert_eq!(audit.unused, 0);
+        assert_eq!(audit.collisions, 4);
+    }
+}
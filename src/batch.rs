@@ -0,0 +1,138 @@
+use std::io::{self, Write};
+
+use crate::{calculate_coords_into, calculate_coords_wrap_into, IHT};
+
+// quantize a point into a buffer which is reused from point to point
+fn quantize_into(q_floats: &mut Vec<isize>, floats: &[f64], num_tilings: usize) {
+    q_floats.clear();
+    q_floats.extend(floats.iter().map(|&x| (x * num_tilings as f64).floor() as isize));
+}
+
+impl IHT {
+    /// Encode many points at once, e.g. a whole replay buffer or dataset, with the same ints for every point. The
+    /// result is a row-major matrix with `num_tilings` indices per point: the indices of `points[i]` are
+    /// `result[i * num_tilings..(i + 1) * num_tilings]`, exactly as `tiles` would have returned them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// let points: [&[f64]; 3] = [&[0.0, 0.0], &[0.5, 0.0], &[10.0, 3.0]];
+    /// let batch = iht.tiles_batch(4, &points, None);
+    /// assert_eq!(batch.len(), 12);
+    ///
+    /// let rows: Vec<&[usize]> = batch.chunks(4).collect();
+    /// assert_eq!(rows[1], &iht.tiles(4, &[0.5, 0.0], None)[..]);
+    /// ```
+    pub fn tiles_batch(&mut self, num_tilings: usize, points: &[&[f64]], ints: Option<&[isize]>) -> Vec<usize> {
+        let mut tiles = Vec::with_capacity(points.len() * num_tilings);
+        let mut q_floats = Vec::new();
+        let mut coords = Vec::new();
+        for floats in points {
+            quantize_into(&mut q_floats, floats, num_tilings);
+            for tiling in 0..num_tilings {
+                calculate_coords_into(&mut coords, tiling, num_tilings, &q_floats, &ints);
+                tiles.push(self.get_index_ref(&coords));
+            }
+        }
+        tiles
+    }
+
+    /// The same as `tiles_batch`, for points stored contiguously in row-major order with `dims` floats per point, as
+    /// in a standard-layout 2D array (e.g. the `as_slice()` of an ndarray `Array2` or a flattened dataset), so there's
+    /// no need to build a slice per point first. The result has the same `num_tilings`-per-point layout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// let data = [0.0, 0.0, 0.5, 0.0, 10.0, 3.0];
+    /// let batch = iht.tiles_batch_rows(4, &data, 2, None);
+    /// assert_eq!(batch, iht.tiles_batch(4, &[&[0.0, 0.0], &[0.5, 0.0], &[10.0, 3.0]], None));
+    /// ```
+    pub fn tiles_batch_rows(&mut self, num_tilings: usize, data: &[f64], dims: usize, ints: Option<&[isize]>) -> Vec<usize> {
+        assert!(dims > 0 && data.len().is_multiple_of(dims), "the data must hold a whole number of points");
+        let points: Vec<&[f64]> = data.chunks(dims).collect();
+        self.tiles_batch(num_tilings, &points, ints)
+    }
+
+    /// The wrap-around version of `tiles_batch`
+    pub fn tiles_wrap_batch(&mut self, num_tilings: usize, points: &[&[f64]], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<usize> {
+        let mut tiles = Vec::with_capacity(points.len() * num_tilings);
+        let mut q_floats = Vec::new();
+        let mut coords = Vec::new();
+        for floats in points {
+            quantize_into(&mut q_floats, floats, num_tilings);
+            for tiling in 0..num_tilings {
+                calculate_coords_wrap_into(&mut coords, tiling, num_tilings, &q_floats, wrap_widths, &ints);
+                tiles.push(self.get_index_ref(&coords));
+            }
+        }
+        tiles
+    }
+
+    /// The read-only version of `tiles_batch`
+    pub fn tiles_batch_read_only(&self, num_tilings: usize, points: &[&[f64]], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let mut tiles = Vec::with_capacity(points.len() * num_tilings);
+        let mut q_floats = Vec::new();
+        let mut coords = Vec::new();
+        for floats in points {
+            quantize_into(&mut q_floats, floats, num_tilings);
+            for tiling in 0..num_tilings {
+                calculate_coords_into(&mut coords, tiling, num_tilings, &q_floats, &ints);
+                tiles.push(self.get_index_read_only_ref(&coords));
+            }
+        }
+        tiles
+    }
+}
+
+/// Write a batch of encodings (as returned by [`IHT::tiles_batch`]) in long format, as CSV with a header and one
+/// `sample,tiling,index` row per tile, which columnar tools (pandas, Spark, DuckDB, ...) can load directly and
+/// convert to Arrow or Parquet.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{write_batch_long, IHT};
+/// let mut iht = IHT::new(1024);
+/// let batch = iht.tiles_batch(2, &[&[0.0], &[10.0]], None);
+///
+/// let mut csv = Vec::new();
+/// write_batch_long(&mut csv, &batch, 2).unwrap();
+/// assert_eq!(String::from_utf8(csv).unwrap(), "sample,tiling,index\n0,0,0\n0,1,1\n1,0,2\n1,1,3\n");
+/// ```
+pub fn write_batch_long<W: Write>(mut writer: W, batch: &[usize], num_tilings: usize) -> io::Result<()> {
+    assert!(num_tilings > 0 && batch.len().is_multiple_of(num_tilings), "the batch must hold a whole number of points");
+    writeln!(writer, "sample,tiling,index")?;
+    for (sample, tiles) in batch.chunks(num_tilings).enumerate() {
+        for (tiling, index) in tiles.iter().enumerate() {
+            writeln!(writer, "{},{},{}", sample, tiling, index)?;
+        }
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_matches_point_by_point() {
+        let points: Vec<Vec<f64>> = (0..50).map(|i| vec![i as f64 * 0.37, -(i as f64) * 0.11]).collect();
+        let refs: Vec<&[f64]> = points.iter().map(|p| &p[..]).collect();
+
+        let mut batched = IHT::new(256);
+        let mut single = IHT::new(256);
+        let batch = batched.tiles_wrap_batch(8, &refs, &[Some(5), None], Some(&[2]));
+        let expected: Vec<usize> = points.iter().flat_map(|p| single.tiles_wrap(8, p, &[Some(5), None], Some(&[2]))).collect();
+        assert_eq!(batch, expected);
+        assert_eq!(batched, single);
+
+        let mut iht = IHT::new(1024);
+        let batch = iht.tiles_batch(8, &refs, None);
+        assert_eq!(iht.tiles_batch_read_only(8, &refs, None), batch.into_iter().map(Some).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,202 @@
+//! `tilecode`: tile code the rows of a CSV file.
+//!
+//! Reads a CSV file (with a header row) from a path or stdin, encodes its float (and optional int) columns with a
+//! coder configured by a TOML file or by flags, and writes the tile indices of each row to stdout as CSV or JSON
+//! lines. Build it with `cargo install tilecoding --features cli`.
+
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::process;
+
+use tilecoding::{CoderConfig, DimensionConfig, TileCoder};
+
+const USAGE: &str = "usage: tilecode [OPTIONS] [INPUT]
+
+Tile code the rows of a CSV file (INPUT, or stdin), writing the tile indices of each row to stdout.
+
+options:
+  -c, --config FILE               the coder configuration, as TOML
+  -n, --tilings N                 the number of tilings, without a configuration file (default 8)
+  -s, --size N                    the size of the table, without a configuration file (default 4096)
+  -d, --dim MIN:MAX:TILES[:FLAG]  a float dimension, without a configuration file; FLAG is `wrap` or `clamp`
+      --floats COLUMNS            the comma-separated float columns, one per dimension (default: the names of the
+                                  dimensions if they all have one, otherwise the first columns)
+      --ints COLUMNS              comma-separated int columns to include in every tile
+  -f, --format FORMAT             `csv` (default) or `jsonl`
+  -h, --help                      print this message";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Csv,
+    Jsonl,
+}
+
+struct Options {
+    config: Option<String>,
+    num_tilings: usize,
+    size: usize,
+    dims: Vec<DimensionConfig>,
+    floats: Option<Vec<String>>,
+    ints: Vec<String>,
+    format: Format,
+    input: Option<String>,
+}
+
+fn parse_dim(spec: &str) -> Result<DimensionConfig, String> {
+    let parts: Vec<&str> = spec.split(':').collect();
+    if parts.len() < 3 || parts.len() > 4 {
+        return Err(format!("invalid dimension `{}` (expected MIN:MAX:TILES[:wrap|clamp])", spec));
+    }
+    let number = |s: &str| s.parse::<f64>().map_err(|_| format!("invalid number `{}` in dimension `{}`", s, spec));
+    let tiles = parts[2].parse::<usize>().map_err(|_| format!("invalid tile count `{}` in dimension `{}`", parts[2], spec))?;
+    let mut dim = DimensionConfig::new(number(parts[0])?, number(parts[1])?, tiles);
+    match parts.get(3) {
+        None => {}
+        Some(&"wrap") => dim.wrap = true,
+        Some(&"clamp") => dim.clamp = true,
+        Some(flag) => return Err(format!("unknown flag `{}` in dimension `{}` (expected `wrap` or `clamp`)", flag, spec)),
+    }
+    Ok(dim)
+}
+
+fn columns(list: &str) -> Vec<String> {
+    list.split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect()
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        config: None,
+        num_tilings: 8,
+        size: 4096,
+        dims: Vec::new(),
+        floats: None,
+        ints: Vec::new(),
+        format: Format::Csv,
+        input: None,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("`{}` needs a value", name));
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            "-c" | "--config" => options.config = Some(value(&arg)?),
+            "-n" | "--tilings" => options.num_tilings = value(&arg)?.parse().map_err(|_| "`--tilings` must be a positive integer".to_string())?,
+            "-s" | "--size" => options.size = value(&arg)?.parse().map_err(|_| "`--size` must be a positive integer".to_string())?,
+            "-d" | "--dim" => options.dims.push(parse_dim(&value(&arg)?)?),
+            "--floats" => options.floats = Some(columns(&value(&arg)?)),
+            "--ints" => options.ints = columns(&value(&arg)?),
+            "-f" | "--format" => {
+                options.format = match value(&arg)?.as_str() {
+                    "csv" => Format::Csv,
+                    "jsonl" => Format::Jsonl,
+                    format => return Err(format!("unknown format `{}` (expected `csv` or `jsonl`)", format)),
+                }
+            }
+            _ if arg.starts_with('-') && arg != "-" => return Err(format!("unknown option `{}`", arg)),
+            _ if options.input.is_some() => return Err("only one input file can be given".to_string()),
+            _ => options.input = Some(arg),
+        }
+    }
+    Ok(options)
+}
+
+fn load_config(options: &Options) -> Result<CoderConfig, String> {
+    match &options.config {
+        Some(path) => {
+            if !options.dims.is_empty() {
+                return Err("`--dim` can't be combined with `--config`".to_string());
+            }
+            let source = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+            CoderConfig::from_toml(&source).map_err(|e| format!("{}: {}", path, e))
+        }
+        None => CoderConfig::new(options.num_tilings, options.size, options.dims.clone()).map_err(|e| e.to_string()),
+    }
+}
+
+fn split(line: &str) -> Vec<&str> {
+    line.split(',').map(str::trim).collect()
+}
+
+fn find(header: &[&str], column: &str) -> Result<usize, String> {
+    header.iter().position(|&c| c == column).ok_or_else(|| format!("no column named `{}` in the header", column))
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let config = load_config(&options)?;
+    let mut coder = TileCoder::from_config(&config);
+
+    let stdin = io::stdin();
+    let input: Box<dyn BufRead> = match options.input.as_deref() {
+        None | Some("-") => Box::new(stdin.lock()),
+        Some(path) => Box::new(io::BufReader::new(fs::File::open(path).map_err(|e| format!("{}: {}", path, e))?)),
+    };
+    let mut lines = input.lines().enumerate();
+    let header = match lines.next() {
+        Some((_, line)) => line.map_err(|e| e.to_string())?,
+        None => return Ok(()),
+    };
+    let header = split(&header);
+
+    let dims = config.dimensions();
+    let float_columns: Vec<usize> = match &options.floats {
+        Some(names) => names.iter().map(|name| find(&header, name)).collect::<Result<_, _>>()?,
+        None => match dims.iter().map(|d| d.name.as_deref()).collect::<Option<Vec<&str>>>() {
+            Some(names) => names.iter().map(|name| find(&header, name)).collect::<Result<_, _>>()?,
+            None => (0..dims.len()).collect(),
+        },
+    };
+    if float_columns.len() != dims.len() {
+        return Err(format!("{} float columns were given for {} dimensions", float_columns.len(), dims.len()));
+    }
+    let int_columns: Vec<usize> = options.ints.iter().map(|name| find(&header, name)).collect::<Result<_, _>>()?;
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let write_error = |e: io::Error| e.to_string();
+    if options.format == Format::Csv {
+        let names: Vec<String> = (0..config.num_tilings()).map(|t| format!("tile{}", t)).collect();
+        writeln!(out, "{}", names.join(",")).map_err(write_error)?;
+    }
+
+    let mut floats = vec![0.0; float_columns.len()];
+    let mut ints = vec![0; int_columns.len()];
+    let mut rows = 0;
+    for (i, line) in lines {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let row = split(&line);
+        let cell = |column: usize| row.get(column).copied().ok_or_else(|| format!("line {}: expected at least {} columns", i + 1, column + 1));
+        for (x, &column) in floats.iter_mut().zip(float_columns.iter()) {
+            let text = cell(column)?;
+            *x = text.parse().map_err(|_| format!("line {}: `{}` isn't a number (column `{}`)", i + 1, text, header[column]))?;
+        }
+        for (x, &column) in ints.iter_mut().zip(int_columns.iter()) {
+            let text = cell(column)?;
+            *x = text.parse().map_err(|_| format!("line {}: `{}` isn't an integer (column `{}`)", i + 1, text, header[column]))?;
+        }
+
+        let tiles = coder.tiles(&floats, if ints.is_empty() { None } else { Some(&ints) });
+        let tiles: Vec<String> = tiles.iter().map(|t| t.to_string()).collect();
+        match options.format {
+            Format::Csv => writeln!(out, "{}", tiles.join(",")),
+            Format::Jsonl => writeln!(out, "{{\"row\":{},\"tiles\":[{}]}}", rows, tiles.join(",")),
+        }
+        .map_err(write_error)?;
+        rows += 1;
+    }
+    out.flush().map_err(write_error)
+}
+
+fn main() {
+    let result = parse_args(env::args().skip(1)).and_then(run);
+    if let Err(message) = result {
+        eprintln!("tilecode: {}", message);
+        eprintln!("run `tilecode --help` for usage");
+        process::exit(1);
+    }
+}
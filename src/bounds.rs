@@ -0,0 +1,236 @@
+use std::io::{self, Write};
+
+use crate::json::Json;
+use crate::{calculate_coords, calculate_q_floats, IHT};
+
+// the range of `q + b` values (inclusive) which produce coordinate `c`, given that coordinates are
+// computed with rust's truncating integer division as `(q + b) / num_tilings`
+fn offset_range(c: isize, num_tilings: isize) -> (isize, isize) {
+    if c > 0 {
+        (c * num_tilings, c * num_tilings + num_tilings - 1)
+    } else if c < 0 {
+        ((c - 1) * num_tilings + 1, c * num_tilings)
+    } else {
+        (1 - num_tilings, num_tilings - 1)
+    }
+}
+
+/// Map the coordinates of a tile (as returned by [`IHT::coords_for`]) back to the hyperrectangle of raw
+/// input values it covers. Only applies to tiles produced by the non-wrapping `tiles` functions.
+///
+/// # Arguments
+///
+/// * `num_tilings`—the number of tilings the tile was generated with
+/// * `coords`—the tile coordinates, laid out as `[tiling, float coordinates..., ints...]`
+/// * `scales`—for each float, the factor the raw input was multiplied by before being tiled (use `1.0` for unscaled inputs)
+///
+/// # Return Value
+///
+/// One `(low, high)` pair per float, in raw (unscaled) input units. A raw value `x` falls in the tile when `low <= x < high`.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{tile_bounds, IHT};
+/// let mut iht = IHT::new(1024);
+/// // position scaled to 10 tiles per unit
+/// let indices = iht.tiles(4, &[0.53 * 10.0], None);
+///
+/// let coords = iht.coords_for(indices[1]).unwrap();
+/// let bounds = tile_bounds(4, coords, &[10.0]);
+/// assert_eq!(bounds, vec![(0.475, 0.575)]);
+/// ```
+pub fn tile_bounds(num_tilings: usize, coords: &[isize], scales: &[f64]) -> Vec<(f64, f64)> {
+    assert!(coords.len() > scales.len(), "there must be a tiling coordinate and one coordinate per scale");
+    let n = num_tilings as isize;
+    let tiling = coords[0];
+    let mut b = tiling;
+
+    coords[1..]
+        .iter()
+        .zip(scales.iter())
+        .map(|(&c, &scale)| {
+            let (low, high) = offset_range(c, n);
+            let width = scale * num_tilings as f64;
+            let bounds = ((low - b) as f64 / width, (high - b + 1) as f64 / width);
+            b += tiling * 2;
+            bounds
+        })
+        .collect()
+}
+
+impl IHT {
+    /// Find the raw input region covered by the tile that was assigned `index`, see [`tile_bounds`]. Returns `None`
+    /// if no tile has been assigned `index`.
+    pub fn tile_bounds(&self, index: usize, num_tilings: usize, scales: &[f64]) -> Option<Vec<(f64, f64)>> {
+        self.coords_for(index).map(|coords| tile_bounds(num_tilings, coords, scales))
+    }
+}
+
+/// A tile of one tiling clipped to a region, see [`tile_rectangles`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileRect {
+    /// The tile's coordinates, laid out as `[tiling, float coordinates...]`
+    pub coords: Vec<isize>,
+    /// One `(low, high)` pair per float, in raw input units, clipped to the region
+    pub bounds: Vec<(f64, f64)>,
+}
+
+impl TileRect {
+    /// The tiling the tile belongs to
+    pub fn tiling(&self) -> usize {
+        self.coords[0] as usize
+    }
+}
+
+/// Enumerate every tile of every tiling which overlaps the region given by `ranges` (one raw `(min, max)` per float),
+/// as rectangles (hyperrectangles, beyond two floats) clipped to the region, e.g. to overlay the tilings on a plot.
+/// As with [`tile_bounds`], `scales` gives the factor each raw float is multiplied by before being tiled, and only
+/// the non-wrapping `tiles` functions are described.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::tile_rectangles;
+/// let rects = tile_rectangles(2, &[(0.0, 1.0), (0.0, 0.5)], &[2.0, 2.0]);
+/// // tiling 0 lines up with the region; tiling 1 is offset by a quarter of a tile (1/8 in raw units), so it
+/// // has slivers along two edges
+/// assert_eq!(rects.iter().filter(|r| r.tiling() == 0).count(), 2);
+/// assert_eq!(rects.iter().filter(|r| r.tiling() == 1).count(), 6);
+/// assert_eq!(rects[0].bounds, vec![(0.0, 0.5), (0.0, 0.5)]);
+/// ```
+pub fn tile_rectangles(num_tilings: usize, ranges: &[(f64, f64)], scales: &[f64]) -> Vec<TileRect> {
+    assert_eq!(ranges.len(), scales.len(), "expected one scale per range");
+    assert!(ranges.iter().all(|&(lo, hi)| lo < hi), "ranges must have min < max");
+    let lows: Vec<f64> = ranges.iter().zip(scales.iter()).map(|(&(lo, _), &s)| lo * s).collect();
+    let highs: Vec<f64> = ranges.iter().zip(scales.iter()).map(|(&(_, hi), &s)| hi * s).collect();
+    let (q_lows, q_highs) = (calculate_q_floats(&lows, num_tilings), calculate_q_floats(&highs, num_tilings));
+
+    let mut rects = Vec::new();
+    for tiling in 0..num_tilings {
+        let first = calculate_coords(tiling, num_tilings, &q_lows, &None);
+        let last = calculate_coords(tiling, num_tilings, &q_highs, &None);
+        // step through the coordinates in every dimension, like an odometer
+        let mut coords = first.clone();
+        loop {
+            let bounds: Vec<(f64, f64)> = tile_bounds(num_tilings, &coords, scales)
+                .into_iter()
+                .zip(ranges.iter())
+                .map(|((low, high), &(lo, hi))| (low.max(lo), high.min(hi)))
+                .collect();
+            if bounds.iter().all(|&(low, high)| low < high) {
+                rects.push(TileRect { coords: coords.clone(), bounds });
+            }
+            let mut dim = coords.len() - 1;
+            while dim > 0 && coords[dim] == last[dim] {
+                coords[dim] = first[dim];
+                dim -= 1;
+            }
+            if dim == 0 {
+                break;
+            }
+            coords[dim] += 1;
+        }
+    }
+    rects
+}
+
+/// Write tile rectangles as CSV, with a header: the tiling, then each float's coordinate, then each float's low and
+/// high bound, e.g. `tiling,c0,c1,low0,high0,low1,high1`
+pub fn write_tile_rectangles_csv<W: Write>(mut writer: W, rects: &[TileRect]) -> io::Result<()> {
+    let dims = rects.first().map_or(0, |r| r.bounds.len());
+    let mut header = vec!["tiling".to_string()];
+    header.extend((0..dims).map(|i| format!("c{}", i)));
+    header.extend((0..dims).flat_map(|i| vec![format!("low{}", i), format!("high{}", i)]));
+    writeln!(writer, "{}", header.join(","))?;
+    for rect in rects {
+        let mut row: Vec<String> = rect.coords.iter().map(|c| c.to_string()).collect();
+        row.extend(rect.bounds.iter().flat_map(|&(low, high)| vec![low.to_string(), high.to_string()]));
+        writeln!(writer, "{}", row.join(","))?;
+    }
+    Ok(())
+}
+
+/// Describe two dimensional tile rectangles as a GeoJSON `FeatureCollection` of polygons, with the tiling and
+/// coordinates of each tile as its properties, for tools which read GeoJSON
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{tile_rectangles, tile_rectangles_geojson};
+/// let geojson = tile_rectangles_geojson(&tile_rectangles(1, &[(0.0, 1.0), (0.0, 1.0)], &[1.0, 1.0]));
+/// assert!(geojson.contains(r#""coordinates":[[[0.0,0.0],[1.0,0.0],[1.0,1.0],[0.0,1.0],[0.0,0.0]]]"#));
+/// ```
+pub fn tile_rectangles_geojson(rects: &[TileRect]) -> String {
+    let features = rects
+        .iter()
+        .map(|rect| {
+            assert_eq!(rect.bounds.len(), 2, "only two dimensional tiles can be written as GeoJSON");
+            let ((x0, x1), (y0, y1)) = (rect.bounds[0], rect.bounds[1]);
+            let ring: Vec<Json> = [(x0, y0), (x1, y0), (x1, y1), (x0, y1), (x0, y0)].iter().map(|&(x, y)| Json::from(vec![x, y])).collect();
+            Json::object(vec![
+                ("type", Json::from("Feature")),
+                ("geometry", Json::object(vec![("type", Json::from("Polygon")), ("coordinates", Json::Array(vec![Json::Array(ring)]))])),
+                (
+                    "properties",
+                    Json::object(vec![("tiling", Json::from(rect.tiling())), ("coords", Json::from(rect.coords[1..].to_vec()))]),
+                ),
+            ])
+        })
+        .collect();
+    Json::object(vec![("type", Json::from("FeatureCollection")), ("features", Json::Array(features))]).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_contain_the_point() {
+        let mut iht = IHT::new(256);
+        let point = [0.37, -2.81];
+        let scales = [4.0, 0.5];
+        let indices = iht.tiles(8, &[point[0] * scales[0], point[1] * scales[1]], None);
+        for &index in indices.iter() {
+            let bounds = iht.tile_bounds(index, 8, &scales).unwrap();
+            for (&x, &(low, high)) in point.iter().zip(bounds.iter()) {
+                assert!(low <= x && x < high, "{} not in [{}, {})", x, low, high);
+            }
+        }
+    }
+
+    #[test]
+    fn bounds_are_tight() {
+        // each tile edge should be exactly where the tiling changes
+        let mut iht = IHT::new(256);
+        let index = iht.tiles(4, &[2.3], None)[2];
+        let (low, high) = iht.tile_bounds(index, 4, &[1.0]).unwrap()[0];
+        assert_eq!(iht.tiles(4, &[low], None)[2], index);
+        assert_ne!(iht.tiles(4, &[low - 1e-9], None)[2], index);
+        assert_ne!(iht.tiles(4, &[high], None)[2], index);
+    }
+
+    #[test]
+    fn rectangles_cover_the_region_once_per_tiling() {
+        let ranges = [(-0.7, 1.3), (2.0, 2.9)];
+        let scales = [3.0, 2.0];
+        let rects = tile_rectangles(4, &ranges, &scales);
+        for tiling in 0..4 {
+            let area: f64 = rects.iter().filter(|r| r.tiling() == tiling).map(|r| (r.bounds[0].1 - r.bounds[0].0) * (r.bounds[1].1 - r.bounds[1].0)).sum();
+            assert!((area - 2.0 * 0.9).abs() < 1e-9);
+        }
+        // each rectangle is the tile the point at its centre is tiled into
+        let mut iht = IHT::new(4096);
+        for rect in rects.iter() {
+            let centre: Vec<f64> = rect.bounds.iter().zip(scales.iter()).map(|(&(low, high), s)| (low + high) / 2.0 * s).collect();
+            let index = iht.tiles(4, &centre, None)[rect.tiling()];
+            assert_eq!(iht.coords_for(index), Some(&rect.coords[..]));
+        }
+
+        let mut csv = Vec::new();
+        write_tile_rectangles_csv(&mut csv, &rects[..1]).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().next(), Some("tiling,c0,c1,low0,high0,low1,high1"));
+        assert_eq!(csv.lines().count(), 2);
+    }
+}
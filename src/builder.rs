@@ -0,0 +1,207 @@
+use std::fmt;
+
+use crate::estimate::reachable_dims;
+use crate::{Dim, Offsets, TileCoder};
+
+/// A departure from Sutton's guidance on choosing tilings, found by [`TileCoderBuilder::diagnose`]. Each one is
+/// legal, but has consequences which are otherwise only discovered by watching a learner alias states; the `Display`
+/// message explains them.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Diagnostic {
+    /// The number of tilings isn't a power of two, so the tiles3 displacement repeats offsets
+    TilingsNotPowerOfTwo {
+        /// The number of tilings
+        num_tilings: usize,
+    },
+    /// There are fewer than 4 tilings per float dimension
+    TooFewTilings {
+        /// The number of tilings
+        num_tilings: usize,
+        /// `4 * dims`, the fewest tilings recommended
+        recommended: usize,
+    },
+    /// The IHT is too small to hold every tile reachable within the dimensions' ranges
+    SizeTooSmall {
+        /// The size of the IHT
+        size: usize,
+        /// The number of tiles reachable within the ranges, see [`TileCoder::reachable_tiles`]
+        reachable: usize,
+    },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Diagnostic::TilingsNotPowerOfTwo { num_tilings } => write!(
+                f,
+                "{} tilings is not a power of two: the tiles3 displacement (1, 3, 5, ...) then repeats offsets in some \
+                 dimensions, so fewer distinct tilings cover them than it seems; use a power of two or `Offsets::Coprime`",
+                num_tilings
+            ),
+            Diagnostic::TooFewTilings { num_tilings, recommended } => write!(
+                f,
+                "{} tilings is fewer than the recommended {} (4 per float dimension): the tilings are offset too coarsely \
+                 to resolve points within a tile, and generalization is blocky along the diagonals",
+                num_tilings, recommended
+            ),
+            Diagnostic::SizeTooSmall { size, reachable } => write!(
+                f,
+                "an IHT of size {} can't hold the {} tiles reachable within the dimensions' ranges: once it is full, new \
+                 tiles collide and unrelated states share weights",
+                size, reachable
+            ),
+        }
+    }
+}
+
+/// A builder for [`TileCoder`]s, for when it's clearer to describe the coder piece by piece (or to start from one of
+/// the [`presets`](crate::presets) and adjust it) than to call `TileCoder::new` directly.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{Dim, TileCoder};
+/// let mut coder = TileCoder::builder()
+///     .num_tilings(4)
+///     .size(1024)
+///     .dim(Dim::bins(-1.0, 1.0, 10))
+///     .dim(Dim::wrapping(0.0, 360.0, 12))
+///     .build();
+/// assert_eq!(coder.tiles(&[0.2, 90.0], None), vec![0, 1, 2, 3]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileCoderBuilder {
+    num_tilings: usize,
+    size: usize,
+    dims: Vec<Dim>,
+    offsets: Offsets,
+}
+
+impl Default for TileCoderBuilder {
+    fn default() -> TileCoderBuilder {
+        TileCoderBuilder::new()
+    }
+}
+
+impl TileCoderBuilder {
+    /// Start a builder with 8 tilings, an IHT of size 4096, the tiles3 offsets, and no dimensions
+    pub fn new() -> TileCoderBuilder {
+        TileCoderBuilder {
+            num_tilings: 8,
+            size: 4096,
+            dims: Vec::new(),
+            offsets: Offsets::Tiles3,
+        }
+    }
+
+    /// Set the number of tilings
+    pub fn num_tilings(mut self, num_tilings: usize) -> TileCoderBuilder {
+        self.num_tilings = num_tilings;
+        self
+    }
+
+    /// Set the size of the IHT
+    pub fn size(mut self, size: usize) -> TileCoderBuilder {
+        self.size = size;
+        self
+    }
+
+    /// Append a float dimension
+    pub fn dim(mut self, dim: Dim) -> TileCoderBuilder {
+        self.dims.push(dim);
+        self
+    }
+
+    /// Replace every float dimension
+    pub fn dims(mut self, dims: Vec<Dim>) -> TileCoderBuilder {
+        self.dims = dims;
+        self
+    }
+
+    /// Offset the tilings using the given scheme instead of the tiles3 displacement
+    pub fn offsets(mut self, offsets: Offsets) -> TileCoderBuilder {
+        self.offsets = offsets;
+        self
+    }
+
+    /// Check the coder against Sutton's guidance: a power of two tilings (for the tiles3 offsets), at least 4 tilings
+    /// per float dimension, and an IHT large enough for every tile within the dimensions' ranges (when they all have
+    /// one). An empty list means the coder follows all of it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{Diagnostic, Dim, TileCoder};
+    /// let builder = TileCoder::builder().num_tilings(6).size(64).dim(Dim::bins(0.0, 1.0, 10)).dim(Dim::bins(0.0, 1.0, 10));
+    /// let diagnostics = builder.diagnose();
+    /// assert_eq!(diagnostics[0], Diagnostic::TilingsNotPowerOfTwo { num_tilings: 6 });
+    /// assert_eq!(diagnostics[1], Diagnostic::TooFewTilings { num_tilings: 6, recommended: 8 });
+    /// assert!(matches!(diagnostics[2], Diagnostic::SizeTooSmall { size: 64, .. }));
+    ///
+    /// assert!(builder.num_tilings(8).size(1024).diagnose().is_empty());
+    /// ```
+    pub fn diagnose(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        if self.offsets == Offsets::Tiles3 && !self.num_tilings.is_power_of_two() {
+            diagnostics.push(Diagnostic::TilingsNotPowerOfTwo { num_tilings: self.num_tilings });
+        }
+        if self.num_tilings < 4 * self.dims.len() {
+            diagnostics.push(Diagnostic::TooFewTilings {
+                num_tilings: self.num_tilings,
+                recommended: 4 * self.dims.len(),
+            });
+        }
+        if let Some(reachable) = reachable_dims(self.num_tilings, &self.offsets, &self.dims, 1) {
+            if reachable > self.size {
+                diagnostics.push(Diagnostic::SizeTooSmall { size: self.size, reachable });
+            }
+        }
+        diagnostics
+    }
+
+    /// Build the coder, with a fresh IHT
+    pub fn build(self) -> TileCoder {
+        TileCoder::new(self.num_tilings, self.size, self.dims).with_offsets(self.offsets)
+    }
+
+    /// Build the coder only if [`TileCoderBuilder::diagnose`] finds nothing, returning the diagnostics otherwise
+    pub fn build_checked(self) -> Result<TileCoder, Vec<Diagnostic>> {
+        let diagnostics = self.diagnose();
+        if diagnostics.is_empty() {
+            Ok(self.build())
+        } else {
+            Err(diagnostics)
+        }
+    }
+}
+
+impl TileCoder {
+    /// Start building a coder, see [`TileCoderBuilder`]
+    pub fn builder() -> TileCoderBuilder {
+        TileCoderBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_matches_constructor() {
+        let dims = vec![Dim::bins(0.0, 1.0, 4), Dim::width(0.5)];
+        let built = TileCoder::builder().num_tilings(4).size(256).dims(dims.clone()).build();
+        assert_eq!(built, TileCoder::new(4, 256, dims));
+    }
+
+    #[test]
+    fn diagnostics_follow_the_offsets_and_ranges() {
+        // coprime offsets don't need a power of two, and unbounded dimensions have no size to check
+        let builder = TileCoder::builder().num_tilings(12).size(16).offsets(Offsets::Coprime).dims(vec![Dim::width(0.1); 3]);
+        assert!(builder.clone().build_checked().is_ok());
+        let builder = builder.offsets(Offsets::Tiles3).dim(Dim::wrapping(0.0, 1.0, 4));
+        let diagnostics = builder.build_checked().unwrap_err();
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics[1].to_string().starts_with("12 tilings is fewer than the recommended 16"));
+    }
+}
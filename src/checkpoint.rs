@@ -0,0 +1,328 @@
+use std::fs;
+use std::path::Path;
+
+use crate::tag::{hash_fingerprint, Tag};
+use crate::{CoderConfig, Error, HashFunction, Overflow, IHT};
+
+const MAGIC: &[u8; 8] = b"TCCHKPNT";
+const VERSION: u64 = 3;
+
+// FNV-1a over the bytes of the checkpoint, appended to it so that a corrupt or truncated file is caught on load
+fn checksum(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+// reads little-endian words from the body of a checkpoint, failing cleanly if it runs out
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn u64(&mut self) -> Result<u64, Error> {
+        let word = self
+            .bytes
+            .get(self.offset..self.offset + 8)
+            .ok_or_else(|| Error::Format("checkpoint is truncated".to_string()))?;
+        self.offset += 8;
+        let mut b = [0; 8];
+        b.copy_from_slice(word);
+        Ok(u64::from_le_bytes(b))
+    }
+
+    // a length-prefixed string, padded with zeros to a whole number of words
+    fn string(&mut self) -> Result<String, Error> {
+        let len = self.u64()? as usize;
+        let padded = len.div_ceil(8).checked_mul(8).ok_or_else(|| Error::Format("checkpoint is truncated".to_string()))?;
+        let bytes = self
+            .bytes
+            .get(self.offset..)
+            .and_then(|rest| rest.get(..padded))
+            .ok_or_else(|| Error::Format("checkpoint is truncated".to_string()))?;
+        self.offset += padded;
+        String::from_utf8(bytes[..len].to_vec()).map_err(|_| Error::Format("checkpoint has an invalid string".to_string()))
+    }
+
+    fn len(&mut self, what: &str) -> Result<usize, Error> {
+        let len = self.u64()? as usize;
+        // every element takes at least a word, so a longer length can only come from a corrupt file
+        if len > (self.bytes.len() - self.offset) / 8 {
+            return Err(Error::Format(format!("checkpoint has an impossible {} length", what)));
+        }
+        Ok(len)
+    }
+}
+
+impl IHT {
+    // the checkpoint format: the magic and version, the size, overfull count, hash function and overflow strategy, the
+    // tag as (length of the crate version, crate version padded to whole words, hash fingerprint, config fingerprint),
+    // the entries sorted by index as (number of coordinates, coordinates..., index), the visit counts, and finally the
+    // checksum
+    fn checkpoint_bytes(&self, config: u64) -> Vec<u8> {
+        let crate_version = env!("CARGO_PKG_VERSION").as_bytes();
+        let mut words = vec![
+            VERSION,
+            self.size as u64,
+            self.overfull_count as u64,
+            match self.hash_function() {
+                HashFunction::Sip => 0,
+                HashFunction::Fx => 1,
+            },
+            match self.overflow {
+                Overflow::Hash => 0,
+                Overflow::DoubleHash => 1,
+            },
+            crate_version.len() as u64,
+        ];
+        for chunk in crate_version.chunks(8) {
+            let mut b = [0; 8];
+            b[..chunk.len()].copy_from_slice(chunk);
+            words.push(u64::from_le_bytes(b));
+        }
+        words.extend_from_slice(&[hash_fingerprint(self.hash_function()), config, self.dictionary.len() as u64]);
+        for (coords, index) in self.entries_snapshot() {
+            words.push(coords.len() as u64);
+            words.extend(coords.iter().map(|&c| c as i64 as u64));
+            words.push(index as u64);
+        }
+        words.push(self.visits.len() as u64);
+        words.extend_from_slice(&self.visits);
+
+        let mut bytes = Vec::with_capacity(8 * (words.len() + 2));
+        bytes.extend_from_slice(MAGIC);
+        for word in words {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        let sum = checksum(&bytes);
+        bytes.extend_from_slice(&sum.to_le_bytes());
+        bytes
+    }
+
+    fn from_checkpoint_bytes(bytes: &[u8]) -> Result<IHT, Error> {
+        IHT::from_tagged_checkpoint_bytes(bytes).map(|(iht, _)| iht)
+    }
+
+    fn from_tagged_checkpoint_bytes(bytes: &[u8]) -> Result<(IHT, Tag), Error> {
+        if bytes.len() < MAGIC.len() + 16 || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(Error::Format("not an IHT checkpoint".to_string()));
+        }
+        let (body, sum) = bytes.split_at(bytes.len() - 8);
+        let version = Reader { bytes: body, offset: MAGIC.len() }.u64()?;
+        if version != VERSION {
+            return Err(Error::Format(format!("unsupported checkpoint version {} (expected {})", version, VERSION)));
+        }
+        let expected = Reader { bytes: sum, offset: 0 }.u64()?;
+        if expected != checksum(body) {
+            return Err(Error::Format("checkpoint failed its checksum".to_string()));
+        }
+
+        let mut reader = Reader { bytes: body, offset: MAGIC.len() + 8 };
+        let size = reader.u64()? as usize;
+        let overfull_count = reader.u64()? as usize;
+        let function = match reader.u64()? {
+            0 => HashFunction::Sip,
+            1 => HashFunction::Fx,
+            other => return Err(Error::Format(format!("unknown hash function {}", other))),
+        };
+        let overflow = match reader.u64()? {
+            0 => Overflow::Hash,
+            1 => Overflow::DoubleHash,
+            other => return Err(Error::Format(format!("unknown overflow strategy {}", other))),
+        };
+        let tag = Tag {
+            crate_version: reader.string()?,
+            hash: reader.u64()?,
+            config: reader.u64()?,
+        };
+        tag.check_version("checkpoint")?;
+        // the overflow indices already handed out came from the saved hash function, so a table which has overflowed
+        // can only be used with the same one; a table which hasn't is unaffected
+        if tag.hash != hash_fingerprint(function) && overfull_count > 0 {
+            return Err(Error::Format(format!(
+                "checkpoint was written by tilecoding {} with a {:?} hash which differs from this build's, so its \
+                 overflowed tiles would get different indices",
+                tag.crate_version, function
+            )));
+        }
+        let mut iht = IHT::new(size).with_hash_function(function).with_overflow(overflow);
+        iht.overfull_count = overfull_count;
+        let count = reader.len("entry")?;
+        for expected in 0..count {
+            let len = reader.len("coordinate")?;
+            let coords = (0..len).map(|_| reader.u64().map(|c| c as i64 as isize)).collect::<Result<Vec<isize>, Error>>()?;
+            let index = reader.u64()? as usize;
+            if index != expected || index >= size {
+                return Err(Error::Format(format!("checkpoint entry has index {}, expected {}", index, expected)));
+            }
+            iht.dictionary.insert(coords, index);
+        }
+        let visits = reader.len("visit")?;
+        iht.visits = (0..visits).map(|_| reader.u64()).collect::<Result<Vec<u64>, Error>>()?;
+        if reader.offset != body.len() {
+            return Err(Error::Format("checkpoint has trailing bytes".to_string()));
+        }
+        Ok((iht, tag))
+    }
+
+    /// Save a checkpoint of the IHT to `path`, in a compact binary format which records its format version and
+    /// ends with a checksum. Everything is saved: the entries, the overfull and visit counts, the hash function, and
+    /// the overflow strategy.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let path = std::env::temp_dir().join("tilecoding-checkpoint-doctest");
+    /// let mut iht = IHT::new(1024);
+    /// iht.tiles(8, &[3.6, 7.21], None);
+    /// iht.save(&path).unwrap();
+    ///
+    /// assert_eq!(IHT::load(&path).unwrap(), iht);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        fs::write(path, self.checkpoint_bytes(0))?;
+        Ok(())
+    }
+
+    /// Load a checkpoint written by [`IHT::save`]. A file which isn't a checkpoint, was written by an unsupported
+    /// version, or fails its checksum is an `Error::Format` rather than a silently different table.
+    ///
+    /// Checkpoints record the version of this crate which wrote them and a fingerprint of their hash function. Loading
+    /// fails if they were written by a version with a different major or minor version, whose format may differ, or
+    /// if the table has had to hash tiles it had no room for and this build's hash function no longer gives the same
+    /// hashes, rather than handing those tiles new indices.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<IHT, Error> {
+        IHT::from_checkpoint_bytes(&fs::read(path)?)
+    }
+
+    /// The same as [`IHT::save`], but also recording the [`CoderConfig`] the table was filled with, so that
+    /// [`IHT::load_with_config`] can check it is loaded for the same one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{CoderConfig, DimensionConfig, IHT};
+    /// let path = std::env::temp_dir().join("tilecoding-config-checkpoint-doctest");
+    /// let config = CoderConfig::new(8, 1024, vec![DimensionConfig::new(-1.2, 0.6, 8)]).unwrap();
+    /// let mut iht = IHT::new(config.size());
+    /// iht.tiles(config.num_tilings(), &config.scale(&[-0.5]), None);
+    /// iht.save_with_config(&path, &config).unwrap();
+    ///
+    /// assert_eq!(IHT::load_with_config(&path, &config).unwrap(), iht);
+    /// let finer = CoderConfig::new(8, 1024, vec![DimensionConfig::new(-1.2, 0.6, 16)]).unwrap();
+    /// assert!(IHT::load_with_config(&path, &finer).is_err());
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn save_with_config<P: AsRef<Path>>(&self, path: P, config: &CoderConfig) -> Result<(), Error> {
+        fs::write(path, self.checkpoint_bytes(config.fingerprint()))?;
+        Ok(())
+    }
+
+    /// Load a checkpoint written by [`IHT::save_with_config`], failing with an `Error::Format` unless it was saved
+    /// for exactly the same configuration, ignoring the names of the dimensions
+    pub fn load_with_config<P: AsRef<Path>>(path: P, config: &CoderConfig) -> Result<IHT, Error> {
+        IHT::from_config_checkpoint_bytes(&fs::read(path)?, config)
+    }
+
+    fn from_config_checkpoint_bytes(bytes: &[u8], config: &CoderConfig) -> Result<IHT, Error> {
+        let (iht, tag) = IHT::from_tagged_checkpoint_bytes(bytes)?;
+        tag.check_config("checkpoint", config).map(|_| iht)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DimensionConfig;
+
+    // the checksum of a checkpoint edited by `edit`, recomputed so only the edit is checked
+    fn edited(bytes: &[u8], edit: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+        let mut bytes = bytes[..bytes.len() - 8].to_vec();
+        edit(&mut bytes);
+        let sum = checksum(&bytes);
+        bytes.extend_from_slice(&sum.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn round_trips_and_rejects_damage() {
+        let mut iht = IHT::new(16).with_hash_function(HashFunction::Fx).with_overflow(Overflow::DoubleHash);
+        for i in 0..6 {
+            iht.tiles(4, &[i as f64 * -0.8], Some(&[i]));
+        }
+        let bytes = iht.checkpoint_bytes(0);
+        let restored = IHT::from_checkpoint_bytes(&bytes).unwrap();
+        assert_eq!((restored.hash_function(), restored.overflow(), restored.overfull_count), (HashFunction::Fx, Overflow::DoubleHash, iht.overfull_count));
+        assert_eq!(restored.visits(3), iht.visits(3));
+        assert_eq!(restored, iht);
+
+        let mut flipped = bytes.clone();
+        flipped[40] ^= 1;
+        assert!(IHT::from_checkpoint_bytes(&flipped).is_err());
+        assert!(IHT::from_checkpoint_bytes(&bytes[..bytes.len() - 8]).is_err());
+        let mut future = bytes;
+        future[8] = 4;
+        assert_eq!(IHT::from_checkpoint_bytes(&future), Err(Error::Format("unsupported checkpoint version 4 (expected 3)".to_string())));
+        assert!(matches!(IHT::load("/nonexistent/checkpoint"), Err(Error::Io(_))));
+
+        // a checkpoint without a tag can't be read
+        let tag_len = 8 * (3 + env!("CARGO_PKG_VERSION").len().div_ceil(8));
+        let untagged = edited(&iht.checkpoint_bytes(0), |b| {
+            b.drain(48..48 + tag_len);
+            b[8] = 2;
+        });
+        assert!(IHT::from_checkpoint_bytes(&untagged).is_err());
+    }
+
+    #[test]
+    fn tags_check_the_hash_and_configuration() {
+        let config = CoderConfig::new(4, 8, vec![DimensionConfig::new(0.0, 1.0, 4)]).unwrap();
+        let mut iht = IHT::new(8);
+        iht.tiles(4, &config.scale(&[0.3]), None);
+        let bytes = iht.checkpoint_bytes(config.fingerprint());
+        assert_eq!(IHT::from_config_checkpoint_bytes(&bytes, &config).unwrap(), iht);
+        let mut named = config.dimensions().to_vec();
+        named[0].name = Some("x".to_string());
+        assert_eq!(CoderConfig::new(4, 8, named).unwrap().fingerprint(), config.fingerprint());
+        let wrapped = CoderConfig::new(4, 8, vec![DimensionConfig { wrap: true, ..DimensionConfig::new(0.0, 1.0, 4) }]).unwrap();
+        assert!(IHT::from_config_checkpoint_bytes(&bytes, &wrapped).is_err());
+        assert!(IHT::from_config_checkpoint_bytes(&iht.checkpoint_bytes(0), &config).is_err());
+
+        // a different hash only matters once the table has overflowed
+        let hash_at = 56 + 8 * env!("CARGO_PKG_VERSION").len().div_ceil(8);
+        let other_hash = |b: &mut Vec<u8>| b[hash_at] ^= 1;
+        assert!(IHT::from_checkpoint_bytes(&edited(&bytes, other_hash)).is_ok());
+        for x in &[0.6, 0.9] {
+            iht.tiles(4, &config.scale(&[*x]), None);
+        }
+        assert!(iht.overfull_count > 0);
+        let error = IHT::from_checkpoint_bytes(&edited(&iht.checkpoint_bytes(0), other_hash)).unwrap_err();
+        assert!(error.to_string().contains(concat!("tilecoding ", env!("CARGO_PKG_VERSION"))));
+
+        // a checkpoint from another minor release is rejected, but not one from another patch release
+        let version = env!("CARGO_PKG_VERSION");
+        let with_version = |other: &str| {
+            let retagged = edited(&bytes, |b| {
+                let mut field = (other.len() as u64).to_le_bytes().to_vec();
+                field.extend_from_slice(other.as_bytes());
+                field.resize(8 + 8 * other.len().div_ceil(8), 0);
+                b.splice(48..56 + 8 * version.len().div_ceil(8), field);
+            });
+            IHT::from_checkpoint_bytes(&retagged)
+        };
+        assert_eq!(with_version(version).unwrap(), IHT::from_checkpoint_bytes(&bytes).unwrap());
+        let (major, minor) = {
+            let mut parts = version.split('.');
+            (parts.next().unwrap(), parts.next().unwrap().parse::<u64>().unwrap())
+        };
+        assert!(with_version(&format!("{}.{}.12345", major, minor)).is_ok());
+        let error = with_version(&format!("{}.{}.0", major, minor + 1)).unwrap_err();
+        assert!(matches!(error, Error::Format(_)), "{}", error);
+    }
+}
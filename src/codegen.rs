@@ -0,0 +1,138 @@
+use std::fmt::Write;
+
+use crate::inline::InlineCoords;
+use crate::{calculate_coords_into, calculate_coords_wrap_into, calculate_q_floats, IHT};
+
+/// A read-only IHT compiled into a binary as a constant table, as generated by [`IHT::to_rust_source`]. The entries
+/// are sorted by their coordinates, and looked up with a binary search, so there is nothing to deserialize or
+/// allocate at startup.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::StaticIHT;
+/// // the output of `to_rust_source` for an IHT which has encoded 0.8 with 2 tilings
+/// pub static TRAINED: StaticIHT = StaticIHT::new(64, &[
+///     (&[0, 0], 0),
+///     (&[1, 1], 1),
+/// ]);
+///
+/// assert_eq!(TRAINED.tiles(2, &[0.8], None), vec![Some(0), Some(1)]);
+/// assert_eq!(TRAINED.tiles(2, &[5.0], None), vec![None, None]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct StaticIHT<'a> {
+    size: usize,
+    entries: &'a [(&'a [isize], usize)],
+}
+
+impl<'a> StaticIHT<'a> {
+    /// Wrap a table of `(coordinates, index)` entries, which must be sorted by their coordinates
+    pub const fn new(size: usize, entries: &'a [(&'a [isize], usize)]) -> StaticIHT<'a> {
+        StaticIHT { size, entries }
+    }
+
+    /// The size of the IHT the table was generated from
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of tiles in the table
+    pub fn count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// The index of the tile with the given coordinates, if it is in the table
+    pub fn get(&self, coords: &[isize]) -> Option<usize> {
+        self.entries
+            .binary_search_by(|(entry, _)| (*entry).cmp(coords))
+            .ok()
+            .map(|i| self.entries[i].1)
+    }
+
+    /// The equivalent of [`IHT::tiles_read_only`] on the compiled table
+    pub fn tiles(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut coords = InlineCoords::new();
+        (0..num_tilings)
+            .map(|tiling| {
+                calculate_coords_into(&mut coords, tiling, num_tilings, &q_floats, &ints);
+                self.get(&coords)
+            })
+            .collect()
+    }
+
+    /// The equivalent of [`IHT::tiles_wrap_read_only`] on the compiled table
+    pub fn tiles_wrap(&self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut coords = InlineCoords::new();
+        (0..num_tilings)
+            .map(|tiling| {
+                calculate_coords_wrap_into(&mut coords, tiling, num_tilings, &q_floats, wrap_widths, &ints);
+                self.get(&coords)
+            })
+            .collect()
+    }
+}
+
+impl IHT {
+    /// Generate Rust source declaring the table as a `pub static` [`StaticIHT`] called `name`, so that a trained coder
+    /// can be compiled straight into a binary, e.g. by writing the source out from a build script and `include!`ing
+    /// it. Only the dictionary is exported: the table is read-only, so the overfull and visit counts don't apply.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(64);
+    /// iht.tiles(2, &[0.8], None);
+    /// assert_eq!(
+    ///     iht.to_rust_source("TRAINED"),
+    ///     "pub static TRAINED: tilecoding::StaticIHT = tilecoding::StaticIHT::new(64, &[\n    (&[0, 0], 0),\n    (&[1, 1], 1),\n]);\n",
+    /// );
+    /// ```
+    pub fn to_rust_source(&self, name: &str) -> String {
+        let mut entries = self.entries_snapshot();
+        entries.sort();
+        let mut source = format!("pub static {}: tilecoding::StaticIHT = tilecoding::StaticIHT::new({}, &[\n", name, self.size);
+        for (coords, index) in entries {
+            let coords: Vec<String> = coords.iter().map(|c| c.to_string()).collect();
+            // writing to a string can't fail
+            let _ = writeln!(source, "    (&[{}], {}),", coords.join(", "), index);
+        }
+        source.push_str("]);\n");
+        source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the source `to_rust_source` generates for the table in the test below, pasted in as a user would include it
+    mod generated {
+        pub static TABLE: crate::StaticIHT = crate::StaticIHT::new(64, &[
+            (&[0, -1, 2], 0),
+            (&[0, 0, 2], 4),
+            (&[1, 0, 2], 1),
+            (&[2, 0, 2], 2),
+            (&[3, 0, 2], 3),
+            (&[3, 1, 2], 5),
+        ]);
+    }
+
+    #[test]
+    fn generated_source_matches_table() {
+        let mut iht = IHT::new(64);
+        iht.tiles(4, &[-0.9], Some(&[2]));
+        iht.tiles(4, &[0.3], Some(&[2]));
+        let source = iht.to_rust_source("TABLE");
+        assert!(source.starts_with("pub static TABLE: tilecoding::StaticIHT = tilecoding::StaticIHT::new(64, &[\n    (&[0, -1, 2], 0),\n"));
+        assert_eq!(source.lines().count(), 2 + iht.count());
+
+        for &x in &[-0.9, 0.3, -0.2, 3.0] {
+            assert_eq!(generated::TABLE.tiles(4, &[x], Some(&[2])), iht.tiles_read_only(4, &[x], Some(&[2])));
+        }
+        assert_eq!(generated::TABLE.count(), iht.count());
+    }
+}
@@ -0,0 +1,437 @@
+use crate::offsets::calculate_coords_offsets;
+use crate::{calculate_q_floats, CoderConfig, DimensionConfig, Offsets, IHT};
+
+/// How a single float dimension is scaled before being tiled by a [`TileCoder`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dim {
+    // raw values are mapped to tile units as `(x - origin) * scale`
+    pub(crate) origin: f64,
+    pub(crate) scale: f64,
+    pub(crate) wrap: Option<isize>,
+    // the declared `[min, max]` range, and whether raw values are clamped to it
+    pub(crate) range: Option<(f64, f64)>,
+    pub(crate) clamp: bool,
+}
+
+impl Dim {
+    /// A dimension with `tiles` tiles (per tiling) spanning `[min, max]`. Values outside of the range are still
+    /// tiled, they just land in tiles beyond the ends of the range.
+    pub fn bins(min: f64, max: f64, tiles: usize) -> Dim {
+        assert!(min < max, "dimension ranges must have min < max");
+        assert!(tiles > 0, "dimensions must have at least one tile");
+        Dim {
+            origin: min,
+            scale: tiles as f64 / (max - min),
+            wrap: None,
+            range: Some((min, max)),
+            clamp: false,
+        }
+    }
+
+    /// An unbounded dimension whose tiles are `width` wide, in the natural units of the dimension. This is the width
+    /// of generalization: away from the origin, points further apart than `width` never share a tile. As in
+    /// [`tiles`](crate::tiles), coordinates are divided towards zero, so the tile of each tiling which straddles the
+    /// origin is twice as wide, and points either side of it up to `2 * width` apart can share it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{Dim, TileCoder};
+    /// // generalize over 0.5 metres in x and 10 degrees in heading
+    /// let mut coder = TileCoder::new(8, 4096, vec![Dim::width(0.5), Dim::width(10.0)]);
+    /// let a = coder.tiles(&[1.0, 45.0], None);
+    /// let b = coder.tiles(&[1.6, 45.0], None);
+    /// assert!(a.iter().all(|i| !b.contains(i)));
+    /// ```
+    pub fn width(width: f64) -> Dim {
+        assert!(width > 0.0 && width.is_finite(), "tile widths must be positive and finite");
+        Dim {
+            origin: 0.0,
+            scale: 1.0 / width,
+            wrap: None,
+            range: None,
+            clamp: false,
+        }
+    }
+
+    /// A dimension with `tiles` tiles (per tiling) spanning `[min, max)`, which wraps around so that values just
+    /// below `max` generalize to values just above `min`
+    pub fn wrapping(min: f64, max: f64, tiles: usize) -> Dim {
+        Dim {
+            wrap: Some(tiles as isize),
+            ..Dim::bins(min, max, tiles)
+        }
+    }
+
+    /// A periodic dimension for an angle in radians (a joint angle, heading, or phase), with `tiles_per_revolution`
+    /// tiles (per tiling) around the circle. Angles may be given in any range, e.g. `[-π, π)` or `[0, 2π)`, or
+    /// unwrapped: angles a whole revolution apart always share their tiles.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{Dim, TileCoder};
+    /// # use std::f64::consts::PI;
+    /// let mut coder = TileCoder::new(8, 4096, vec![Dim::angle_radians(16)]);
+    /// let heading = coder.tiles(&[-0.1], None);
+    /// assert_eq!(coder.tiles(&[2.0 * PI - 0.1], None), heading);
+    /// assert_eq!(coder.tiles(&[4.0 * PI - 0.1], None), heading);
+    /// ```
+    pub fn angle_radians(tiles_per_revolution: usize) -> Dim {
+        Dim::wrapping(0.0, 2.0 * std::f64::consts::PI, tiles_per_revolution)
+    }
+
+    /// Clamp raw values to the dimension's `[min, max]` range before tiling them, so that observations slightly out
+    /// of range land in the boundary tiles instead of using up IHT capacity with brand new tiles. Only dimensions
+    /// created with `bins` have a range to clamp to.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{Dim, TileCoder};
+    /// let mut coder = TileCoder::new(8, 4096, vec![Dim::bins(-1.2, 0.6, 8).clamped()]);
+    /// assert_eq!(coder.tiles(&[0.61], None), coder.tiles(&[0.6], None));
+    /// assert_eq!(coder.tiles(&[-5.0], None), coder.tiles(&[-1.2], None));
+    /// ```
+    pub fn clamped(mut self) -> Dim {
+        assert!(self.range.is_some(), "only dimensions with a range can be clamped");
+        assert!(self.wrap.is_none(), "wrapping dimensions can't be clamped");
+        self.clamp = true;
+        self
+    }
+
+    /// The declared `[min, max]` range of the dimension, if it has one
+    pub fn range(&self) -> Option<(f64, f64)> {
+        self.range
+    }
+
+    /// Scale a raw value to tile units, i.e. such that tile boundaries (of the first tiling) lie on the integers
+    pub fn scale(&self, x: f64) -> f64 {
+        let x = match self.range {
+            Some((min, max)) if self.clamp => x.max(min).min(max),
+            // bring wrapping values into `[min, max)` first, since the tiles3 wrapping is only continuous for
+            // non-negative values
+            Some((min, max)) if self.wrap.is_some() => min + (x - min).rem_euclid(max - min),
+            _ => x,
+        };
+        (x - self.origin) * self.scale
+    }
+
+    // the inverse of `scale`, from tile units back to raw values (ignoring clamping and wrapping)
+    #[cfg(feature = "viz")]
+    pub(crate) fn unscale(&self, s: f64) -> f64 {
+        s / self.scale + self.origin
+    }
+
+    /// The width the dimension wraps around at, in tile units
+    pub fn wrap_width(&self) -> Option<isize> {
+        self.wrap
+    }
+
+    // the same dimension with `factor` times as many tiles
+    pub(crate) fn refined(&self, factor: usize) -> Dim {
+        Dim {
+            scale: self.scale * factor as f64,
+            wrap: self.wrap.map(|w| w * factor as isize),
+            ..self.clone()
+        }
+    }
+}
+
+impl From<&DimensionConfig> for Dim {
+    fn from(config: &DimensionConfig) -> Dim {
+        if config.wrap {
+            Dim::wrapping(config.min, config.max, config.tiles)
+        } else if config.clamp {
+            Dim::bins(config.min, config.max, config.tiles).clamped()
+        } else {
+            Dim::bins(config.min, config.max, config.tiles)
+        }
+    }
+}
+
+/// A stateful tile coder which owns its IHT and remembers the number of tilings and how each float dimension is
+/// scaled, so that raw observations can be tiled directly.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{Dim, TileCoder};
+/// // 20 tiles over the position range, 5 over the velocity range
+/// let mut coder = TileCoder::new(8, 4096, vec![Dim::bins(-1.2, 0.6, 20), Dim::bins(-0.07, 0.07, 5)]);
+///
+/// let indices = coder.tiles(&[-0.5, 0.01], None);
+/// assert_eq!(indices, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+///
+/// // the same as scaling by hand
+/// let mut iht = tilecoding::IHT::new(4096);
+/// let scaled = [(-0.5 + 1.2) * 20.0 / 1.8, (0.01 + 0.07) * 5.0 / 0.14];
+/// assert_eq!(iht.tiles(8, &scaled, None), indices);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileCoder {
+    num_tilings: usize,
+    dims: Vec<Dim>,
+    offsets: Offsets,
+    // which dimensions take part in tiling, or `None` for all of them
+    mask: Option<Vec<bool>>,
+    iht: IHT,
+}
+
+// the coordinates of a tile with only the unmasked dimensions: the tiling is negated (so these can never equal the
+// coordinates of an unmasked tile), then the number of unmasked dimensions, each one's number and coordinate, and the
+// ints
+fn mask_coords(coords: Vec<isize>, mask: &[bool], num_ints: usize) -> Vec<isize> {
+    let active = mask.iter().filter(|&&m| m).count();
+    let mut masked = Vec::with_capacity(2 + 2 * active + num_ints);
+    masked.push(-(coords[0] + 1));
+    masked.push(active as isize);
+    for (dim, (&c, _)) in coords[1..1 + mask.len()].iter().zip(mask).enumerate().filter(|(_, (_, &m))| m) {
+        masked.push(dim as isize);
+        masked.push(c);
+    }
+    masked.extend_from_slice(&coords[1 + mask.len()..]);
+    masked
+}
+
+impl TileCoder {
+    /// Create a coder with `num_tilings` tilings over the given dimensions, backed by an IHT with the given size
+    pub fn new(num_tilings: usize, size: usize, dims: Vec<Dim>) -> TileCoder {
+        TileCoder {
+            num_tilings,
+            dims,
+            offsets: Offsets::Tiles3,
+            mask: None,
+            iht: IHT::new(size),
+        }
+    }
+
+    /// Create a coder described by a configuration
+    pub fn from_config(config: &CoderConfig) -> TileCoder {
+        TileCoder::new(config.num_tilings(), config.size(), config.dimensions().iter().map(Dim::from).collect())
+            .with_offsets(config.offsets().clone())
+    }
+
+    /// Offset the tilings using the given scheme instead of the tiles3 displacement
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{Dim, Offsets, TileCoder};
+    /// let mut coder = TileCoder::new(8, 4096, vec![Dim::width(0.1), Dim::width(0.1)])
+    ///     .with_offsets(Offsets::Displacement(vec![1, 5]));
+    /// assert_eq!(coder.tiles(&[0.25, 0.5], None).len(), 8);
+    /// ```
+    pub fn with_offsets(mut self, offsets: Offsets) -> TileCoder {
+        if let Some(dims) = offsets.dimensions() {
+            assert_eq!(dims, self.dims.len(), "the displacement vector must have one entry per dimension");
+        }
+        self.offsets = offsets;
+        self
+    }
+
+    /// The scheme used to offset the tilings from one another
+    pub fn offsets(&self) -> &Offsets {
+        &self.offsets
+    }
+
+    /// Select which dimensions take part in tiling, with one flag per dimension, e.g. to ablate some of them. The mask
+    /// can be changed at any time: the masked dimensions' values are ignored, and every retained dimension keeps its
+    /// own coordinates, so the tiles of each mask stay the same whenever that mask is selected again. Masking no
+    /// dimensions (or clearing the mask with `None`) gives exactly the unmasked tiles.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{Dim, TileCoder};
+    /// let mut coder = TileCoder::new(8, 4096, vec![Dim::width(0.1), Dim::width(0.1)]);
+    /// let full = coder.tiles(&[0.25, 0.5], None);
+    ///
+    /// // ignore the second dimension
+    /// coder.set_mask(Some(vec![true, false]));
+    /// let ablated = coder.tiles(&[0.25, 0.5], None);
+    /// assert_eq!(coder.tiles(&[0.25, -30.0], None), ablated);
+    ///
+    /// // and bring it back, with its old tiles
+    /// coder.set_mask(None);
+    /// assert_eq!(coder.tiles(&[0.25, 0.5], None), full);
+    /// ```
+    pub fn set_mask(&mut self, mask: Option<Vec<bool>>) {
+        if let Some(mask) = &mask {
+            assert_eq!(mask.len(), self.dims.len(), "the mask must have one entry per dimension");
+        }
+        self.mask = mask.filter(|mask| !mask.iter().all(|&m| m));
+    }
+
+    /// The mask selecting which dimensions take part in tiling, if some are masked, see [`TileCoder::set_mask`]
+    pub fn mask(&self) -> Option<&[bool]> {
+        self.mask.as_deref()
+    }
+
+    /// The number of tilings, i.e. the number of indices returned by `tiles`
+    pub fn num_tilings(&self) -> usize {
+        self.num_tilings
+    }
+
+    /// The size of the underlying IHT; every index is `< size`
+    pub fn size(&self) -> usize {
+        self.iht.size()
+    }
+
+    /// The float dimensions, in the order their values are expected
+    pub fn dims(&self) -> &[Dim] {
+        &self.dims
+    }
+
+    /// The underlying IHT
+    pub fn iht(&self) -> &IHT {
+        &self.iht
+    }
+
+    pub(crate) fn iht_mut(&mut self) -> &mut IHT {
+        &mut self.iht
+    }
+
+    /// Scale a raw point to tile units
+    pub fn scale(&self, floats: &[f64]) -> Vec<f64> {
+        assert_eq!(floats.len(), self.dims.len(), "expected one float per dimension");
+        floats.iter().zip(self.dims.iter()).map(|(&x, d)| d.scale(x)).collect()
+    }
+
+    fn wrap_widths(&self) -> Option<Vec<Option<isize>>> {
+        if self.dims.iter().any(|d| d.wrap.is_some()) {
+            Some(self.dims.iter().map(|d| d.wrap).collect())
+        } else {
+            None
+        }
+    }
+
+    // the coordinates of the point's tile in every tiling
+    pub(crate) fn coords(&self, floats: &[f64], ints: Option<&[isize]>) -> Vec<Vec<isize>> {
+        let q_floats = calculate_q_floats(&self.scale(floats), self.num_tilings);
+        let wrap_widths = self.wrap_widths();
+        let offsets = self.offsets.resolve(self.num_tilings, self.dims.len());
+        (0..self.num_tilings)
+            .map(|tiling| {
+                let coords = calculate_coords_offsets(tiling, self.num_tilings, &q_floats, &offsets, wrap_widths.as_deref(), &ints);
+                match &self.mask {
+                    Some(mask) => mask_coords(coords, mask, ints.map_or(0, |i| i.len())),
+                    None => coords,
+                }
+            })
+            .collect()
+    }
+
+    /// Encode a raw point (and optional ints) as `num_tilings` tile indices, see [`IHT::tiles`]
+    pub fn tiles(&mut self, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        let coords = self.coords(floats, ints);
+        coords.into_iter().map(|coords| self.iht.get_index(coords)).collect()
+    }
+
+    /// The read-only version of `tiles`, see [`IHT::tiles_read_only`]
+    pub fn tiles_read_only(&self, floats: &[f64], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let coords = self.coords(floats, ints);
+        coords.into_iter().map(|coords| self.iht.get_index_read_only(coords)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolution_is_per_dimension() {
+        let mut coder = TileCoder::new(4, 1024, vec![Dim::bins(0.0, 1.0, 20), Dim::bins(0.0, 1.0, 5)]);
+        let base = coder.tiles(&[0.5, 0.5], None);
+        // a twentieth of the range is a whole tile in the first dimension...
+        assert!(coder.tiles(&[0.55, 0.5], None).iter().zip(base.iter()).all(|(a, b)| a != b));
+        // ...but only a quarter of a tile in the second
+        assert!(coder.tiles(&[0.5, 0.55], None).iter().zip(base.iter()).any(|(a, b)| a == b));
+    }
+
+    #[test]
+    fn width_divides_before_quantization() {
+        let mut coder = TileCoder::new(8, 1024, vec![Dim::width(0.25)]);
+        let mut iht = IHT::new(1024);
+        for &x in [-1.3, 0.0, 0.1, 2.75].iter() {
+            assert_eq!(coder.tiles(&[x], None), iht.tiles(8, &[x / 0.25], None));
+        }
+    }
+
+    #[test]
+    fn width_tiles_straddling_the_origin_are_twice_as_wide() {
+        let mut coder = TileCoder::new(8, 1024, vec![Dim::width(1.0)]);
+        let shares = |coder: &mut TileCoder, a: f64, b: f64| {
+            let a = coder.tiles(&[a], None);
+            coder.tiles(&[b], None).iter().any(|i| a.contains(i))
+        };
+        assert!(shares(&mut coder, -0.8, 0.8));
+        assert!(!shares(&mut coder, 1.0, 2.1));
+        assert!(!shares(&mut coder, -2.1, -1.0));
+    }
+
+    #[test]
+    fn clamping_saves_capacity() {
+        let mut coder = TileCoder::new(4, 1024, vec![Dim::bins(0.0, 1.0, 4).clamped(), Dim::width(1.0)]);
+        coder.tiles(&[0.0, 0.0], None);
+        coder.tiles(&[1.0, 0.0], None);
+        let count = coder.iht().count();
+        coder.tiles(&[-0.3, 0.0], None);
+        coder.tiles(&[1.3, 0.0], None);
+        assert_eq!(coder.iht().count(), count);
+    }
+
+    #[test]
+    fn wrapping_dimensions_wrap() {
+        let mut coder = TileCoder::new(4, 1024, vec![Dim::wrapping(0.0, 360.0, 12)]);
+        assert_eq!(coder.tiles(&[10.0], None), coder.tiles(&[370.0], None));
+        assert_eq!(coder.tiles_read_only(&[10.0], None), coder.tiles_read_only(&[370.0], None));
+    }
+
+    #[test]
+    fn wrapping_dimensions_wrap_below_min() {
+        let mut coder = TileCoder::new(8, 1024, vec![Dim::wrapping(-180.0, 180.0, 12), Dim::angle_radians(8)]);
+        let a = coder.tiles(&[-185.0, -0.2], None);
+        assert_eq!(coder.tiles(&[175.0, 2.0 * std::f64::consts::PI - 0.2], None), a);
+        assert_eq!(coder.tiles(&[535.0, -2.0 * std::f64::consts::PI - 0.2], None), a);
+    }
+
+    #[test]
+    fn custom_offsets() {
+        let offsets = Offsets::Displacement(vec![3]);
+        let mut coder = TileCoder::new(4, 1024, vec![Dim::width(1.0)]).with_offsets(offsets.clone());
+        let mut iht = IHT::new(1024);
+        assert_eq!(coder.tiles(&[0.6], None), iht.tiles_with_offsets(4, &[0.6], &offsets, None));
+    }
+
+    #[test]
+    fn masks_keep_retained_dimensions() {
+        let mut coder = TileCoder::new(4, 4096, vec![Dim::width(1.0), Dim::width(1.0), Dim::width(1.0)]);
+        let full = coder.tiles(&[0.3, 1.7, -2.2], Some(&[1]));
+
+        coder.set_mask(Some(vec![true, false, true]));
+        let coords = coder.coords(&[0.3, 1.7, -2.2], Some(&[1]));
+        let unmasked = TileCoder::new(4, 4096, vec![Dim::width(1.0); 3]).coords(&[0.3, 1.7, -2.2], Some(&[1]));
+        for (c, u) in coords.iter().zip(unmasked.iter()) {
+            assert_eq!(c, &vec![-(u[0] + 1), 2, 0, u[1], 2, u[3], 1]);
+        }
+        let masked = coder.tiles(&[0.3, 99.0, -2.2], Some(&[1]));
+        assert!(masked.iter().all(|t| !full.contains(t)));
+
+        coder.set_mask(Some(vec![true; 3]));
+        assert_eq!(coder.mask(), None);
+        assert_eq!(coder.tiles_read_only(&[0.3, 1.7, -2.2], Some(&[1])), full.into_iter().map(Some).collect::<Vec<_>>());
+        coder.set_mask(Some(vec![true, false, true]));
+        assert_eq!(coder.tiles(&[0.3, -5.0, -2.2], Some(&[1])), masked);
+    }
+
+    #[test]
+    fn built_from_config() {
+        let config = CoderConfig::new(8, 512, vec![DimensionConfig::new(-1.0, 1.0, 4)]).unwrap();
+        let mut coder = TileCoder::from_config(&config);
+        assert_eq!(coder.num_tilings(), 8);
+        assert_eq!(coder.size(), 512);
+        let mut iht = IHT::new(512);
+        assert_eq!(coder.tiles(&[0.3], None), iht.tiles(8, &config.scale(&[0.3]), None));
+    }
+}
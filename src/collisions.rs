@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use crate::{base_hash, calculate_coords, calculate_coords_wrap, calculate_q_floats};
+
+/// An opt-in audit of the hashing `tiles` function (the one taking a `size` rather than an IHT): it returns exactly
+/// the same indices, and records every distinct tile it has seen along with its index, so that the actual
+/// collisions (distinct tiles hashed to the same index) can be counted and inspected. Compare with the analytical
+/// [`estimate_collisions`](crate::estimate_collisions).
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{tiles, CollisionAudit};
+/// let mut audit = CollisionAudit::new(64);
+/// for i in 0..20 {
+///     let x = i as f64 * 0.5;
+///     assert_eq!(audit.tiles(4, &[x], None), tiles(64, 4, &[x], None));
+/// }
+/// assert_eq!(audit.distinct_tiles(), 42);
+/// // 42 tiles in 64 indices are bound to collide
+/// assert!(audit.colliding_tiles() > 0);
+/// for (index, tiles) in audit.collisions() {
+///     assert!(tiles.len() > 1 && tiles.iter().all(|coords| audit.index_of(coords) == Some(index)));
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CollisionAudit {
+    size: usize,
+    seen: HashMap<Vec<isize>, usize>,
+    // the number of distinct tiles hashed to each index that has been used
+    per_index: HashMap<usize, usize>,
+}
+
+impl CollisionAudit {
+    /// Audit the hashing `tiles` function with the given size
+    pub fn new(size: usize) -> CollisionAudit {
+        CollisionAudit {
+            size,
+            ..CollisionAudit::default()
+        }
+    }
+
+    /// The size the indices are hashed into
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    fn record(&mut self, coords: Vec<isize>) -> usize {
+        if let Some(&index) = self.seen.get(&coords) {
+            return index;
+        }
+        let index = base_hash(&coords) % self.size;
+        self.seen.insert(coords, index);
+        *self.per_index.entry(index).or_insert(0) += 1;
+        index
+    }
+
+    /// Exactly the same as the free `tiles` function, recording the tiles
+    pub fn tiles(&mut self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        (0..num_tilings).map(|tiling| self.record(calculate_coords(tiling, num_tilings, &q_floats, &ints))).collect()
+    }
+
+    /// Exactly the same as the free `tiles_wrap` function, recording the tiles
+    pub fn tiles_wrap(&mut self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<usize> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        (0..num_tilings)
+            .map(|tiling| self.record(calculate_coords_wrap(tiling, num_tilings, &q_floats, wrap_widths, &ints)))
+            .collect()
+    }
+
+    /// The number of distinct tiles seen
+    pub fn distinct_tiles(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// The number of distinct indices the tiles were hashed to
+    pub fn occupied(&self) -> usize {
+        self.per_index.len()
+    }
+
+    /// The number of indices shared by more than one distinct tile
+    pub fn colliding_indices(&self) -> usize {
+        self.per_index.values().filter(|&&tiles| tiles > 1).count()
+    }
+
+    /// The number of distinct tiles which share their index with at least one other tile
+    pub fn colliding_tiles(&self) -> usize {
+        self.per_index.values().filter(|&&tiles| tiles > 1).sum()
+    }
+
+    /// The index a tile was hashed to, if it has been seen
+    pub fn index_of(&self, coords: &[isize]) -> Option<usize> {
+        self.seen.get(coords).copied()
+    }
+
+    /// Every collision, as the shared index and the coordinates of the tiles hashed to it (laid out as
+    /// `[tiling, float coordinates..., ints...]`), sorted by index and then by coordinates
+    pub fn collisions(&self) -> Vec<(usize, Vec<Vec<isize>>)> {
+        let mut by_index: HashMap<usize, Vec<Vec<isize>>> = HashMap::new();
+        for (coords, &index) in self.seen.iter() {
+            if self.per_index[&index] > 1 {
+                by_index.entry(index).or_default().push(coords.clone());
+            }
+        }
+        let mut collisions: Vec<(usize, Vec<Vec<isize>>)> = by_index.into_iter().collect();
+        for (_, tiles) in collisions.iter_mut() {
+            tiles.sort();
+        }
+        collisions.sort_by_key(|&(index, _)| index);
+        collisions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles_wrap;
+
+    #[test]
+    fn counts_only_distinct_tiles() {
+        let mut audit = CollisionAudit::new(1);
+        assert_eq!(audit.tiles_wrap(2, &[0.5], &[Some(4)], None), tiles_wrap(1, 2, &[0.5], &[Some(4)], None));
+        // seeing the same tiles again doesn't add collisions
+        audit.tiles_wrap(2, &[0.5], &[Some(4)], None);
+        assert_eq!((audit.distinct_tiles(), audit.occupied(), audit.colliding_indices(), audit.colliding_tiles()), (2, 1, 1, 2));
+        assert_eq!(audit.collisions(), vec![(0, vec![vec![0, 0], vec![1, 1]])]);
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use crate::inline::InlineCoords;
+use crate::{base_hash, calculate_coords_into, calculate_coords_wrap_into, calculate_q_floats, check_index_type, Error, TileIndex, IHT};
+
+// coordinates narrowed onto the stack whenever there are this few of them
+const STACK: usize = 16;
+
+// call `f` with the coordinates narrowed to `i32`s, or with `None` if any of them doesn't fit in one
+fn with_narrowed<R>(coords: &[isize], f: impl FnOnce(Option<&[i32]>) -> R) -> R {
+    let mut stack = [0i32; STACK];
+    let mut heap = Vec::new();
+    let narrowed = if coords.len() <= STACK {
+        &mut stack[..coords.len()]
+    } else {
+        heap.resize(coords.len(), 0);
+        &mut heap[..]
+    };
+    for (n, &c) in narrowed.iter_mut().zip(coords) {
+        match i32::try_from(c) {
+            Ok(c) => *n = c,
+            Err(_) => return f(None),
+        }
+    }
+    f(Some(narrowed))
+}
+
+/// An IHT which stores its indices, and returns its tiles, as the integer type `T` rather than `usize`, and stores
+/// its coordinates as `i32`s, for tables with millions of entries. On 64-bit platforms a `u32` table holds its
+/// dictionary values in half the memory, and its tiles can index a weight vector without widening; sizes up to
+/// `u32::MAX + 1` cover all but the most enormous tables. Each key takes roughly half the memory of an `IHT`'s.
+///
+/// Coordinates are narrowed with a check: a tile with a coordinate outside the `i32` range (from an enormous or
+/// unscaled input) falls back to a full-width key, so every input still works.
+///
+/// It hands out exactly the same indices as an [`IHT`] fed the same points, including when it is overfull.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{CompactIHT, IHT};
+/// let mut compact: CompactIHT<u32> = CompactIHT::new(1024).unwrap();
+/// let mut iht = IHT::new(1024);
+///
+/// let tiles: Vec<u32> = compact.tiles(8, &[3.6, 7.21], None);
+/// assert_eq!(tiles.iter().map(|&t| t as usize).collect::<Vec<_>>(), iht.tiles(8, &[3.6, 7.21], None));
+///
+/// // a table too large for its index type is an error up front
+/// assert!(CompactIHT::<u16>::new(1 << 20).is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactIHT<T: TileIndex = u32> {
+    size: usize,
+    overfull_count: usize,
+    dictionary: HashMap<Box<[i32]>, T>,
+    spilled: HashMap<Vec<isize>, T>,
+}
+
+impl<T: TileIndex> CompactIHT<T> {
+    /// Create a new table with the given size, failing if an index `< size` doesn't fit in `T`
+    pub fn new(size: usize) -> Result<CompactIHT<T>, Error> {
+        check_index_type::<T>(size)?;
+        Ok(CompactIHT {
+            size,
+            overfull_count: 0,
+            dictionary: HashMap::with_capacity(size),
+            spilled: HashMap::new(),
+        })
+    }
+
+    /// The size of the table; no index will be `>= size`
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of tiles in the table
+    pub fn count(&self) -> usize {
+        self.dictionary.len() + self.spilled.len()
+    }
+
+    /// Whether the table is full, so that new tiles collide with existing ones
+    pub fn full(&self) -> bool {
+        self.count() >= self.size
+    }
+
+    /// The number of tiles whose coordinates didn't fit in `i32`s
+    pub fn spilled_count(&self) -> usize {
+        self.spilled.len()
+    }
+
+    /// The number of lookups that collided because the table was full
+    pub fn overfull_count(&self) -> usize {
+        self.overfull_count
+    }
+
+    fn get_index_read_only(&self, coords: &[isize]) -> Option<T> {
+        with_narrowed(coords, |narrowed| match narrowed {
+            Some(narrowed) => self.dictionary.get(narrowed).copied(),
+            None => self.spilled.get(coords).copied(),
+        })
+    }
+
+    fn get_index(&mut self, coords: &[isize]) -> T {
+        if let Some(index) = self.get_index_read_only(coords) {
+            return index;
+        }
+        if self.full() {
+            self.overfull_count += 1;
+            return T::from_index(base_hash(coords) % self.size);
+        }
+        let index = T::from_index(self.count());
+        let (dictionary, spilled) = (&mut self.dictionary, &mut self.spilled);
+        with_narrowed(coords, |narrowed| match narrowed {
+            Some(narrowed) => dictionary.insert(narrowed.into(), index),
+            None => spilled.insert(coords.to_vec(), index),
+        });
+        index
+    }
+
+    /// Encode a point as `out.len()` tile indices (i.e. with `out.len()` tilings) written into `out`, see
+    /// [`IHT::tiles`]
+    pub fn tiles_into(&mut self, out: &mut [T], floats: &[f64], ints: Option<&[isize]>) {
+        let num_tilings = out.len();
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut coords = InlineCoords::new();
+        for (tiling, tile) in out.iter_mut().enumerate() {
+            calculate_coords_into(&mut coords, tiling, num_tilings, &q_floats, &ints);
+            *tile = self.get_index(&coords);
+        }
+    }
+
+    /// Encode a point as `num_tilings` tile indices, see [`IHT::tiles`]
+    pub fn tiles(&mut self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<T> {
+        let mut tiles = vec![T::from_index(0); num_tilings];
+        self.tiles_into(&mut tiles, floats, ints);
+        tiles
+    }
+
+    /// Encode a point as tile indices with wrapping, see [`IHT::tiles_wrap`]
+    pub fn tiles_wrap(&mut self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<T> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut coords = InlineCoords::new();
+        (0..num_tilings)
+            .map(|tiling| {
+                calculate_coords_wrap_into(&mut coords, tiling, num_tilings, &q_floats, wrap_widths, &ints);
+                self.get_index(&coords)
+            })
+            .collect()
+    }
+
+    /// The read-only version of `tiles`, see [`IHT::tiles_read_only`]
+    pub fn tiles_read_only(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<Option<T>> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut coords = InlineCoords::new();
+        (0..num_tilings)
+            .map(|tiling| {
+                calculate_coords_into(&mut coords, tiling, num_tilings, &q_floats, &ints);
+                self.get_index_read_only(&coords)
+            })
+            .collect()
+    }
+
+    /// Convert to an ordinary IHT with the same entries, e.g. to use the rest of the crate's tools on it
+    pub fn to_iht(&self) -> IHT {
+        let mut entries: Vec<(Vec<isize>, usize)> = self
+            .dictionary
+            .iter()
+            .map(|(coords, &index)| (coords.iter().map(|&c| c as isize).collect(), index.to_index()))
+            .chain(self.spilled.iter().map(|(coords, &index)| (coords.clone(), index.to_index())))
+            .collect();
+        entries.sort_by_key(|&(_, index)| index);
+        let mut iht = IHT::new(self.size);
+        for (coords, _) in entries {
+            iht.lookup_index(coords);
+        }
+        iht.overfull_count = self.overfull_count;
+        iht
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_iht_including_spills_and_overflow() {
+        let mut compact = CompactIHT::<u8>::new(256).unwrap();
+        let mut iht = IHT::new(256);
+        for i in 0..60 {
+            let x = i as f64 * 0.7 - 20.0;
+            let tiles = compact.tiles(8, &[x, 1.0], Some(&[i % 3]));
+            assert_eq!(tiles.iter().map(|&t| t.to_index()).collect::<Vec<_>>(), iht.tiles(8, &[x, 1.0], Some(&[i % 3])));
+            let wrapped = compact.tiles_wrap(4, &[x], &[Some(7)], None);
+            assert_eq!(wrapped.iter().map(|&t| t.to_index()).collect::<Vec<_>>(), iht.tiles_wrap(4, &[x], &[Some(7)], None));
+            // too large to narrow
+            let huge = compact.tiles(4, &[x * 1e10], None);
+            assert_eq!(huge.iter().map(|&t| t.to_index()).collect::<Vec<_>>(), iht.tiles(4, &[x * 1e10], None));
+        }
+        assert!(compact.full() && compact.spilled_count() > 0);
+        assert_eq!(compact.overfull_count(), iht.overfull_count);
+        assert_eq!(compact.tiles_read_only(8, &[-20.0, 1.0], Some(&[0])).iter().map(|t| t.map(u8::to_index)).collect::<Vec<_>>(), iht.tiles_read_only(8, &[-20.0, 1.0], Some(&[0])));
+        assert_eq!(compact.to_iht().entries_snapshot(), iht.entries_snapshot());
+        assert!(CompactIHT::<u8>::new(257).is_err());
+    }
+}
@@ -0,0 +1,581 @@
+use crate::{stable_hash, Error, Offsets};
+
+/// The configuration of a single float dimension of a [`CoderConfig`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DimensionConfig {
+    /// An optional name for the dimension, used in error messages
+    pub name: Option<String>,
+    /// The lower end of the range of the dimension
+    pub min: f64,
+    /// The upper end of the range of the dimension
+    pub max: f64,
+    /// The number of tiles spanning `[min, max]` in each tiling
+    pub tiles: usize,
+    /// Whether the dimension wraps around from `max` back to `min`
+    pub wrap: bool,
+    /// Whether values outside of `[min, max]` are clamped to the range before tiling
+    pub clamp: bool,
+}
+
+impl DimensionConfig {
+    /// A dimension over `[min, max]` with `tiles` tiles per tiling, which doesn't wrap
+    pub fn new(min: f64, max: f64, tiles: usize) -> DimensionConfig {
+        DimensionConfig {
+            name: None,
+            min,
+            max,
+            tiles,
+            wrap: false,
+            clamp: false,
+        }
+    }
+
+    /// Scale a raw value of this dimension so that the tile boundaries lie on the integers
+    pub fn scale(&self, x: f64) -> f64 {
+        let x = if self.clamp {
+            x.max(self.min).min(self.max)
+        } else if self.wrap {
+            // the same normalization as a wrapping `Dim`
+            self.min + (x - self.min).rem_euclid(self.max - self.min)
+        } else {
+            x
+        };
+        (x - self.min) / (self.max - self.min) * self.tiles as f64
+    }
+
+    fn label(&self, i: usize) -> String {
+        match &self.name {
+            Some(name) => format!("dimension {} (`{}`)", i, name),
+            None => format!("dimension {}", i),
+        }
+    }
+}
+
+/// A validated description of a tile coder: the number of tilings, the IHT size, and the range,
+/// resolution, and wrapping of each float dimension.
+///
+/// Configurations are typically loaded from a small TOML file, so that experiments can vary their
+/// tilings without code changes:
+///
+/// ```toml
+/// num_tilings = 8
+/// size = 4096
+/// offsets = "tiles3"  # optional, "coprime", "random", or a displacement vector such as [1, 3]
+/// seed = 42           # optional, required by "random" offsets
+///
+/// [[dimension]]
+/// name = "position"   # optional
+/// min = -1.2
+/// max = 0.6
+/// tiles = 8
+/// clamp = true        # optional, defaults to false
+///
+/// [[dimension]]
+/// name = "angle"
+/// min = 0.0
+/// max = 6.283185307179586
+/// tiles = 10
+/// wrap = true         # optional, defaults to false
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{CoderConfig, TileCoder};
+/// let config = CoderConfig::from_toml(r#"
+///     num_tilings = 8
+///     size = 4096
+///
+///     [[dimension]]
+///     min = -1.2
+///     max = 0.6
+///     tiles = 8
+///
+///     [[dimension]]
+///     min = -0.07
+///     max = 0.07
+///     tiles = 8
+/// "#).unwrap();
+///
+/// let mut coder = TileCoder::from_config(&config);
+/// let indices = coder.tiles(&[-0.5, 0.01], None);
+/// assert_eq!(indices.len(), 8);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoderConfig {
+    num_tilings: usize,
+    size: usize,
+    offsets: Offsets,
+    seed: Option<u64>,
+    dimensions: Vec<DimensionConfig>,
+}
+
+impl CoderConfig {
+    /// Create and validate a configuration using the tiles3 offsets
+    pub fn new(num_tilings: usize, size: usize, dimensions: Vec<DimensionConfig>) -> Result<CoderConfig, Error> {
+        let config = CoderConfig {
+            num_tilings,
+            size,
+            offsets: Offsets::Tiles3,
+            seed: None,
+            dimensions,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Use a different offset scheme, failing if a displacement vector doesn't match the dimensions
+    pub fn with_offsets(mut self, offsets: Offsets) -> Result<CoderConfig, Error> {
+        self.offsets = offsets;
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// The number of tilings
+    pub fn num_tilings(&self) -> usize {
+        self.num_tilings
+    }
+
+    /// The size of the IHT (or hashing space) to use
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The scheme used to offset the tilings from each other
+    pub fn offsets(&self) -> &Offsets {
+        &self.offsets
+    }
+
+    /// The seed recorded in the configuration, used by seeded offset schemes
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// The float dimensions, in the order their values are expected
+    pub fn dimensions(&self) -> &[DimensionConfig] {
+        &self.dimensions
+    }
+
+    /// Scale a raw point so that it can be passed to the `tiles` functions. This ignores the configured offsets, which
+    /// only [`TileCoder::from_config`](crate::TileCoder::from_config) applies.
+    pub fn scale(&self, floats: &[f64]) -> Vec<f64> {
+        assert_eq!(floats.len(), self.dimensions.len(), "expected one float per dimension");
+        floats.iter().zip(self.dimensions.iter()).map(|(&x, d)| d.scale(x)).collect()
+    }
+
+    /// The wrap widths to pass to the `tiles_wrap` functions along with scaled points
+    pub fn wrap_widths(&self) -> Vec<Option<isize>> {
+        self.dimensions
+            .iter()
+            .map(|d| if d.wrap { Some(d.tiles as isize) } else { None })
+            .collect()
+    }
+
+    /// A stable fingerprint of everything in the configuration which affects the tiles (the number of tilings, the
+    /// size, the offsets and seed, and every dimension, but not the dimensions' names), which is the same on every
+    /// platform and rust version. Two configurations with the same fingerprint encode points identically.
+    pub fn fingerprint(&self) -> u64 {
+        let mut words = vec![self.num_tilings as u64, self.size as u64];
+        match &self.offsets {
+            Offsets::Tiles3 => words.push(0),
+            Offsets::Displacement(displacement) => {
+                words.extend_from_slice(&[1, displacement.len() as u64]);
+                words.extend(displacement.iter().map(|&d| d as i64 as u64));
+            }
+            Offsets::Random(seed) => words.extend_from_slice(&[2, *seed]),
+            Offsets::Coprime => words.push(3),
+        }
+        words.extend_from_slice(&[self.seed.is_some() as u64, self.seed.unwrap_or(0)]);
+        for d in &self.dimensions {
+            words.extend_from_slice(&[d.min.to_bits(), d.max.to_bits(), d.tiles as u64, d.wrap as u64, d.clamp as u64]);
+        }
+        // split into halves so that nothing is lost where `isize` is 32 bits; never 0, which means no configuration
+        let halves: Vec<isize> = words.iter().flat_map(|&w| [w as u32 as isize, (w >> 32) as u32 as isize]).collect();
+        stable_hash(&halves).max(1)
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if self.num_tilings == 0 {
+            return Err(Error::Config("`num_tilings` must be at least 1".to_string()));
+        }
+        if self.size == 0 {
+            return Err(Error::Config("`size` must be at least 1".to_string()));
+        }
+        if self.dimensions.is_empty() {
+            return Err(Error::Config("at least one [[dimension]] is required".to_string()));
+        }
+        if let Some(dims) = self.offsets.dimensions() {
+            if dims != self.dimensions.len() {
+                return Err(Error::Config(format!(
+                    "the displacement vector has {} entries but there are {} dimensions",
+                    dims,
+                    self.dimensions.len()
+                )));
+            }
+        }
+        for (i, d) in self.dimensions.iter().enumerate() {
+            if !d.min.is_finite() || !d.max.is_finite() {
+                return Err(Error::Config(format!("{}: `min` and `max` must be finite", d.label(i))));
+            }
+            if d.min >= d.max {
+                return Err(Error::Config(format!("{}: `min` ({}) must be less than `max` ({})", d.label(i), d.min, d.max)));
+            }
+            if d.tiles == 0 {
+                return Err(Error::Config(format!("{}: `tiles` must be at least 1", d.label(i))));
+            }
+            if d.wrap && d.clamp {
+                return Err(Error::Config(format!("{}: a dimension can't both `wrap` and `clamp`", d.label(i))));
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse and validate a configuration from TOML (see the type-level documentation for the schema)
+    pub fn from_toml(source: &str) -> Result<CoderConfig, Error> {
+        let document = toml::parse(source)?;
+
+        let mut root = document.root;
+        let num_tilings = root.take_usize("num_tilings")?.ok_or_else(|| Error::Config("missing `num_tilings`".to_string()))?;
+        let size = root.take_usize("size")?.ok_or_else(|| Error::Config("missing `size`".to_string()))?;
+        let seed = root.take_usize("seed")?.map(|s| s as u64);
+        let offsets = root.take_offsets("offsets", seed)?.unwrap_or(Offsets::Tiles3);
+        root.finish(&["num_tilings", "size", "offsets", "seed"])?;
+
+        let mut dimensions = Vec::with_capacity(document.dimensions.len());
+        for mut table in document.dimensions {
+            let line = table.line;
+            let missing = |key: &str| Error::Config(format!("line {}: [[dimension]] is missing `{}`", line, key));
+            let name = table.take_string("name")?.map(|(name, _)| name);
+            let min = table.take_f64("min")?.ok_or_else(|| missing("min"))?;
+            let max = table.take_f64("max")?.ok_or_else(|| missing("max"))?;
+            let tiles = table.take_usize("tiles")?.ok_or_else(|| missing("tiles"))?;
+            let wrap = table.take_bool("wrap")?.unwrap_or(false);
+            let clamp = table.take_bool("clamp")?.unwrap_or(false);
+            table.finish(&["name", "min", "max", "tiles", "wrap", "clamp"])?;
+            dimensions.push(DimensionConfig {
+                name,
+                min,
+                max,
+                tiles,
+                wrap,
+                clamp,
+            });
+        }
+
+        let config = CoderConfig {
+            num_tilings,
+            size,
+            offsets,
+            seed,
+            dimensions,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+// just enough TOML for coder configuration files: `key = value` pairs of integers, floats, strings, and booleans
+// in the root table, followed by any number of `[[dimension]]` tables
+mod toml {
+    use crate::{Error, Offsets};
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub(super) enum Value {
+        Int(i64),
+        Float(f64),
+        Str(String),
+        Bool(bool),
+        Array(Vec<Value>),
+    }
+
+    impl Value {
+        fn kind(&self) -> &'static str {
+            match self {
+                Value::Int(_) => "an integer",
+                Value::Float(_) => "a float",
+                Value::Str(_) => "a string",
+                Value::Bool(_) => "a boolean",
+                Value::Array(_) => "an array",
+            }
+        }
+    }
+
+    pub(super) struct Table {
+        pub(super) line: usize,
+        entries: Vec<(String, Value, usize)>,
+    }
+
+    impl Table {
+        fn take(&mut self, key: &str) -> Option<(Value, usize)> {
+            let position = self.entries.iter().position(|(k, _, _)| k == key)?;
+            let (_, value, line) = self.entries.remove(position);
+            Some((value, line))
+        }
+
+        fn wrong_type(key: &str, expected: &str, value: &Value, line: usize) -> Error {
+            Error::Config(format!("line {}: `{}` must be {}, found {}", line, key, expected, value.kind()))
+        }
+
+        pub(super) fn take_usize(&mut self, key: &str) -> Result<Option<usize>, Error> {
+            match self.take(key) {
+                None => Ok(None),
+                Some((Value::Int(i), _)) if i >= 0 => Ok(Some(i as usize)),
+                Some((Value::Int(i), line)) => Err(Error::Config(format!("line {}: `{}` must not be negative, found {}", line, key, i))),
+                Some((value, line)) => Err(Table::wrong_type(key, "an integer", &value, line)),
+            }
+        }
+
+        pub(super) fn take_f64(&mut self, key: &str) -> Result<Option<f64>, Error> {
+            match self.take(key) {
+                None => Ok(None),
+                Some((Value::Int(i), _)) => Ok(Some(i as f64)),
+                Some((Value::Float(x), _)) => Ok(Some(x)),
+                Some((value, line)) => Err(Table::wrong_type(key, "a number", &value, line)),
+            }
+        }
+
+        /// Take a string value along with the line it was declared on
+        pub(super) fn take_string(&mut self, key: &str) -> Result<Option<(String, usize)>, Error> {
+            match self.take(key) {
+                None => Ok(None),
+                Some((Value::Str(s), line)) => Ok(Some((s, line))),
+                Some((value, line)) => Err(Table::wrong_type(key, "a string", &value, line)),
+            }
+        }
+
+        /// Take an offset scheme, given either as a named scheme or as an array of integer displacements. Random
+        /// offsets are drawn using `seed`.
+        pub(super) fn take_offsets(&mut self, key: &str, seed: Option<u64>) -> Result<Option<Offsets>, Error> {
+            match self.take(key) {
+                None => Ok(None),
+                Some((Value::Str(ref s), _)) if s == "tiles3" => Ok(Some(Offsets::Tiles3)),
+                Some((Value::Str(ref s), _)) if s == "coprime" => Ok(Some(Offsets::Coprime)),
+                Some((Value::Str(ref s), line)) if s == "random" => match seed {
+                    Some(seed) => Ok(Some(Offsets::Random(seed))),
+                    None => Err(Error::Config(format!("line {}: random `{}` require a `seed`", line, key))),
+                },
+                Some((Value::Array(items), line)) => {
+                    let displacement = items
+                        .iter()
+                        .map(|item| match item {
+                            Value::Int(i) => Ok(*i as isize),
+                            other => Err(Table::wrong_type(key, "an array of integers", other, line)),
+                        })
+                        .collect::<Result<Vec<isize>, Error>>()?;
+                    Ok(Some(Offsets::Displacement(displacement)))
+                }
+                Some((value, line)) => Err(Error::Config(format!(
+                    "line {}: unknown `{}` {} (expected \"tiles3\", \"coprime\", \"random\", or an array of integer displacements)",
+                    line,
+                    key,
+                    match value {
+                        Value::Str(s) => format!("`{}`", s),
+                        other => other.kind().to_string(),
+                    }
+                ))),
+            }
+        }
+
+        pub(super) fn take_bool(&mut self, key: &str) -> Result<Option<bool>, Error> {
+            match self.take(key) {
+                None => Ok(None),
+                Some((Value::Bool(b), _)) => Ok(Some(b)),
+                Some((value, line)) => Err(Table::wrong_type(key, "a boolean", &value, line)),
+            }
+        }
+
+        /// Fail if any keys remain which weren't taken
+        pub(super) fn finish(self, expected: &[&str]) -> Result<(), Error> {
+            match self.entries.first() {
+                None => Ok(()),
+                Some((key, _, line)) => Err(Error::Config(format!(
+                    "line {}: unknown key `{}` (expected one of: {})",
+                    line,
+                    key,
+                    expected.join(", ")
+                ))),
+            }
+        }
+    }
+
+    pub(super) struct Document {
+        pub(super) root: Table,
+        pub(super) dimensions: Vec<Table>,
+    }
+
+    fn parse_value(raw: &str, line: usize) -> Result<Value, Error> {
+        let invalid = || Error::Config(format!("line {}: invalid value `{}`", line, raw));
+        if raw.starts_with('[') {
+            if !raw.ends_with(']') {
+                return Err(invalid());
+            }
+            let inner = raw[1..raw.len() - 1].trim();
+            if inner.is_empty() {
+                return Ok(Value::Array(Vec::new()));
+            }
+            return inner
+                .split(',')
+                .map(|item| match parse_value(item.trim(), line)? {
+                    Value::Array(_) => Err(invalid()),
+                    value => Ok(value),
+                })
+                .collect::<Result<Vec<Value>, Error>>()
+                .map(Value::Array);
+        }
+        if raw.starts_with('"') {
+            if raw.len() < 2 || !raw.ends_with('"') || raw[1..raw.len() - 1].contains('"') {
+                return Err(invalid());
+            }
+            return Ok(Value::Str(raw[1..raw.len() - 1].to_string()));
+        }
+        match raw {
+            "true" => return Ok(Value::Bool(true)),
+            "false" => return Ok(Value::Bool(false)),
+            _ => {}
+        }
+        let number = raw.replace('_', "");
+        if let Ok(i) = number.parse::<i64>() {
+            return Ok(Value::Int(i));
+        }
+        number.parse::<f64>().map(Value::Float).map_err(|_| invalid())
+    }
+
+    // strip a trailing comment, ignoring `#` inside of strings
+    fn strip_comment(line: &str) -> &str {
+        let mut in_string = false;
+        for (i, c) in line.char_indices() {
+            match c {
+                '"' => in_string = !in_string,
+                '#' if !in_string => return &line[..i],
+                _ => {}
+            }
+        }
+        line
+    }
+
+    pub(super) fn parse(source: &str) -> Result<Document, Error> {
+        let mut root = Table { line: 1, entries: Vec::new() };
+        let mut dimensions: Vec<Table> = Vec::new();
+
+        for (i, line) in source.lines().enumerate() {
+            let number = i + 1;
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                if line == "[[dimension]]" {
+                    dimensions.push(Table { line: number, entries: Vec::new() });
+                    continue;
+                }
+                return Err(Error::Config(format!("line {}: unknown table `{}` (expected `[[dimension]]`)", number, line)));
+            }
+
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = parts
+                .next()
+                .ok_or_else(|| Error::Config(format!("line {}: expected `key = value`", number)))?
+                .trim();
+            if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                return Err(Error::Config(format!("line {}: invalid key `{}`", number, key)));
+            }
+
+            let table = dimensions.last_mut().unwrap_or(&mut root);
+            if table.entries.iter().any(|(k, _, _)| k == key) {
+                return Err(Error::Config(format!("line {}: duplicate key `{}`", number, key)));
+            }
+            let value = parse_value(value, number)?;
+            table.entries.push((key.to_string(), value, number));
+        }
+
+        Ok(Document { root, dimensions })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTAIN_CAR: &str = r#"
+        # mountain car with 8 tilings
+        num_tilings = 8
+        size = 4_096
+        seed = 7
+        offsets = [1, 5]
+
+        [[dimension]]
+        name = "position"
+        min = -1.2
+        max = 0.6
+        tiles = 8
+        clamp = true
+
+        [[dimension]]
+        name = "heading # not a comment"
+        min = 0
+        max = 5.0
+        tiles = 10
+        wrap = true
+    "#;
+
+    fn error(source: &str) -> String {
+        CoderConfig::from_toml(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn parses_full_config() {
+        let config = CoderConfig::from_toml(MOUNTAIN_CAR).unwrap();
+        assert_eq!(config.num_tilings(), 8);
+        assert_eq!(config.size(), 4096);
+        assert_eq!(config.seed(), Some(7));
+        assert_eq!(config.offsets(), &Offsets::Displacement(vec![1, 5]));
+        assert_eq!(config.dimensions().len(), 2);
+        assert_eq!(config.dimensions()[1].name.as_deref(), Some("heading # not a comment"));
+        assert_eq!(config.wrap_widths(), vec![None, Some(10)]);
+        assert_eq!(config.scale(&[0.6, 2.5]), vec![8.0, 5.0]);
+        assert_eq!(config.scale(&[0.7, 2.5]), vec![8.0, 5.0]);
+    }
+
+    #[test]
+    fn helpful_errors() {
+        assert_eq!(error("size = 10\n[[dimension]]\nmin = 0\nmax = 1\ntiles = 2"), "invalid configuration: missing `num_tilings`");
+        assert_eq!(
+            error("num_tilings = 8\nsize = 10\n[[dimension]]\nmin = 0\nmax = 1\ntile = 2"),
+            "invalid configuration: line 3: [[dimension]] is missing `tiles`"
+        );
+        assert_eq!(
+            error("num_tilings = 8\nsize = 10\n[[dimension]]\nmin = 0\nmax = 1\ntiles = 2\ntile = 2"),
+            "invalid configuration: line 7: unknown key `tile` (expected one of: name, min, max, tiles, wrap, clamp)"
+        );
+        assert_eq!(
+            error("num_tilings = 8\nsize = 10\n[[dimension]]\nmin = 1\nmax = 1\ntiles = 2"),
+            "invalid configuration: dimension 0: `min` (1) must be less than `max` (1)"
+        );
+        assert_eq!(error("num_tilings = \"eight\""), "invalid configuration: line 1: `num_tilings` must be an integer, found a string");
+        assert_eq!(error("num_tilings = 8\n[tiling]"), "invalid configuration: line 2: unknown table `[tiling]` (expected `[[dimension]]`)");
+        assert_eq!(
+            error("num_tilings = 8\nsize = 8\noffsets = \"staggered\""),
+            "invalid configuration: line 3: unknown `offsets` `staggered` (expected \"tiles3\", \"coprime\", \"random\", or an array of integer displacements)"
+        );
+        assert_eq!(error("num_tilings = 8\nsize = 8\noffsets = \"random\""), "invalid configuration: line 3: random `offsets` require a `seed`");
+        assert_eq!(
+            error("num_tilings = 8\nsize = 8\noffsets = [1, 3]\n[[dimension]]\nmin = 0\nmax = 1\ntiles = 2"),
+            "invalid configuration: the displacement vector has 2 entries but there are 1 dimensions"
+        );
+    }
+
+    #[test]
+    fn random_offsets_use_seed() {
+        let config = CoderConfig::from_toml("offsets = \"random\"\nseed = 3\nnum_tilings = 8\nsize = 64\n[[dimension]]\nmin = 0\nmax = 1\ntiles = 2").unwrap();
+        assert_eq!(config.offsets(), &Offsets::Random(3));
+    }
+
+    #[test]
+    fn config_from_code_is_validated() {
+        assert!(CoderConfig::new(8, 1024, vec![DimensionConfig::new(0.0, 1.0, 4)]).is_ok());
+        assert!(CoderConfig::new(0, 1024, vec![DimensionConfig::new(0.0, 1.0, 4)]).is_err());
+        assert!(CoderConfig::new(8, 1024, vec![]).is_err());
+    }
+}
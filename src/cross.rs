@@ -0,0 +1,122 @@
+use crate::{TileCoder, IHT};
+
+/// The conjunction of two tile coders, whose features are the pairs of an active tile from each coder, hashed into
+/// a shared index space by an IHT of their own. See [`cross`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrossCoder {
+    a: TileCoder,
+    b: TileCoder,
+    iht: IHT,
+}
+
+/// Cross two coders, producing features which are only active when a tile of `a` and a tile of `b` are active
+/// together. Every pair of active tiles is a feature, so a crossed point has `a.num_tilings() * b.num_tilings()`
+/// indices, allocated from an IHT with the given size.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{cross, Dim, TileCoder};
+/// let position = TileCoder::new(2, 64, vec![Dim::bins(0.0, 1.0, 4)]);
+/// let goal = TileCoder::new(2, 64, vec![Dim::bins(0.0, 1.0, 4)]);
+/// let mut crossed = cross(position, goal, 1024);
+///
+/// let a = crossed.tiles(&[0.1], &[0.9], None);
+/// assert_eq!(a, vec![0, 1, 2, 3]);
+/// // the same position with a different goal shares none of the crossed features
+/// let b = crossed.tiles(&[0.1], &[0.2], None);
+/// assert!(b.iter().all(|i| !a.contains(i)));
+/// ```
+pub fn cross(a: TileCoder, b: TileCoder, size: usize) -> CrossCoder {
+    CrossCoder { a, b, iht: IHT::new(size) }
+}
+
+impl CrossCoder {
+    /// The first coder of the cross
+    pub fn a(&self) -> &TileCoder {
+        &self.a
+    }
+
+    /// The second coder of the cross
+    pub fn b(&self) -> &TileCoder {
+        &self.b
+    }
+
+    /// The IHT the crossed features are allocated from
+    pub fn iht(&self) -> &IHT {
+        &self.iht
+    }
+
+    /// The number of indices returned by `tiles`
+    pub fn num_features(&self) -> usize {
+        self.a.num_tilings() * self.b.num_tilings()
+    }
+
+    // the key of the crossed feature for a pair of active tiles
+    fn key(a: usize, b: usize, ints: Option<&[isize]>) -> Vec<isize> {
+        let mut key = Vec::with_capacity(2 + ints.map_or(0, |i| i.len()));
+        key.push(a as isize);
+        key.push(b as isize);
+        if let Some(ints) = ints {
+            key.extend(ints);
+        }
+        key
+    }
+
+    /// Encode a point as the crossed features of `floats_a` (encoded by the first coder) and `floats_b` (encoded by
+    /// the second coder). The ints are only part of the crossed features, not of either coder's tiles.
+    pub fn tiles(&mut self, floats_a: &[f64], floats_b: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        let a = self.a.tiles(floats_a, None);
+        let b = self.b.tiles(floats_b, None);
+        let mut tiles = Vec::with_capacity(a.len() * b.len());
+        for &i in a.iter() {
+            for &j in b.iter() {
+                tiles.push(self.iht.get_index(CrossCoder::key(i, j, ints)));
+            }
+        }
+        tiles
+    }
+
+    /// The read-only version of `tiles`; a crossed feature is `None` if either of its tiles is unseen
+    pub fn tiles_read_only(&self, floats_a: &[f64], floats_b: &[f64], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let a = self.a.tiles_read_only(floats_a, None);
+        let b = self.b.tiles_read_only(floats_b, None);
+        let mut tiles = Vec::with_capacity(a.len() * b.len());
+        for &i in a.iter() {
+            for &j in b.iter() {
+                tiles.push(match (i, j) {
+                    (Some(i), Some(j)) => self.iht.get_index_read_only(CrossCoder::key(i, j, ints)),
+                    _ => None,
+                });
+            }
+        }
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dim;
+
+    #[test]
+    fn every_pair_is_a_feature() {
+        let mut crossed = cross(TileCoder::new(3, 64, vec![Dim::width(1.0)]), TileCoder::new(2, 64, vec![Dim::width(1.0)]), 64);
+        assert_eq!(crossed.num_features(), 6);
+        assert_eq!(crossed.tiles(&[0.0], &[0.0], None), vec![0, 1, 2, 3, 4, 5]);
+        // sharing a tile in both coders shares the crossed feature
+        let near = crossed.tiles(&[0.1], &[0.1], None);
+        assert!(near.iter().any(|i| *i < 6));
+        assert_eq!(crossed.tiles_read_only(&[0.1], &[0.1], None), near.into_iter().map(Some).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn ints_salt_the_cross() {
+        let mut crossed = cross(TileCoder::new(2, 64, vec![Dim::width(1.0)]), TileCoder::new(2, 64, vec![Dim::width(1.0)]), 64);
+        let a = crossed.tiles(&[0.0], &[0.0], Some(&[0]));
+        let b = crossed.tiles(&[0.0], &[0.0], Some(&[1]));
+        assert!(b.iter().all(|i| !a.contains(i)));
+        assert_eq!(crossed.a().iht().count(), 2);
+        assert_eq!(crossed.tiles_read_only(&[0.0], &[0.0], Some(&[2])), vec![None; 4]);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::IHT;
+
+/// A batch of encodings as a sparse one-hot matrix in compressed sparse row (CSR) form, with one row per point and
+/// one column per index of the table. The column indices of each row are sorted and unique, and a tile shared by
+/// several tilings of a point (i.e. a collision) has the number of tilings that hit it as its value.
+///
+/// The raw parts are laid out the way sparse linear algebra libraries expect them, e.g.
+/// `sprs::CsMat::new((rows, cols), indptr, indices, data)`.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::IHT;
+/// let mut iht = IHT::new(1024);
+/// let points: [&[f64]; 2] = [&[0.0, 0.0], &[0.5, 0.0]];
+/// let matrix = iht.tiles_batch_csr(4, &points, None);
+/// assert_eq!((matrix.rows(), matrix.cols()), (2, 1024));
+/// assert_eq!(matrix.row(1).0, &iht.tiles(4, &[0.5, 0.0], None)[..]);
+///
+/// let weights = vec![0.5; 1024];
+/// assert_eq!(matrix.mul_vec(&weights), vec![2.0, 2.0]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsrMatrix {
+    cols: usize,
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    data: Vec<f64>,
+}
+
+impl CsrMatrix {
+    /// Build the matrix from a row-major batch of `num_tilings` indices per point (as returned by
+    /// [`IHT::tiles_batch`]), where every index is `< cols`
+    pub fn from_batch(batch: &[usize], num_tilings: usize, cols: usize) -> CsrMatrix {
+        assert!(num_tilings > 0 && batch.len().is_multiple_of(num_tilings), "the batch must hold a whole number of points");
+        let mut matrix = CsrMatrix {
+            cols,
+            indptr: Vec::with_capacity(batch.len() / num_tilings + 1),
+            indices: Vec::with_capacity(batch.len()),
+            data: Vec::with_capacity(batch.len()),
+        };
+        matrix.indptr.push(0);
+        let mut row = Vec::with_capacity(num_tilings);
+        for tiles in batch.chunks(num_tilings) {
+            row.clear();
+            row.extend_from_slice(tiles);
+            row.sort_unstable();
+            for &index in row.iter() {
+                assert!(index < cols, "index {} is out of bounds for {} columns", index, cols);
+                if matrix.indices.len() > *matrix.indptr.last().unwrap() && matrix.indices.last() == Some(&index) {
+                    *matrix.data.last_mut().unwrap() += 1.0;
+                } else {
+                    matrix.indices.push(index);
+                    matrix.data.push(1.0);
+                }
+            }
+            matrix.indptr.push(matrix.indices.len());
+        }
+        matrix
+    }
+
+    /// The number of rows, i.e. points
+    pub fn rows(&self) -> usize {
+        self.indptr.len() - 1
+    }
+
+    /// The number of columns, i.e. the size of the table
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The offsets of each row into `indices` and `data`, with one more entry than there are rows
+    pub fn indptr(&self) -> &[usize] {
+        &self.indptr
+    }
+
+    /// The column indices of the non-zero entries, row by row
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// The values of the non-zero entries, row by row
+    pub fn data(&self) -> &[f64] {
+        &self.data
+    }
+
+    /// The column indices and values of one row
+    pub fn row(&self, row: usize) -> (&[usize], &[f64]) {
+        let range = self.indptr[row]..self.indptr[row + 1];
+        (&self.indices[range.clone()], &self.data[range])
+    }
+
+    /// Multiply the matrix by a dense vector with one entry per column, e.g. to evaluate a linear function of every
+    /// point at once
+    pub fn mul_vec(&self, vector: &[f64]) -> Vec<f64> {
+        assert_eq!(vector.len(), self.cols, "expected one entry per column");
+        (0..self.rows())
+            .map(|row| {
+                let (indices, data) = self.row(row);
+                indices.iter().zip(data.iter()).map(|(&i, &x)| vector[i] * x).sum()
+            })
+            .collect()
+    }
+
+    /// Take the `(rows, cols)` shape, `indptr`, `indices`, and `data`
+    pub fn into_raw_parts(self) -> ((usize, usize), Vec<usize>, Vec<usize>, Vec<f64>) {
+        ((self.rows(), self.cols), self.indptr, self.indices, self.data)
+    }
+}
+
+impl IHT {
+    /// Encode many points at once as a sparse one-hot matrix, see [`IHT::tiles_batch`] and [`CsrMatrix`]
+    pub fn tiles_batch_csr(&mut self, num_tilings: usize, points: &[&[f64]], ints: Option<&[isize]>) -> CsrMatrix {
+        let batch = self.tiles_batch(num_tilings, points, ints);
+        CsrMatrix::from_batch(&batch, num_tilings, self.size())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collisions_are_summed() {
+        let matrix = CsrMatrix::from_batch(&[3, 1, 3, 0, 2, 2, 2, 2], 4, 4);
+        assert_eq!(matrix.indptr(), &[0, 3, 4]);
+        assert_eq!(matrix.row(0), (&[0, 1, 3][..], &[1.0, 1.0, 2.0][..]));
+        assert_eq!(matrix.row(1), (&[2][..], &[4.0][..]));
+        assert_eq!(matrix.mul_vec(&[1.0, 2.0, 3.0, 4.0]), vec![11.0, 12.0]);
+        assert_eq!(matrix.into_raw_parts().0, (2, 4));
+    }
+}
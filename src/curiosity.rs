@@ -0,0 +1,155 @@
+fn mean_squared_error(prediction: &[f64], target: &[f64]) -> f64 {
+    let sum: f64 = prediction.iter().zip(target).map(|(p, t)| (t - p) * (t - p)).sum();
+    sum / prediction.len().max(1) as f64
+}
+
+/// A small forward model over tile features, whose prediction error can be used as an
+/// intrinsic ("curiosity") reward.
+///
+/// The model linearly predicts the next observation from the tiles of the current state (and
+/// typically the action, passed via `ints` when tiling). Transitions the model has already learned
+/// produce little error, novel ones produce a lot, so the error is a natural exploration bonus.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{Curiosity, IHT};
+/// let mut iht = IHT::new(1024);
+/// let mut curiosity = Curiosity::new(1024, 1, 0.5);
+///
+/// let tiles = iht.tiles(8, &[0.3], Some(&[1]));
+/// let first = curiosity.reward(&tiles, &[0.4]);
+/// for _ in 0..50 {
+///     curiosity.reward(&tiles, &[0.4]);
+/// }
+///
+/// // the transition is now well predicted, so it is no longer interesting
+/// assert!(curiosity.reward(&tiles, &[0.4]) < first);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Curiosity {
+    size: usize,
+    outputs: usize,
+    alpha: f64,
+    scale: f64,
+    weights: Vec<f64>,
+}
+
+impl Curiosity {
+    /// Create a forward model for tile indices `< size` predicting `outputs` values. `alpha` is the
+    /// step size, which is divided by the number of active tiles on each update.
+    pub fn new(size: usize, outputs: usize, alpha: f64) -> Curiosity {
+        Curiosity {
+            size,
+            outputs,
+            alpha,
+            scale: 1.0,
+            weights: vec![0.0; size * outputs],
+        }
+    }
+
+    /// Set the factor the prediction error is multiplied by to produce the reward (`1.0` by default)
+    pub fn with_scale(mut self, scale: f64) -> Curiosity {
+        self.scale = scale;
+        self
+    }
+
+    /// The number of tile indices the model has weights for
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of values the model predicts
+    pub fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    /// Predict the next observation from the active tiles. Panics if a tile is `>= size`.
+    pub fn predict(&self, tiles: &[usize]) -> Vec<f64> {
+        let mut prediction = vec![0.0; self.outputs];
+        for &tile in tiles {
+            assert!(tile < self.size, "tile {} is out of range for a curiosity model of size {}", tile, self.size);
+            let w = &self.weights[tile * self.outputs..(tile + 1) * self.outputs];
+            for (p, w) in prediction.iter_mut().zip(w) {
+                *p += w;
+            }
+        }
+        prediction
+    }
+
+    /// The mean squared error of the model's prediction of `next` from the active tiles, without learning anything.
+    /// Panics unless `next` has `outputs` values.
+    pub fn error(&self, tiles: &[usize], next: &[f64]) -> f64 {
+        self.check_outputs(next);
+        mean_squared_error(&self.predict(tiles), next)
+    }
+
+    fn check_outputs(&self, next: &[f64]) {
+        assert_eq!(next.len(), self.outputs, "the curiosity model predicts {} values, but was given {}", self.outputs, next.len());
+    }
+
+    /// Update the model towards predicting `next` from the active tiles, returning the error before the update.
+    /// Panics unless `next` has `outputs` values.
+    pub fn update(&mut self, tiles: &[usize], next: &[f64]) -> f64 {
+        self.check_outputs(next);
+        let prediction = self.predict(tiles);
+        let error = mean_squared_error(&prediction, next);
+
+        if !tiles.is_empty() {
+            let step = self.alpha / tiles.len() as f64;
+            for &tile in tiles {
+                let w = &mut self.weights[tile * self.outputs..(tile + 1) * self.outputs];
+                for ((w, p), n) in w.iter_mut().zip(&prediction).zip(next) {
+                    *w += step * (n - p);
+                }
+            }
+        }
+
+        error
+    }
+
+    /// Learn from the transition and return the scaled prediction error as an intrinsic reward. Add this to the
+    /// environment's reward to get a curiosity-driven reward signal.
+    pub fn reward(&mut self, tiles: &[usize], next: &[f64]) -> f64 {
+        self.scale * self.update(tiles, next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn untrained_model_predicts_zero() {
+        let curiosity = Curiosity::new(16, 2, 0.1);
+        assert_eq!(curiosity.predict(&[0, 3, 5]), vec![0.0, 0.0]);
+        assert_eq!(curiosity.error(&[0, 3, 5], &[1.0, 3.0]), 5.0);
+    }
+
+    #[test]
+    fn full_step_learns_in_one_update() {
+        let mut curiosity = Curiosity::new(16, 2, 1.0);
+        let error = curiosity.update(&[1, 2], &[1.0, -2.0]);
+        assert_eq!(error, 2.5);
+        assert_eq!(curiosity.predict(&[1, 2]), vec![1.0, -2.0]);
+        assert_eq!(curiosity.error(&[1, 2], &[1.0, -2.0]), 0.0);
+    }
+
+    #[test]
+    fn reward_is_scaled() {
+        let mut curiosity = Curiosity::new(16, 1, 0.5).with_scale(0.1);
+        assert!((curiosity.reward(&[4], &[2.0]) - 0.4).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "predicts 2 values, but was given 1")]
+    fn next_must_have_every_output() {
+        Curiosity::new(16, 2, 0.5).update(&[4], &[2.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "tile 16 is out of range")]
+    fn tiles_must_be_in_range() {
+        Curiosity::new(16, 1, 0.5).predict(&[16]);
+    }
+}
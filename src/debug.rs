@@ -0,0 +1,77 @@
+use crate::{base_hash, calculate_coords, calculate_q_floats, IHT};
+
+// the coordinates of every tiling in turn, exactly as the tiling functions compute them
+fn debug_coords(num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<Vec<isize>> {
+    let q_floats = calculate_q_floats(floats, num_tilings);
+    (0..num_tilings).map(|tiling| calculate_coords(tiling, num_tilings, &q_floats, &ints)).collect()
+}
+
+/// The same as the free `tiles` function, but returning the coordinates which produced each index alongside it, for
+/// working out why two points share a feature: a shared index with different coordinates is a hash collision
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{tiles, tiles_debug};
+/// let debug = tiles_debug(1024, 8, &[3.6, 7.21], None);
+/// assert_eq!(debug.iter().map(|(index, _)| *index).collect::<Vec<_>>(), tiles(1024, 8, &[3.6, 7.21], None));
+/// // the coordinates are the tiling number followed by the tile in each dimension
+/// assert_eq!(debug[0].1, vec![0, 3, 7]);
+/// ```
+pub fn tiles_debug(size: usize, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<(usize, Vec<isize>)> {
+    debug_coords(num_tilings, floats, ints)
+        .into_iter()
+        .map(|coords| (base_hash(&coords[..]) % size, coords))
+        .collect()
+}
+
+impl IHT {
+    /// The same as `tiles`, but returning the coordinates which produced each index alongside it. Two points which
+    /// unexpectedly share an index either share those coordinates, or collided because the table was full.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// let a = iht.tiles_debug(8, &[3.6, 7.21], None);
+    /// let b = iht.tiles_debug(8, &[3.7, 7.21], None);
+    ///
+    /// // the fourth tiling is the only one where the points land in different tiles
+    /// assert_eq!((a[3].1.clone(), b[3].1.clone()), (vec![3, 3, 8], vec![3, 4, 8]));
+    /// assert_eq!((a[3].0, b[3].0), (3, 8));
+    /// ```
+    pub fn tiles_debug(&mut self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<(usize, Vec<isize>)> {
+        debug_coords(num_tilings, floats, ints)
+            .into_iter()
+            .map(|coords| (self.get_index_ref(&coords), coords))
+            .collect()
+    }
+
+    /// The read-only version of `tiles_debug`, where a tile which isn't in the table has an index of `None`
+    pub fn tiles_read_only_debug(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<(Option<usize>, Vec<isize>)> {
+        debug_coords(num_tilings, floats, ints)
+            .into_iter()
+            .map(|coords| (self.get_index_read_only_ref(&coords), coords))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_tiles_and_shows_collisions() {
+        let mut iht = IHT::new(8);
+        let mut plain = iht.clone();
+        let first = iht.tiles_debug(8, &[-1.3, 2.0], Some(&[1]));
+        assert_eq!(first.iter().map(|(i, _)| *i).collect::<Vec<_>>(), plain.tiles(8, &[-1.3, 2.0], Some(&[1])));
+        assert!(first.iter().enumerate().all(|(t, (_, coords))| coords[0] == t as isize && coords[3] == 1));
+
+        // the table is now full, so a far away point shares indices with different coordinates
+        let far = iht.tiles_debug(8, &[40.0, 2.0], Some(&[1]));
+        assert!(far.iter().all(|(i, coords)| first.iter().any(|(j, other)| i == j && coords != other)));
+        assert!(iht.tiles_read_only_debug(8, &[40.0, 2.0], Some(&[1])).iter().all(|(i, _)| i.is_none()));
+    }
+}
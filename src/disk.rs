@@ -0,0 +1,353 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::tag::{hash_fingerprint, Tag, PROBE};
+use crate::{base_hash, calculate_coords_into, calculate_q_floats, stable_hash, CoderConfig, Error, HashFunction};
+
+const MAGIC: &[u8; 8] = b"TCDISK02";
+// the magic, then the size, count, overfull count, number of slots, and end of the key region, then the tag as crate
+// version, hash fingerprint, and config fingerprint, as u64s
+const HEADER_LEN: u64 = 72;
+// each slot holds the hash of its key, the offset of the key in the file (0 for an empty slot), and its index
+const SLOT_LEN: u64 = 24;
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn invalid_tag(error: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+// a fingerprint of both hashes a table depends on: the one placing its keys in the slot table, and the one giving
+// indices to the tiles it has no room for
+fn hash_fingerprints() -> u64 {
+    stable_hash(PROBE) ^ hash_fingerprint(HashFunction::Sip).rotate_left(32)
+}
+
+/// An IHT whose dictionary lives in a file instead of in memory, for index spaces too large to hold in RAM. The
+/// dictionary is an open-addressing hash table at the start of the file, followed by the coordinates of each tile;
+/// the operating system's page cache keeps the hot parts of it in memory. It hands out exactly the same indices as an
+/// [`IHT`](crate::IHT) fed the same points, including when it is overfull.
+///
+/// Since every lookup may touch the disk, the tiling functions return `io::Result`s. The count and overfull count
+/// are written back to the file by [`DiskIHT::flush`], and when the table is dropped.
+///
+/// The file also records the version of this crate which created it and a fingerprint of the hashes it was filled
+/// with, and opening one created by a version with a different major or minor version, or with different hashes, fails
+/// with an `InvalidData` error rather than looking tiles up in the wrong places.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{DiskIHT, IHT};
+/// let path = std::env::temp_dir().join("tilecoding-disk-iht-doctest");
+/// let mut disk = DiskIHT::create(&path, 1024).unwrap();
+/// let mut iht = IHT::new(1024);
+/// assert_eq!(disk.tiles(8, &[3.6, 7.21], None).unwrap(), iht.tiles(8, &[3.6, 7.21], None));
+/// drop(disk);
+///
+/// let disk = DiskIHT::open(&path).unwrap();
+/// assert_eq!(disk.count(), 8);
+/// assert_eq!(disk.tiles_read_only(8, &[3.6, 7.21], None).unwrap(), iht.tiles_read_only(8, &[3.6, 7.21], None));
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct DiskIHT {
+    file: File,
+    size: usize,
+    count: usize,
+    overfull_count: usize,
+    slots: u64,
+    key_end: u64,
+    // the tag, as written to the header
+    tag: [u64; 3],
+}
+
+impl DiskIHT {
+    /// Create a new table with the given size in the file at `path`, replacing anything already there. The file
+    /// starts out sparse on filesystems which support it, so a huge table doesn't take up disk space until it fills.
+    pub fn create<P: AsRef<Path>>(path: P, size: usize) -> io::Result<DiskIHT> {
+        DiskIHT::create_tagged(path, size, 0)
+    }
+
+    /// The same as [`DiskIHT::create`], but also recording the [`CoderConfig`] the table is filled with, so that
+    /// [`DiskIHT::open_with_config`] can check it is reopened for the same one
+    pub fn create_with_config<P: AsRef<Path>>(path: P, size: usize, config: &CoderConfig) -> io::Result<DiskIHT> {
+        DiskIHT::create_tagged(path, size, config.fingerprint())
+    }
+
+    fn create_tagged<P: AsRef<Path>>(path: P, size: usize, config: u64) -> io::Result<DiskIHT> {
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(path)?;
+        // keep the table at most half full, so probe sequences stay short
+        let slots = (size.max(1) * 2).next_power_of_two() as u64;
+        let key_end = HEADER_LEN + slots * SLOT_LEN;
+        file.set_len(key_end)?;
+        let mut iht = DiskIHT {
+            file,
+            size,
+            count: 0,
+            overfull_count: 0,
+            slots,
+            key_end,
+            tag: Tag::new(hash_fingerprints(), config).to_words(),
+        };
+        iht.flush()?;
+        Ok(iht)
+    }
+
+    /// Open a table previously created with [`DiskIHT::create`]
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<DiskIHT> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let mut header = [0; HEADER_LEN as usize];
+        file.read_exact(&mut header[..8]).map_err(|_| invalid("truncated disk IHT header"))?;
+        if &header[..8] != MAGIC {
+            return Err(invalid("not a disk IHT file"));
+        }
+        file.read_exact(&mut header[8..]).map_err(|_| invalid("truncated disk IHT header"))?;
+        let field = |i: usize| {
+            let mut word = [0; 8];
+            word.copy_from_slice(&header[8 * i..8 * (i + 1)]);
+            u64::from_le_bytes(word)
+        };
+        let tag = [field(6), field(7), field(8)];
+        Tag::from_words(tag).check_version("disk IHT").map_err(invalid_tag)?;
+        Tag::from_words(tag).check_hash("disk IHT", hash_fingerprints()).map_err(invalid_tag)?;
+        let iht = DiskIHT {
+            size: field(1) as usize,
+            count: field(2) as usize,
+            overfull_count: field(3) as usize,
+            slots: field(4),
+            key_end: field(5),
+            tag,
+            file,
+        };
+        let table_end = iht.slots.checked_mul(SLOT_LEN).and_then(|t| t.checked_add(HEADER_LEN));
+        if !iht.slots.is_power_of_two() || table_end.is_none_or(|end| iht.key_end < end) || iht.count > iht.size {
+            return Err(invalid("corrupt disk IHT header"));
+        }
+        Ok(iht)
+    }
+
+    /// Open a table created with [`DiskIHT::create_with_config`], failing with an `InvalidData` error unless it was
+    /// created for exactly the same configuration, ignoring the names of the dimensions
+    pub fn open_with_config<P: AsRef<Path>>(path: P, config: &CoderConfig) -> io::Result<DiskIHT> {
+        let iht = DiskIHT::open(path)?;
+        Tag::from_words(iht.tag).check_config("disk IHT", config).map_err(invalid_tag)?;
+        Ok(iht)
+    }
+
+    /// Write the count and overfull count back to the file and flush it to disk
+    pub fn flush(&mut self) -> io::Result<()> {
+        let mut header = Vec::with_capacity(HEADER_LEN as usize);
+        header.extend_from_slice(MAGIC);
+        for &field in [self.size as u64, self.count as u64, self.overfull_count as u64, self.slots, self.key_end].iter().chain(&self.tag) {
+            header.extend_from_slice(&field.to_le_bytes());
+        }
+        self.write_at(0, &header)?;
+        self.file.sync_data()
+    }
+
+    /// The size of the table; no index will be `>= size`
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of tiles in the table
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Whether the table is full, so that new tiles collide with existing ones
+    pub fn full(&self) -> bool {
+        self.count >= self.size
+    }
+
+    /// The number of lookups that collided because the table was full
+    pub fn overfull_count(&self) -> usize {
+        self.overfull_count
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(buf)
+    }
+
+    fn write_at(&self, offset: u64, buf: &[u8]) -> io::Result<()> {
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(offset))?;
+        file.write_all(buf)
+    }
+
+    fn read_u64s<const N: usize>(&self, offset: u64) -> io::Result<[u64; N]> {
+        let mut bytes = vec![0; 8 * N];
+        self.read_at(offset, &mut bytes)?;
+        let mut words = [0; N];
+        for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(8)) {
+            let mut b = [0; 8];
+            b.copy_from_slice(chunk);
+            *word = u64::from_le_bytes(b);
+        }
+        Ok(words)
+    }
+
+    fn key_matches(&self, key_offset: u64, coords: &[isize]) -> io::Result<bool> {
+        let [len] = self.read_u64s::<1>(key_offset)?;
+        if len != coords.len() as u64 {
+            return Ok(false);
+        }
+        let mut bytes = vec![0; 8 * coords.len()];
+        self.read_at(key_offset + 8, &mut bytes)?;
+        Ok(bytes.chunks_exact(8).zip(coords).all(|(chunk, &c)| chunk == (c as i64).to_le_bytes()))
+    }
+
+    // find the slot holding `coords`, or the empty slot where they would go, along with the index if they're present
+    fn probe(&self, coords: &[isize], hash: u64) -> io::Result<(u64, Option<usize>)> {
+        let mut slot = hash & (self.slots - 1);
+        for _ in 0..self.slots {
+            let offset = HEADER_LEN + slot * SLOT_LEN;
+            let [slot_hash, key_offset, index] = self.read_u64s::<3>(offset)?;
+            if key_offset == 0 {
+                return Ok((offset, None));
+            }
+            if slot_hash == hash && self.key_matches(key_offset, coords)? {
+                return Ok((offset, Some(index as usize)));
+            }
+            slot = (slot + 1) & (self.slots - 1);
+        }
+        // a table is never more than half full, so only a corrupt file can have no free slot
+        Err(invalid("disk IHT table has no free slot"))
+    }
+
+    fn get_index(&mut self, coords: &[isize]) -> io::Result<usize> {
+        let hash = stable_hash(coords);
+        let (slot_offset, existing) = self.probe(coords, hash)?;
+        if let Some(index) = existing {
+            return Ok(index);
+        }
+        if self.full() {
+            self.overfull_count += 1;
+            return Ok(base_hash(coords) % self.size);
+        }
+
+        let index = self.count;
+        let mut key = Vec::with_capacity(8 * (1 + coords.len()));
+        key.extend_from_slice(&(coords.len() as u64).to_le_bytes());
+        for &c in coords {
+            key.extend_from_slice(&(c as i64).to_le_bytes());
+        }
+        self.write_at(self.key_end, &key)?;
+        let mut slot = Vec::with_capacity(SLOT_LEN as usize);
+        for &word in &[hash, self.key_end, index as u64] {
+            slot.extend_from_slice(&word.to_le_bytes());
+        }
+        self.write_at(slot_offset, &slot)?;
+        self.key_end += key.len() as u64;
+        self.count += 1;
+        Ok(index)
+    }
+
+    /// Encode a point as `num_tilings` tile indices, see [`IHT::tiles`](crate::IHT::tiles)
+    pub fn tiles(&mut self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> io::Result<Vec<usize>> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut tiles = Vec::with_capacity(num_tilings);
+        let mut coords = Vec::new();
+        for tiling in 0..num_tilings {
+            calculate_coords_into(&mut coords, tiling, num_tilings, &q_floats, &ints);
+            tiles.push(self.get_index(&coords)?);
+        }
+        Ok(tiles)
+    }
+
+    /// The read-only version of `tiles`, see [`IHT::tiles_read_only`](crate::IHT::tiles_read_only)
+    pub fn tiles_read_only(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> io::Result<Vec<Option<usize>>> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut tiles = Vec::with_capacity(num_tilings);
+        let mut coords = Vec::new();
+        for tiling in 0..num_tilings {
+            calculate_coords_into(&mut coords, tiling, num_tilings, &q_floats, &ints);
+            tiles.push(self.probe(&coords, stable_hash(&coords))?.1);
+        }
+        Ok(tiles)
+    }
+}
+
+impl Drop for DiskIHT {
+    fn drop(&mut self) {
+        // there's nowhere to report an error from here; call `flush` first to see it
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DimensionConfig, IHT};
+    use std::convert::TryInto;
+
+    #[test]
+    fn matches_iht_and_survives_reopening() {
+        let path = std::env::temp_dir().join(format!("tilecoding-disk-iht-test-{}", std::process::id()));
+        let config = CoderConfig::new(4, 100, vec![DimensionConfig::new(0.0, 1.0, 1), DimensionConfig::new(0.0, 1.0, 1)]).unwrap();
+        let mut disk = DiskIHT::create_with_config(&path, 100, &config).unwrap();
+        let mut iht = IHT::new(100);
+        for i in 0..30 {
+            let x = i as f64 * 0.7 - 10.0;
+            assert_eq!(disk.tiles(4, &[x, 1.0], Some(&[i % 3])).unwrap(), iht.tiles(4, &[x, 1.0], Some(&[i % 3])));
+        }
+        assert!(disk.full());
+        assert_eq!(disk.overfull_count(), iht.overfull_count);
+        drop(disk);
+
+        let disk = DiskIHT::open_with_config(&path, &config).unwrap();
+        assert_eq!((disk.size(), disk.count(), disk.overfull_count()), (100, 100, iht.overfull_count));
+        assert_eq!(disk.tiles_read_only(4, &[-10.0, 1.0], Some(&[0])).unwrap(), iht.tiles_read_only(4, &[-10.0, 1.0], Some(&[0])));
+        drop(disk);
+        assert!(DiskIHT::open_with_config(&path, &config.clone().with_offsets(crate::Offsets::Coprime).unwrap()).is_err());
+
+        // a file without a tag can't be opened
+        let mut untagged = b"TCDISK01".to_vec();
+        for &field in &[100, 0, 0, 256, 48 + 256 * SLOT_LEN] {
+            untagged.extend_from_slice(&field.to_le_bytes());
+        }
+        untagged.resize((48 + 256 * SLOT_LEN) as usize, 0);
+        std::fs::write(&path, &untagged).unwrap();
+        assert!(DiskIHT::open(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+        assert!(DiskIHT::open(&path).is_err());
+    }
+
+    #[test]
+    fn rejects_corrupt_files() {
+        let path = std::env::temp_dir().join(format!("tilecoding-disk-iht-corrupt-{}", std::process::id()));
+        let disk = DiskIHT::create(&path, 1).unwrap();
+        // every slot occupied by a key which doesn't match
+        for slot in 0..disk.slots {
+            disk.write_at(HEADER_LEN + slot * SLOT_LEN, &[1; SLOT_LEN as usize]).unwrap();
+        }
+        assert_eq!(disk.tiles_read_only(1, &[0.0], None).unwrap_err().to_string(), "disk IHT table has no free slot");
+        drop(disk);
+
+        // a number of slots so large the table's end overflows
+        let file = std::fs::read(&path).unwrap();
+        let mut header = file.clone();
+        header[32..40].copy_from_slice(&(1u64 << 62).to_le_bytes());
+        std::fs::write(&path, &header).unwrap();
+        assert_eq!(DiskIHT::open(&path).unwrap_err().to_string(), "corrupt disk IHT header");
+
+        // a tag from another minor release, or with another hash
+        let word = |i: usize| u64::from_le_bytes(file[8 * i..8 * (i + 1)].try_into().unwrap());
+        let (major, minor) = (word(6) >> 42, (word(6) >> 21) & 0x1f_ffff);
+        let mut retagged = file.clone();
+        retagged[48..56].copy_from_slice(&((major << 42) | ((minor + 1) << 21)).to_le_bytes());
+        std::fs::write(&path, &retagged).unwrap();
+        assert_eq!(DiskIHT::open(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        let mut retagged = file;
+        retagged[56] ^= 1;
+        std::fs::write(&path, &retagged).unwrap();
+        assert!(DiskIHT::open(&path).unwrap_err().to_string().contains("hash"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
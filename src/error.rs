@@ -0,0 +1,37 @@
+use std::fmt;
+
+/// The errors which can occur when loading or validating tile coding state
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Serialized data was malformed, truncated, or written by an unsupported version
+    Format(String),
+    /// A coder configuration was malformed or described an invalid coder
+    Config(String),
+    /// The indices of a table don't fit in the requested index type
+    IndexType(String),
+    /// Reading or writing a file failed
+    Io(String),
+    /// The arguments to a tiling function were invalid, see [`crate::try_tiles`]
+    Input(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Format(msg) => write!(f, "invalid format: {}", msg),
+            Error::Config(msg) => write!(f, "invalid configuration: {}", msg),
+            Error::IndexType(msg) => write!(f, "invalid index type: {}", msg),
+            Error::Io(msg) => write!(f, "i/o error: {}", msg),
+            Error::Input(msg) => write!(f, "invalid input: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Error {
+        Error::Io(e.to_string())
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{Dim, Offsets, TileCoder};
+
+// the number of distinct coordinates tiling `tiling` gives the scaled values `[lo, hi]` of dimension `dim`. The
+// coordinate `(q + b) / n` never decreases as `q` grows and steps by at most one, so every value between the
+// coordinates of the ends is reached.
+fn coords_spanned(tiling: usize, num_tilings: usize, offsets: &Offsets, dim: usize, lo: f64, hi: f64) -> usize {
+    let n = num_tilings as isize;
+    let b = offsets.offset(tiling, num_tilings, dim);
+    let q = |x: f64| (x * num_tilings as f64).floor() as isize;
+    ((q(hi) + b) / n - (q(lo) + b) / n + 1) as usize
+}
+
+fn reachable(num_tilings: usize, offsets: &Offsets, spans: &[(f64, f64, Option<isize>)], num_int_values: usize) -> usize {
+    let offsets = &*offsets.resolve(num_tilings, spans.len());
+    (0..num_tilings)
+        .map(|tiling| {
+            spans.iter().enumerate().fold(num_int_values, |tiles, (dim, &(lo, hi, wrap))| {
+                let spanned = coords_spanned(tiling, num_tilings, offsets, dim, lo, hi);
+                tiles.saturating_mul(wrap.map_or(spanned, |w| spanned.min(w as usize)))
+            })
+        })
+        .fold(0, usize::saturating_add)
+}
+
+// the reachable tiles of coder dimensions over their ranges, or `None` if any of them doesn't have one
+pub(crate) fn reachable_dims(num_tilings: usize, offsets: &Offsets, dims: &[Dim], num_int_values: usize) -> Option<usize> {
+    let spans = dims
+        .iter()
+        .map(|d| match d.wrap_width() {
+            Some(w) => Some((0.0, w as f64, Some(w))),
+            None => d.range().map(|(min, max)| (d.scale(min), d.scale(max), None)),
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(reachable(num_tilings, offsets, &spans, num_int_values))
+}
+
+/// The number of distinct tiles that `tiles` (or `IHT::tiles`) can produce for floats within the given `[min, max]`
+/// ranges, tiled with the given widths (i.e. each float divided by its width before tiling), and `num_int_values`
+/// distinct combinations of ints (1 if there are none). An IHT at least this large never collides; one that is
+/// smaller by some factor holds that fraction of the tiles before it starts to.
+///
+/// The count is exact for the tiles3 offsets, counting each tiling's tiles separately, since the tiling is part of
+/// every tile's coordinates. It saturates at `usize::MAX`.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{reachable_tiles, IHT};
+/// // mountain car: 8 tilings of 8 x 8 tiles over position and velocity, and 3 actions
+/// let ranges = [(-1.2, 0.6), (-0.07, 0.07)];
+/// let widths = [1.8 / 8.0, 0.14 / 8.0];
+/// let tiles = reachable_tiles(8, &ranges, &widths, 3);
+/// assert_eq!(tiles, 1584);
+///
+/// // every tile in reach, tiled at the edges of the ranges, fits
+/// let mut iht = IHT::new(tiles);
+/// for &x in &[-1.2, 0.6] {
+///     for &v in &[-0.07, 0.07] {
+///         for a in 0..3 {
+///             iht.tiles(8, &[x / widths[0], v / widths[1]], Some(&[a]));
+///         }
+///     }
+/// }
+/// assert!(iht.count() <= tiles);
+/// ```
+pub fn reachable_tiles(num_tilings: usize, ranges: &[(f64, f64)], widths: &[f64], num_int_values: usize) -> usize {
+    assert_eq!(ranges.len(), widths.len(), "expected one tile width per range");
+    let spans: Vec<(f64, f64, Option<isize>)> = ranges.iter().zip(widths.iter()).map(|(&(min, max), &w)| (min / w, max / w, None)).collect();
+    reachable(num_tilings, &Offsets::Tiles3, &spans, num_int_values)
+}
+
+/// The expected collisions of the hashing `tiles` function, see [`estimate_collisions`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CollisionEstimate {
+    /// The expected number of distinct indices in use
+    pub occupied: f64,
+    /// The expected fraction of tiles whose index is shared with at least one other tile
+    pub colliding_fraction: f64,
+    /// The probability that at least two tiles share an index (the birthday problem)
+    pub any_collision: f64,
+}
+
+/// An analytical estimate of how much the hashing version of `tiles` (the one taking a `size` rather than an IHT)
+/// collides, treating the hash of each of `distinct_tiles` tiles as uniformly random over `size` indices. The number
+/// of distinct tiles can be found with [`reachable_tiles`].
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{estimate_collisions, reachable_tiles};
+/// let tiles = reachable_tiles(8, &[(0.0, 10.0), (0.0, 10.0)], &[1.0, 1.0], 1);
+/// let small = estimate_collisions(1 << 10, tiles);
+/// let large = estimate_collisions(1 << 16, tiles);
+/// assert!(small.colliding_fraction > 0.5);
+/// assert!(large.colliding_fraction < 0.02);
+/// // collisions are nearly certain long before a noticeable fraction of tiles collide
+/// assert!(large.any_collision > 0.99);
+/// ```
+pub fn estimate_collisions(size: usize, distinct_tiles: usize) -> CollisionEstimate {
+    assert!(size > 0, "the size must be at least 1");
+    let m = size as f64;
+    let k = distinct_tiles as f64;
+    // the probability that a given index is missed by each tile, in logs for precision when `size` is huge
+    let miss = (-1.0 / m).ln_1p();
+    let any_collision = if distinct_tiles > size {
+        1.0
+    } else if distinct_tiles <= 1 << 20 {
+        // the exact birthday probability, 1 - the product of (1 - i / m) for every tile after the first
+        1.0 - (1..distinct_tiles).map(|i| (-(i as f64) / m).ln_1p()).sum::<f64>().exp()
+    } else {
+        1.0 - (-k * (k - 1.0) / (2.0 * m)).exp()
+    };
+    CollisionEstimate {
+        occupied: m * -(k * miss).exp_m1(),
+        // a tile collides unless every other tile misses its index
+        colliding_fraction: if distinct_tiles == 0 { 0.0 } else { -((k - 1.0) * miss).exp_m1() },
+        any_collision,
+    }
+}
+
+impl TileCoder {
+    /// The number of distinct tiles the coder can produce for points within the ranges of its dimensions and
+    /// `num_int_values` distinct combinations of ints, using its own offsets; see [`reachable_tiles`]. This is
+    /// `None` if any dimension doesn't have a range, i.e. was created with `Dim::width`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{Dim, TileCoder};
+    /// let coder = TileCoder::new(4, 1024, vec![Dim::bins(0.0, 1.0, 10), Dim::angle_radians(6)]);
+    /// assert_eq!(coder.reachable_tiles(1), Some(4 * 11 * 6));
+    /// assert_eq!(TileCoder::new(4, 1024, vec![Dim::width(0.1)]).reachable_tiles(1), None);
+    /// ```
+    pub fn reachable_tiles(&self, num_int_values: usize) -> Option<usize> {
+        reachable_dims(self.num_tilings(), self.offsets(), self.dims(), num_int_values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tiles, IHT};
+
+    #[test]
+    fn count_matches_exhaustive_tiling() {
+        let ranges = [(-2.3, 1.1), (0.4, 3.9)];
+        let widths = [0.5, 0.7];
+        let mut iht = IHT::new(1 << 16);
+        for i in 0..=200 {
+            for j in 0..=200 {
+                let x = ranges[0].0 + (ranges[0].1 - ranges[0].0) * i as f64 / 200.0;
+                let y = ranges[1].0 + (ranges[1].1 - ranges[1].0) * j as f64 / 200.0;
+                iht.tiles(8, &[x / widths[0], y / widths[1]], None);
+            }
+        }
+        assert_eq!(reachable_tiles(8, &ranges, &widths, 1), iht.count());
+        assert_eq!(reachable_tiles(8, &ranges, &widths, 5), 5 * iht.count());
+    }
+
+    #[test]
+    fn collision_estimate_matches_hashing() {
+        let exact = estimate_collisions(2, 2);
+        assert!((exact.occupied - 1.5).abs() < 1e-12 && (exact.colliding_fraction - 0.5).abs() < 1e-12 && (exact.any_collision - 0.5).abs() < 1e-12);
+        assert_eq!(estimate_collisions(10, 11).any_collision, 1.0);
+        assert_eq!(estimate_collisions(10, 1).colliding_fraction, 0.0);
+
+        // 20000 distinct tiles hashed into 16384 indices
+        let size = 1 << 14;
+        let mut counts = vec![0usize; size];
+        for i in 0..2500 {
+            for index in tiles(size, 8, &[i as f64 * 3.0], None) {
+                counts[index] += 1;
+            }
+        }
+        let occupied = counts.iter().filter(|&&c| c > 0).count() as f64;
+        let colliding = counts.iter().filter(|&&c| c > 1).sum::<usize>() as f64 / 20000.0;
+        let estimate = estimate_collisions(size, 20000);
+        assert!((occupied - estimate.occupied).abs() < 0.02 * estimate.occupied);
+        assert!((colliding - estimate.colliding_fraction).abs() < 0.02);
+    }
+}
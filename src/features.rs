@@ -0,0 +1,179 @@
+use crate::{TileCoder, IHT};
+
+/// A set of active binary features, i.e. the tile indices a point is encoded as, along with the size of the index
+/// space they come from. This provides the operations linear function approximation needs on top of a bare list of
+/// indices.
+///
+/// Indices are kept in the order they were given, and may repeat (when an overfull IHT hashes two tiles onto the
+/// same index); a repeated index counts once for every time it appears in `dot` and `add_scaled_to`, exactly as if
+/// the features were a dense vector of counts.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::IHT;
+/// let mut iht = IHT::new(1024);
+/// let mut weights = vec![0.0; iht.size()];
+///
+/// let features = iht.features(8, &[3.6, 7.21], None);
+/// // move the value of the point a tenth of the way to 1
+/// let value = features.dot(&weights);
+/// features.add_scaled_to(&mut weights, 0.1 * (1.0 - value) / features.len() as f64);
+/// assert!((features.dot(&weights) - 0.1).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SparseFeatures {
+    indices: Vec<usize>,
+    size: usize,
+}
+
+impl SparseFeatures {
+    /// Active features with the given indices, out of `size` features in total
+    pub fn new(indices: Vec<usize>, size: usize) -> SparseFeatures {
+        assert!(indices.iter().all(|&i| i < size), "feature indices must be less than the size");
+        SparseFeatures { indices, size }
+    }
+
+    /// The active indices
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// The total number of features, active or not; every index is `< size`
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of active indices
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Whether no features are active
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Iterate over the active indices
+    pub fn iter(&self) -> std::slice::Iter<'_, usize> {
+        self.indices.iter()
+    }
+
+    /// Whether the feature with the given index is active
+    pub fn contains(&self, index: usize) -> bool {
+        self.indices.contains(&index)
+    }
+
+    /// The dot product of the features with a dense weight vector, i.e. the sum of the active weights
+    pub fn dot(&self, weights: &[f64]) -> f64 {
+        assert_eq!(weights.len(), self.size, "the weight vector must have one weight per feature");
+        self.indices.iter().map(|&i| weights[i]).sum()
+    }
+
+    /// Add `alpha` times the features to a dense weight vector, i.e. add `alpha` to every active weight
+    pub fn add_scaled_to(&self, weights: &mut [f64], alpha: f64) {
+        assert_eq!(weights.len(), self.size, "the weight vector must have one weight per feature");
+        for &i in self.indices.iter() {
+            weights[i] += alpha;
+        }
+    }
+
+    // the distinct indices, in their original order
+    fn distinct(indices: impl Iterator<Item = usize>) -> Vec<usize> {
+        let mut seen = Vec::new();
+        for i in indices {
+            if !seen.contains(&i) {
+                seen.push(i);
+            }
+        }
+        seen
+    }
+
+    fn check_size(&self, other: &SparseFeatures) {
+        assert_eq!(self.size, other.size, "features must come from the same index space");
+    }
+
+    /// The features active in either set, without repeats
+    pub fn union(&self, other: &SparseFeatures) -> SparseFeatures {
+        self.check_size(other);
+        SparseFeatures::new(SparseFeatures::distinct(self.iter().chain(other.iter()).copied()), self.size)
+    }
+
+    /// The features active in both sets, without repeats
+    pub fn intersection(&self, other: &SparseFeatures) -> SparseFeatures {
+        self.check_size(other);
+        SparseFeatures::new(SparseFeatures::distinct(self.iter().copied().filter(|&i| other.contains(i))), self.size)
+    }
+
+    /// The features active in this set but not the other, without repeats
+    pub fn difference(&self, other: &SparseFeatures) -> SparseFeatures {
+        self.check_size(other);
+        SparseFeatures::new(SparseFeatures::distinct(self.iter().copied().filter(|&i| !other.contains(i))), self.size)
+    }
+
+    /// The number of distinct features active in both sets
+    pub fn overlap(&self, other: &SparseFeatures) -> usize {
+        self.intersection(other).len()
+    }
+}
+
+impl From<SparseFeatures> for Vec<usize> {
+    fn from(features: SparseFeatures) -> Vec<usize> {
+        features.indices
+    }
+}
+
+impl<'a> IntoIterator for &'a SparseFeatures {
+    type Item = &'a usize;
+    type IntoIter = std::slice::Iter<'a, usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.indices.iter()
+    }
+}
+
+impl IHT {
+    /// The same as `tiles`, except that the indices are returned as [`SparseFeatures`] over the IHT's index space
+    pub fn features(&mut self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> SparseFeatures {
+        SparseFeatures::new(self.tiles(num_tilings, floats, ints), self.size())
+    }
+
+    /// The same as `tiles_wrap`, except that the indices are returned as [`SparseFeatures`] over the IHT's index space
+    pub fn features_wrap(&mut self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> SparseFeatures {
+        SparseFeatures::new(self.tiles_wrap(num_tilings, floats, wrap_widths, ints), self.size())
+    }
+}
+
+impl TileCoder {
+    /// The same as `tiles`, except that the indices are returned as [`SparseFeatures`] over the coder's index space
+    pub fn features(&mut self, floats: &[f64], ints: Option<&[isize]>) -> SparseFeatures {
+        let size = self.size();
+        SparseFeatures::new(self.tiles(floats, ints), size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_count_like_dense_vectors() {
+        let features = SparseFeatures::new(vec![1, 3, 1], 4);
+        let mut weights = vec![0.0; 4];
+        features.add_scaled_to(&mut weights, 0.5);
+        assert_eq!(weights, vec![0.0, 1.0, 0.0, 0.5]);
+        assert_eq!(features.dot(&[1.0, 2.0, 3.0, 4.0]), 8.0);
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = SparseFeatures::new(vec![0, 2, 4, 2], 8);
+        let b = SparseFeatures::new(vec![4, 5, 0], 8);
+        assert_eq!(a.union(&b).indices(), &[0, 2, 4, 5]);
+        assert_eq!(a.intersection(&b).indices(), &[0, 4]);
+        assert_eq!(a.difference(&b).indices(), &[2]);
+        assert_eq!(a.overlap(&b), 2);
+        assert_eq!(Vec::from(b.clone()), vec![4, 5, 0]);
+        assert_eq!((&b).into_iter().sum::<usize>(), 9);
+    }
+}
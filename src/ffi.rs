@@ -0,0 +1,300 @@
+//! A C interface to the IHT, declared in `include/tilecoding.h`. Every function is prefixed with `tilecoding_`, and
+//! the functions which write tile indices write exactly `num_tilings` of them into a caller-provided array.
+
+use std::slice;
+
+use crate::{tiles_python, tiles_wrap_python, IHT};
+
+/// The call succeeded
+pub const TILECODING_OK: i32 = 0;
+/// A required pointer was null
+pub const TILECODING_NULL_POINTER: i32 = -1;
+
+// a slice from a pointer and length, where a null pointer is only allowed for an empty slice
+unsafe fn slice_from<'a, T>(ptr: *const T, len: usize) -> Option<&'a [T]> {
+    if len == 0 {
+        Some(&[])
+    } else if ptr.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(ptr, len))
+    }
+}
+
+/// Create an IHT with the given size, which must later be freed with `tilecoding_iht_free`
+#[no_mangle]
+pub extern "C" fn tilecoding_iht_new(size: usize) -> *mut IHT {
+    Box::into_raw(Box::new(IHT::new(size)))
+}
+
+/// Free an IHT created by `tilecoding_iht_new`; null is ignored
+///
+/// # Safety
+///
+/// `iht` must be null or a pointer returned by `tilecoding_iht_new` which hasn't been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn tilecoding_iht_free(iht: *mut IHT) {
+    if !iht.is_null() {
+        drop(Box::from_raw(iht));
+    }
+}
+
+/// The number of entries in the IHT, or 0 if it is null
+///
+/// # Safety
+///
+/// `iht` must be null or a live pointer returned by `tilecoding_iht_new`.
+#[no_mangle]
+pub unsafe extern "C" fn tilecoding_iht_count(iht: *const IHT) -> usize {
+    iht.as_ref().map_or(0, IHT::count)
+}
+
+/// Encode a point as `num_tilings` indices written to `out`, see [`IHT::tiles`]. `ints` may be null when `num_ints` is
+/// 0. Returns `TILECODING_OK`, or `TILECODING_NULL_POINTER` without writing anything.
+///
+/// # Safety
+///
+/// `iht` must be a live pointer returned by `tilecoding_iht_new`, `floats` and `ints` must point to at least
+/// `num_floats` and `num_ints` values, and `out` must have room for `num_tilings` indices.
+#[no_mangle]
+pub unsafe extern "C" fn tilecoding_tiles(
+    iht: *mut IHT,
+    num_tilings: usize,
+    floats: *const f64,
+    num_floats: usize,
+    ints: *const isize,
+    num_ints: usize,
+    out: *mut usize,
+) -> i32 {
+    let (iht, floats, ints) = match (iht.as_mut(), slice_from(floats, num_floats), slice_from(ints, num_ints)) {
+        (Some(iht), Some(floats), Some(ints)) if !out.is_null() => (iht, floats, ints),
+        _ => return TILECODING_NULL_POINTER,
+    };
+    let tiles = iht.tiles(num_tilings, floats, if ints.is_empty() { None } else { Some(ints) });
+    out.copy_from_nonoverlapping(tiles.as_ptr(), tiles.len());
+    TILECODING_OK
+}
+
+/// The wrap-around version of `tilecoding_tiles`, with one wrap width per float where a width of 0 or less means the
+/// float doesn't wrap, see [`IHT::tiles_wrap`]
+///
+/// # Safety
+///
+/// The same as `tilecoding_tiles`, and `wrap_widths` must point to `num_floats` values.
+#[no_mangle]
+pub unsafe extern "C" fn tilecoding_tiles_wrap(
+    iht: *mut IHT,
+    num_tilings: usize,
+    floats: *const f64,
+    wrap_widths: *const isize,
+    num_floats: usize,
+    ints: *const isize,
+    num_ints: usize,
+    out: *mut usize,
+) -> i32 {
+    let (iht, floats, widths, ints) = match (
+        iht.as_mut(),
+        slice_from(floats, num_floats),
+        slice_from(wrap_widths, num_floats),
+        slice_from(ints, num_ints),
+    ) {
+        (Some(iht), Some(floats), Some(widths), Some(ints)) if !out.is_null() => (iht, floats, widths, ints),
+        _ => return TILECODING_NULL_POINTER,
+    };
+    let widths: Vec<Option<isize>> = widths.iter().map(|&w| if w > 0 { Some(w) } else { None }).collect();
+    let tiles = iht.tiles_wrap(num_tilings, floats, &widths, if ints.is_empty() { None } else { Some(ints) });
+    out.copy_from_nonoverlapping(tiles.as_ptr(), tiles.len());
+    TILECODING_OK
+}
+
+/// The read-only version of `tilecoding_tiles`, which writes -1 for tiles that aren't in the IHT, see
+/// [`IHT::tiles_read_only`]
+///
+/// # Safety
+///
+/// The same as `tilecoding_tiles`, except that `iht` is only read.
+#[no_mangle]
+pub unsafe extern "C" fn tilecoding_tiles_read_only(
+    iht: *const IHT,
+    num_tilings: usize,
+    floats: *const f64,
+    num_floats: usize,
+    ints: *const isize,
+    num_ints: usize,
+    out: *mut isize,
+) -> i32 {
+    let (iht, floats, ints) = match (iht.as_ref(), slice_from(floats, num_floats), slice_from(ints, num_ints)) {
+        (Some(iht), Some(floats), Some(ints)) if !out.is_null() => (iht, floats, ints),
+        _ => return TILECODING_NULL_POINTER,
+    };
+    let tiles = iht.tiles_read_only(num_tilings, floats, if ints.is_empty() { None } else { Some(ints) });
+    for (i, tile) in tiles.into_iter().enumerate() {
+        *out.add(i) = tile.map_or(-1, |tile| tile as isize);
+    }
+    TILECODING_OK
+}
+
+// the wrap widths of the tiles3 parity functions, where a null pointer means the point is encoded with `tiles` rather
+// than `tileswrap`: tiles3's `tileswrap` reduces the offsets modulo the number of tilings where `tiles` doesn't, so
+// even without any wrapping floats they give different tiles
+unsafe fn parity_widths(wrap_widths: *const isize, num_floats: usize) -> Option<Option<Vec<Option<isize>>>> {
+    if wrap_widths.is_null() {
+        return Some(None);
+    }
+    Some(Some(slice_from(wrap_widths, num_floats)?.iter().map(|&w| if w > 0 { Some(w) } else { None }).collect()))
+}
+
+/// The tiles3 parity version of `tilecoding_tiles_wrap`, matching Sutton's Python `tileswrap(iht, ...)` (and
+/// `tiles(iht, ...)` when `wrap_widths` is null), see [`IHT::tiles_wrap_python`]
+///
+/// # Safety
+///
+/// The same as `tilecoding_tiles_wrap`, except that `wrap_widths` may be null.
+#[no_mangle]
+pub unsafe extern "C" fn tilecoding_tiles_python(
+    iht: *mut IHT,
+    num_tilings: usize,
+    floats: *const f64,
+    wrap_widths: *const isize,
+    num_floats: usize,
+    ints: *const isize,
+    num_ints: usize,
+    out: *mut usize,
+) -> i32 {
+    let (iht, floats, widths, ints) = match (
+        iht.as_mut(),
+        slice_from(floats, num_floats),
+        parity_widths(wrap_widths, num_floats),
+        slice_from(ints, num_ints),
+    ) {
+        (Some(iht), Some(floats), Some(widths), Some(ints)) if !out.is_null() => (iht, floats, widths, ints),
+        _ => return TILECODING_NULL_POINTER,
+    };
+    let ints = if ints.is_empty() { None } else { Some(ints) };
+    let tiles = match widths {
+        Some(widths) => iht.tiles_wrap_python(num_tilings, floats, &widths, ints),
+        None => iht.tiles_python(num_tilings, floats, ints),
+    };
+    out.copy_from_nonoverlapping(tiles.as_ptr(), tiles.len());
+    TILECODING_OK
+}
+
+/// The read-only version of `tilecoding_tiles_python`, which writes -1 for tiles that aren't in the IHT, see
+/// [`IHT::tiles_wrap_read_only_python`]
+///
+/// # Safety
+///
+/// The same as `tilecoding_tiles_python`, except that `iht` is only read.
+#[no_mangle]
+pub unsafe extern "C" fn tilecoding_tiles_read_only_python(
+    iht: *const IHT,
+    num_tilings: usize,
+    floats: *const f64,
+    wrap_widths: *const isize,
+    num_floats: usize,
+    ints: *const isize,
+    num_ints: usize,
+    out: *mut isize,
+) -> i32 {
+    let (iht, floats, widths, ints) = match (
+        iht.as_ref(),
+        slice_from(floats, num_floats),
+        parity_widths(wrap_widths, num_floats),
+        slice_from(ints, num_ints),
+    ) {
+        (Some(iht), Some(floats), Some(widths), Some(ints)) if !out.is_null() => (iht, floats, widths, ints),
+        _ => return TILECODING_NULL_POINTER,
+    };
+    let ints = if ints.is_empty() { None } else { Some(ints) };
+    let tiles = match widths {
+        Some(widths) => iht.tiles_wrap_read_only_python(num_tilings, floats, &widths, ints),
+        None => iht.tiles_read_only_python(num_tilings, floats, ints),
+    };
+    for (i, tile) in tiles.into_iter().enumerate() {
+        *out.add(i) = tile.map_or(-1, |tile| tile as isize);
+    }
+    TILECODING_OK
+}
+
+/// The tiles3 parity version of encoding without an IHT, matching Python's `tileswrap(size, ...)` (and
+/// `tiles(size, ...)` when `wrap_widths` is null), see [`tiles_wrap_python`]
+///
+/// # Safety
+///
+/// The same as `tilecoding_tiles_python`, but without an IHT.
+#[no_mangle]
+pub unsafe extern "C" fn tilecoding_hash_tiles_python(
+    size: usize,
+    num_tilings: usize,
+    floats: *const f64,
+    wrap_widths: *const isize,
+    num_floats: usize,
+    ints: *const isize,
+    num_ints: usize,
+    out: *mut usize,
+) -> i32 {
+    let (floats, widths, ints) = match (slice_from(floats, num_floats), parity_widths(wrap_widths, num_floats), slice_from(ints, num_ints)) {
+        (Some(floats), Some(widths), Some(ints)) if !out.is_null() => (floats, widths, ints),
+        _ => return TILECODING_NULL_POINTER,
+    };
+    let ints = if ints.is_empty() { None } else { Some(ints) };
+    let tiles = match widths {
+        Some(widths) => tiles_wrap_python(size, num_tilings, floats, &widths, ints),
+        None => tiles_python(size, num_tilings, floats, ints),
+    };
+    out.copy_from_nonoverlapping(tiles.as_ptr(), tiles.len());
+    TILECODING_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn round_trip_through_c_interface() {
+        let iht = tilecoding_iht_new(1024);
+        let mut out = [0usize; 8];
+        let mut read = [0isize; 8];
+        unsafe {
+            assert_eq!(tilecoding_tiles(iht, 8, [3.6, 7.21].as_ptr(), 2, ptr::null(), 0, out.as_mut_ptr()), TILECODING_OK);
+            assert_eq!(out, [0, 1, 2, 3, 4, 5, 6, 7]);
+            assert_eq!(tilecoding_iht_count(iht), 8);
+
+            assert_eq!(tilecoding_tiles_read_only(iht, 8, [3.7, 7.21].as_ptr(), 2, ptr::null(), 0, read.as_mut_ptr()), TILECODING_OK);
+            assert_eq!(read, [0, 1, 2, -1, 4, 5, 6, 7]);
+
+            let expected = (*iht).tiles_wrap(8, &[0.5], &[Some(4)], Some(&[1]));
+            assert_eq!(tilecoding_tiles_wrap(iht, 8, [0.5].as_ptr(), [4].as_ptr(), 1, [1].as_ptr(), 1, out.as_mut_ptr()), TILECODING_OK);
+            assert_eq!(out.to_vec(), expected);
+
+            assert_eq!(tilecoding_tiles(iht, 8, ptr::null(), 2, ptr::null(), 0, out.as_mut_ptr()), TILECODING_NULL_POINTER);
+            tilecoding_iht_free(iht);
+
+            // the tiles3 parity functions, with python's flooring below zero
+            let iht = tilecoding_iht_new(1024);
+            let expected = (*iht).tiles_python(8, &[-3.6, 7.21], None);
+            assert_eq!(tilecoding_tiles_python(iht, 8, [-3.6, 7.21].as_ptr(), ptr::null(), 2, ptr::null(), 0, out.as_mut_ptr()), TILECODING_OK);
+            assert_eq!(out.to_vec(), expected);
+            assert_eq!(tilecoding_tiles_read_only_python(iht, 8, [-3.6, 7.21].as_ptr(), ptr::null(), 2, ptr::null(), 0, read.as_mut_ptr()), TILECODING_OK);
+            assert_eq!(read.to_vec(), expected.iter().map(|&t| t as isize).collect::<Vec<_>>());
+            let expected = (*iht).tiles_wrap_read_only_python(8, &[-3.6, 7.21], &[None, None], None);
+            assert_eq!(tilecoding_tiles_read_only_python(iht, 8, [-3.6, 7.21].as_ptr(), [0, -1].as_ptr(), 2, ptr::null(), 0, read.as_mut_ptr()), TILECODING_OK);
+            assert_eq!(read.to_vec(), expected.into_iter().map(|t| t.map_or(-1, |t| t as isize)).collect::<Vec<_>>());
+            assert_eq!(tilecoding_hash_tiles_python(1024, 8, [3.6, 7.21].as_ptr(), ptr::null(), 2, ptr::null(), 0, out.as_mut_ptr()), TILECODING_OK);
+            assert_eq!(out, [101, 1010, 377, 156, 358, 989, 935, 302]);
+
+            // no tilings write nothing, rather than aborting the caller's process
+            let mut untouched = [usize::MAX; 1];
+            let mut untouched_read = [isize::MAX; 1];
+            assert_eq!(tilecoding_tiles(iht, 0, [1.0].as_ptr(), 1, ptr::null(), 0, untouched.as_mut_ptr()), TILECODING_OK);
+            assert_eq!(tilecoding_tiles_wrap(iht, 0, [1.0].as_ptr(), [4].as_ptr(), 1, ptr::null(), 0, untouched.as_mut_ptr()), TILECODING_OK);
+            assert_eq!(tilecoding_tiles_read_only(iht, 0, [1.0].as_ptr(), 1, ptr::null(), 0, untouched_read.as_mut_ptr()), TILECODING_OK);
+            assert_eq!(tilecoding_tiles_python(iht, 0, [1.0].as_ptr(), ptr::null(), 1, ptr::null(), 0, untouched.as_mut_ptr()), TILECODING_OK);
+            assert_eq!(tilecoding_hash_tiles_python(64, 0, [1.0].as_ptr(), ptr::null(), 1, ptr::null(), 0, untouched.as_mut_ptr()), TILECODING_OK);
+            assert_eq!((untouched, untouched_read), ([usize::MAX], [isize::MAX]));
+            tilecoding_iht_free(iht);
+            tilecoding_iht_free(ptr::null_mut());
+        }
+    }
+}
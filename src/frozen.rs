@@ -0,0 +1,299 @@
+use crate::tag::{Tag, PROBE};
+use crate::{calculate_coords, calculate_coords_wrap, calculate_q_floats, stable_hash, CoderConfig, Error, IHT};
+
+const MAGIC: &[u8; 8] = b"TCFROZEN";
+const VERSION: u64 = 2;
+// magic, version, size, count, slots, arena length, then the tag as crate version, slot hash fingerprint, and config
+// fingerprint
+const HEADER_LEN: usize = 8 * 9;
+const ENTRY_LEN: usize = 8 * 3;
+
+// a fingerprint of the hash which places the entries in the slot table, since lookups only find them if it's unchanged
+fn slot_hash_fingerprint() -> u64 {
+    stable_hash(PROBE)
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    let mut word = [0u8; 8];
+    word.copy_from_slice(&bytes[offset..offset + 8]);
+    u64::from_le_bytes(word)
+}
+
+/// A read-only view of a frozen IHT, queried directly from its serialized bytes.
+///
+/// The bytes produced by [`IHT::freeze`] are laid out as an open-addressing hash table, so a `FrozenIHT` can be
+/// created from them (for example from a memory-mapped file) without parsing or allocating the dictionary:
+/// `from_bytes` only validates the header, and lookups read straight from the buffer. This is the crate's zero-copy
+/// format: rather than archiving an IHT with a crate like `rkyv`, freeze it and use a `FrozenIHT` over the bytes.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{FrozenIHT, IHT};
+/// let mut iht = IHT::new(1024);
+/// let indices = iht.tiles(8, &[3.6, 7.21], None);
+///
+/// let bytes = iht.freeze();
+/// let frozen = FrozenIHT::from_bytes(&bytes).unwrap();
+/// assert_eq!(frozen.tiles(8, &[3.6, 7.21], None), indices.into_iter().map(Some).collect::<Vec<_>>());
+/// assert_eq!(frozen.tiles(8, &[-37.2, 7.0], None), vec![None; 8]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct FrozenIHT<'a> {
+    size: usize,
+    count: usize,
+    slots: usize,
+    table: &'a [u8],
+    entries: &'a [u8],
+    arena: &'a [u8],
+}
+
+impl IHT {
+    /// Serialize the IHT into the frozen format read by [`FrozenIHT`], which also records the version of this crate
+    /// which wrote it
+    pub fn freeze(&self) -> Vec<u8> {
+        self.freeze_tagged(0)
+    }
+
+    /// The same as [`IHT::freeze`], but also recording the [`CoderConfig`] the table was filled with, so that
+    /// [`FrozenIHT::from_bytes_with_config`] can check it is used with the same one
+    pub fn freeze_with_config(&self, config: &CoderConfig) -> Vec<u8> {
+        self.freeze_tagged(config.fingerprint())
+    }
+
+    fn freeze_tagged(&self, config: u64) -> Vec<u8> {
+        let slots = (self.dictionary.len() * 2).max(1).next_power_of_two();
+        let mut table = vec![0u64; slots];
+        let mut entries: Vec<(u64, u64, u64)> = Vec::with_capacity(self.dictionary.len());
+        let mut arena: Vec<i64> = Vec::new();
+
+        // sort the entries so that freezing the same IHT always produces the same bytes
+        for (coords, index) in self.entries_snapshot() {
+            let mut slot = stable_hash(&coords) as usize & (slots - 1);
+            while table[slot] != 0 {
+                slot = (slot + 1) & (slots - 1);
+            }
+            table[slot] = entries.len() as u64 + 1;
+            entries.push((arena.len() as u64, coords.len() as u64, index as u64));
+            arena.extend(coords.iter().map(|&c| c as i64));
+        }
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + slots * 8 + entries.len() * ENTRY_LEN + arena.len() * 8);
+        bytes.extend_from_slice(MAGIC);
+        let [crate_version, hash, config] = Tag::new(slot_hash_fingerprint(), config).to_words();
+        for word in [VERSION, self.size as u64, entries.len() as u64, slots as u64, arena.len() as u64, crate_version, hash, config].iter() {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        for word in table {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        for (offset, len, index) in entries {
+            bytes.extend_from_slice(&offset.to_le_bytes());
+            bytes.extend_from_slice(&len.to_le_bytes());
+            bytes.extend_from_slice(&index.to_le_bytes());
+        }
+        for c in arena {
+            bytes.extend_from_slice(&c.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+impl<'a> FrozenIHT<'a> {
+    /// Wrap the bytes produced by [`IHT::freeze`]. Only the header is checked, so this is constant-time. Bytes
+    /// frozen by a version of this crate with a different major or minor version, or whose entries were placed with a
+    /// hash which differs from this build's, are an `Error::Format`.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<FrozenIHT<'a>, Error> {
+        FrozenIHT::from_tagged_bytes(bytes).map(|(frozen, _)| frozen)
+    }
+
+    /// Wrap the bytes produced by [`IHT::freeze_with_config`], failing with an `Error::Format` unless they were
+    /// frozen for exactly the same configuration, ignoring the names of the dimensions
+    pub fn from_bytes_with_config(bytes: &'a [u8], config: &CoderConfig) -> Result<FrozenIHT<'a>, Error> {
+        let (frozen, tag) = FrozenIHT::from_tagged_bytes(bytes)?;
+        tag.check_config("frozen IHT", config).map(|_| frozen)
+    }
+
+    fn from_tagged_bytes(bytes: &'a [u8]) -> Result<(FrozenIHT<'a>, Tag), Error> {
+        if bytes.len() < 16 || &bytes[..8] != MAGIC {
+            return Err(Error::Format("not a frozen IHT".to_string()));
+        }
+        let version = read_u64(bytes, 8);
+        if version != VERSION {
+            return Err(Error::Format(format!("unsupported frozen IHT version {} (expected {})", version, VERSION)));
+        }
+        if bytes.len() < HEADER_LEN {
+            return Err(Error::Format("not a frozen IHT".to_string()));
+        }
+        let tag = Tag::from_words([read_u64(bytes, 48), read_u64(bytes, 56), read_u64(bytes, 64)]);
+        tag.check_version("frozen IHT")?;
+        tag.check_hash("frozen IHT", slot_hash_fingerprint())?;
+
+        let size = read_u64(bytes, 16) as usize;
+        let count = read_u64(bytes, 24) as usize;
+        let slots = read_u64(bytes, 32) as usize;
+        let arena_len = read_u64(bytes, 40) as usize;
+        if !slots.is_power_of_two() || count > slots {
+            return Err(Error::Format("corrupt slot table".to_string()));
+        }
+
+        let table_end = slots
+            .checked_mul(8)
+            .and_then(|t| t.checked_add(HEADER_LEN))
+            .ok_or_else(|| Error::Format("corrupt slot table".to_string()))?;
+        let entries_end = count
+            .checked_mul(ENTRY_LEN)
+            .and_then(|e| e.checked_add(table_end))
+            .ok_or_else(|| Error::Format("corrupt entry table".to_string()))?;
+        let arena_end = arena_len
+            .checked_mul(8)
+            .and_then(|a| a.checked_add(entries_end))
+            .ok_or_else(|| Error::Format("corrupt coordinate arena".to_string()))?;
+        if bytes.len() != arena_end {
+            return Err(Error::Format(format!("expected {} bytes, found {}", arena_end, bytes.len())));
+        }
+
+        let frozen = FrozenIHT {
+            size,
+            count,
+            slots,
+            table: &bytes[HEADER_LEN..table_end],
+            entries: &bytes[table_end..entries_end],
+            arena: &bytes[entries_end..],
+        };
+        Ok((frozen, tag))
+    }
+
+    /// The size of the IHT that was frozen
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of tiles in the frozen IHT
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    fn matches(&self, entry: usize, coords: &[isize]) -> Option<usize> {
+        let offset = read_u64(self.entries, entry * ENTRY_LEN) as usize;
+        let len = read_u64(self.entries, entry * ENTRY_LEN + 8) as usize;
+        if len != coords.len() || offset.checked_add(len)?.checked_mul(8)? > self.arena.len() {
+            return None;
+        }
+        let all_equal = coords
+            .iter()
+            .enumerate()
+            .all(|(i, &c)| read_u64(self.arena, (offset + i) * 8) as i64 == c as i64);
+        if all_equal {
+            Some(read_u64(self.entries, entry * ENTRY_LEN + 16) as usize)
+        } else {
+            None
+        }
+    }
+
+    fn get_index(&self, coords: &[isize]) -> Option<usize> {
+        let mask = self.slots - 1;
+        let mut slot = stable_hash(coords) as usize & mask;
+        for _ in 0..self.slots {
+            match read_u64(self.table, slot * 8) as usize {
+                0 => return None,
+                id if id <= self.count => {
+                    if let Some(index) = self.matches(id - 1, coords) {
+                        return Some(index);
+                    }
+                }
+                _ => return None,
+            }
+            slot = (slot + 1) & mask;
+        }
+        None
+    }
+
+    /// The equivalent of [`IHT::tiles_read_only`] on the frozen table
+    pub fn tiles(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        (0..num_tilings)
+            .map(|tiling| self.get_index(&calculate_coords(tiling, num_tilings, &q_floats, &ints)))
+            .collect()
+    }
+
+    /// The equivalent of [`IHT::tiles_wrap_read_only`] on the frozen table
+    pub fn tiles_wrap(&self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        (0..num_tilings)
+            .map(|tiling| self.get_index(&calculate_coords_wrap(tiling, num_tilings, &q_floats, wrap_widths, &ints)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frozen_matches_read_only() {
+        let mut iht = IHT::new(256);
+        for i in 0..40 {
+            iht.tiles(8, &[i as f64 * 0.37, -(i as f64) * 0.11], Some(&[i % 3]));
+            iht.tiles_wrap(8, &[i as f64 * 0.5], &[Some(5)], None);
+        }
+        let bytes = iht.freeze();
+        let frozen = FrozenIHT::from_bytes(&bytes).unwrap();
+        assert_eq!(frozen.size(), 256);
+        assert_eq!(frozen.count(), iht.count());
+        for i in 0..60 {
+            let point = [i as f64 * 0.29, -(i as f64) * 0.13];
+            assert_eq!(frozen.tiles(8, &point, Some(&[i % 3])), iht.tiles_read_only(8, &point, Some(&[i % 3])));
+            assert_eq!(frozen.tiles_wrap(8, &point[..1], &[Some(5)], None), iht.tiles_wrap_read_only(8, &point[..1], &[Some(5)], None));
+        }
+    }
+
+    #[test]
+    fn rejects_bad_bytes() {
+        let bytes = IHT::new(16).freeze();
+        assert!(FrozenIHT::from_bytes(&bytes).is_ok());
+        assert!(FrozenIHT::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(FrozenIHT::from_bytes(b"not a table at all, but long enough for a header!").is_err());
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[8] = 3;
+        assert_eq!(
+            FrozenIHT::from_bytes(&wrong_version).unwrap_err(),
+            Error::Format("unsupported frozen IHT version 3 (expected 2)".to_string())
+        );
+
+        // lookups in a table with a corrupt entry miss rather than panic
+        let mut iht = IHT::new(16);
+        iht.tiles(1, &[0.5], None);
+        let mut corrupt = iht.freeze();
+        let entry = HEADER_LEN + 2 * 8;
+        corrupt[entry..entry + 8].copy_from_slice(&(1u64 << 61).to_le_bytes());
+        assert_eq!(FrozenIHT::from_bytes(&corrupt).unwrap().tiles(1, &[0.5], None), vec![None]);
+    }
+
+    #[test]
+    fn checks_the_tag() {
+        let config = CoderConfig::new(4, 16, vec![crate::DimensionConfig::new(0.0, 1.0, 2)]).unwrap();
+        let mut iht = IHT::new(16);
+        iht.tiles(4, &config.scale(&[0.3]), None);
+        let bytes = iht.freeze_with_config(&config);
+        let expected = iht.tiles_read_only(4, &config.scale(&[0.3]), None);
+        assert_eq!(FrozenIHT::from_bytes_with_config(&bytes, &config).unwrap().tiles(4, &config.scale(&[0.3]), None), expected);
+        assert!(FrozenIHT::from_bytes_with_config(&iht.freeze(), &config).is_err());
+
+        // another minor release, or another slot hash, can't be read
+        let (major, minor) = (read_u64(&bytes, 48) >> 42, (read_u64(&bytes, 48) >> 21) & 0x1f_ffff);
+        let mut other = bytes.clone();
+        other[48..56].copy_from_slice(&((major << 42) | ((minor + 1) << 21)).to_le_bytes());
+        assert!(FrozenIHT::from_bytes(&other).is_err());
+        let mut other = bytes.clone();
+        other[56] ^= 1;
+        assert!(FrozenIHT::from_bytes(&other).is_err());
+
+        // nor can bytes without a tag
+        let mut untagged = bytes;
+        untagged.drain(48..HEADER_LEN);
+        untagged[8] = 1;
+        assert!(FrozenIHT::from_bytes(&untagged).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use std::borrow::Borrow;
+
+use crate::{base_hash, calculate_coords, calculate_q_floats, TileCoder, IHT};
+
+// collect any iterable floats and ints into the slices the tiling functions work on
+fn collect<F, I>(floats: F, ints: I) -> (Vec<f64>, Vec<isize>)
+where
+    F: IntoIterator,
+    F::Item: Borrow<f64>,
+    I: IntoIterator<Item = isize>,
+{
+    (floats.into_iter().map(|x| *x.borrow()).collect(), ints.into_iter().collect())
+}
+
+impl IHT {
+    /// The same as `tiles`, except that the floats and ints can be anything iterable (arrays, `Vec`s, iterator
+    /// adapters, ...), so they don't need to be collected into slices first. Having no ints can be written as `None`
+    /// or `[]`, and a single int as `Some(int)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// let raw = vec![36.0, 72.1];
+    ///
+    /// let indices = iht.tiles_from(8, raw.iter().map(|x| x / 10.0), None);
+    /// assert_eq!(indices, iht.tiles(8, &[3.6, 7.21], None));
+    ///
+    /// let action = 2;
+    /// assert_eq!(iht.tiles_from(8, &[3.6, 7.21], Some(action)), iht.tiles(8, &[3.6, 7.21], Some(&[2])));
+    /// ```
+    pub fn tiles_from<F, I>(&mut self, num_tilings: usize, floats: F, ints: I) -> Vec<usize>
+    where
+        F: IntoIterator,
+        F::Item: Borrow<f64>,
+        I: IntoIterator<Item = isize>,
+    {
+        let (floats, ints) = collect(floats, ints);
+        let ints = if ints.is_empty() { None } else { Some(&ints[..]) };
+        self.tiles(num_tilings, &floats, ints)
+    }
+
+    /// The read-only version of `tiles_from`
+    pub fn tiles_read_only_from<F, I>(&self, num_tilings: usize, floats: F, ints: I) -> Vec<Option<usize>>
+    where
+        F: IntoIterator,
+        F::Item: Borrow<f64>,
+        I: IntoIterator<Item = isize>,
+    {
+        let (floats, ints) = collect(floats, ints);
+        let ints = if ints.is_empty() { None } else { Some(&ints[..]) };
+        self.tiles_read_only(num_tilings, &floats, ints)
+    }
+}
+
+impl TileCoder {
+    /// Encode a raw point from anything iterable, e.g. `vector.iter()` for an nalgebra vector or (strided) view, see
+    /// [`IHT::tiles_from`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{Dim, TileCoder};
+    /// let mut coder = TileCoder::new(8, 4096, vec![Dim::bins(-1.0, 1.0, 8), Dim::bins(-1.0, 1.0, 8)]);
+    /// // e.g. every other element of a larger state vector
+    /// let state = [0.25, 100.0, -0.5, 100.0];
+    /// let indices = coder.tiles_from(state.iter().step_by(2), None);
+    /// assert_eq!(indices, coder.tiles(&[0.25, -0.5], None));
+    /// ```
+    pub fn tiles_from<F, I>(&mut self, floats: F, ints: I) -> Vec<usize>
+    where
+        F: IntoIterator,
+        F::Item: Borrow<f64>,
+        I: IntoIterator<Item = isize>,
+    {
+        let (floats, ints) = collect(floats, ints);
+        let ints = if ints.is_empty() { None } else { Some(&ints[..]) };
+        self.tiles(&floats, ints)
+    }
+
+    /// The read-only version of `tiles_from`
+    pub fn tiles_read_only_from<F, I>(&self, floats: F, ints: I) -> Vec<Option<usize>>
+    where
+        F: IntoIterator,
+        F::Item: Borrow<f64>,
+        I: IntoIterator<Item = isize>,
+    {
+        let (floats, ints) = collect(floats, ints);
+        let ints = if ints.is_empty() { None } else { Some(&ints[..]) };
+        self.tiles_read_only(&floats, ints)
+    }
+}
+
+/// The same as the `tiles` function, except that the floats and ints can be anything iterable, see
+/// [`IHT::tiles_from`]
+pub fn tiles_from<F, I>(size: usize, num_tilings: usize, floats: F, ints: I) -> Vec<usize>
+where
+    F: IntoIterator,
+    F::Item: Borrow<f64>,
+    I: IntoIterator<Item = isize>,
+{
+    let (floats, ints) = collect(floats, ints);
+    let ints = if ints.is_empty() { None } else { Some(&ints[..]) };
+    let q_floats = calculate_q_floats(&floats, num_tilings);
+    (0..num_tilings).map(|tiling| base_hash(calculate_coords(tiling, num_tilings, &q_floats, &ints)) % size).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles;
+
+    #[test]
+    fn any_iterable_works() {
+        let expected = tiles(512, 4, &[0.25, 1.5], Some(&[1, 2]));
+        assert_eq!(tiles_from(512, 4, [0.25, 1.5], vec![1, 2]), expected);
+        assert_eq!(tiles_from(512, 4, [0.25, 1.5].iter(), [1, 2].iter().copied()), expected);
+        assert_eq!(tiles_from(512, 4, (1..3).map(|x| x as f64 * 1.25 - 1.0), 1..3), expected);
+        assert_eq!(tiles_from(512, 4, [0.25, 1.5], []), tiles(512, 4, &[0.25, 1.5], None));
+    }
+
+    #[test]
+    fn iht_versions_match() {
+        let mut iht = IHT::new(64);
+        let indices = iht.tiles_from(4, vec![1.0f64], Some(3));
+        assert_eq!(indices, iht.tiles(4, &[1.0], Some(&[3])));
+        assert_eq!(iht.tiles_read_only_from(4, [1.0], Some(3)), indices.into_iter().map(Some).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{Dim, TileCoderBuilder};
+
+// the length of a degree of latitude (or of longitude at the equator), on a sphere with the mean radius of the Earth
+const KM_PER_DEGREE: f64 = 6371.0088 * std::f64::consts::PI / 180.0;
+
+// enough tiles over `span` that none is wider than `tile`, both in the same units
+fn tiles_over(span: f64, tile: f64) -> usize {
+    assert!(tile > 0.0 && tile.is_finite(), "tile sizes must be positive and finite");
+    ((span / tile).ceil() as usize).max(1)
+}
+
+impl Dim {
+    /// A latitude in degrees, with `tiles` tiles (per tiling) between the poles. Latitudes beyond ±90° (e.g. from
+    /// noisy GPS fixes) are clamped to the poles.
+    pub fn latitude(tiles: usize) -> Dim {
+        Dim::bins(-90.0, 90.0, tiles).clamped()
+    }
+
+    /// A longitude in degrees, with `tiles` tiles (per tiling) around the globe, which wraps at the antimeridian so
+    /// that 179.9° and -179.9° are neighbours. Longitudes may be given in `[-180, 180)`, `[0, 360)`, or unwrapped.
+    pub fn longitude(tiles: usize) -> Dim {
+        Dim::wrapping(-180.0, 180.0, tiles)
+    }
+}
+
+impl TileCoderBuilder {
+    /// Append a latitude and a longitude dimension, in that order, with tiles at most `tile_degrees` degrees wide in
+    /// either direction
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::TileCoder;
+    /// let mut coder = TileCoder::builder().num_tilings(8).size(1 << 16).lat_long_degrees(0.5).build();
+    /// // either side of the antimeridian, near Fiji
+    /// assert_eq!(coder.tiles(&[-17.7, -179.99], None), coder.tiles(&[-17.7, 180.01], None));
+    /// let east = coder.tiles(&[-17.7, 179.99], None);
+    /// let west = coder.tiles(&[-17.7, -179.99], None);
+    /// assert!(east.iter().filter(|t| west.contains(t)).count() >= 7);
+    /// ```
+    pub fn lat_long_degrees(self, tile_degrees: f64) -> TileCoderBuilder {
+        self.dim(Dim::latitude(tiles_over(180.0, tile_degrees))).dim(Dim::longitude(tiles_over(360.0, tile_degrees)))
+    }
+
+    /// Append a latitude and a longitude dimension, in that order, with tiles at most `tile_km` kilometres across
+    /// at `reference_latitude` (in degrees). A degree of longitude shrinks towards the poles, so the longitude tiles
+    /// are sized for the latitude the data is centred on: they are narrower in kilometres poleward of it, and wider
+    /// equatorward of it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::TileCoder;
+    /// // tiles about 5 km across, around Oslo
+    /// let coder = TileCoder::builder().num_tilings(8).size(1 << 16).lat_long_km(5.0, 59.9).build();
+    /// let (lat, long) = (&coder.dims()[0], &coder.dims()[1]);
+    /// // 5 km north or east is about a tile in either direction
+    /// assert!((lat.scale(59.9 + 5.0 / 111.2) - lat.scale(59.9) - 1.0).abs() < 0.01);
+    /// assert!((long.scale(10.75 + 5.0 / 55.7) - long.scale(10.75) - 1.0).abs() < 0.01);
+    /// ```
+    pub fn lat_long_km(self, tile_km: f64, reference_latitude: f64) -> TileCoderBuilder {
+        assert!(reference_latitude.abs() < 90.0, "the reference latitude must be between the poles");
+        let km_per_degree_long = KM_PER_DEGREE * reference_latitude.to_radians().cos();
+        self.dim(Dim::latitude(tiles_over(180.0 * KM_PER_DEGREE, tile_km)))
+            .dim(Dim::longitude(tiles_over(360.0 * km_per_degree_long, tile_km)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TileCoder;
+
+    #[test]
+    fn latitude_clamps_and_longitude_wraps() {
+        let mut coder = TileCoder::builder().num_tilings(4).size(4096).lat_long_degrees(10.0).build();
+        assert_eq!(coder.dims(), &[Dim::latitude(18), Dim::longitude(36)][..]);
+        assert_eq!(coder.tiles(&[91.0, 12.0], None), coder.tiles(&[90.0, 12.0], None));
+        assert_eq!(coder.tiles(&[45.0, 190.0], None), coder.tiles(&[45.0, -170.0], None));
+
+        // a finer tile size than requested is used when it doesn't divide the globe evenly
+        let km = TileCoder::builder().lat_long_km(10.0, 60.0).build();
+        assert_eq!(km.dims(), &[Dim::latitude(2002), Dim::longitude(2002)][..]);
+    }
+}
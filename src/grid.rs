@@ -0,0 +1,125 @@
+/// A collision-free tile coder for small, bounded spaces. Rather than hashing tile coordinates, each tile's
+/// index is computed arithmetically from its position in the grid, so no IHT is needed and indices are
+/// deterministic.
+///
+/// Each dimension is declared as a `(min, max, tiles)` range, where `tiles` is the number of tiles spanning
+/// `[min, max]` in a single tiling. Offset tilings need one extra tile per dimension to cover the range, so
+/// each tiling holds `(tiles + 1)` tiles per dimension. Inputs outside of the range are clamped to it.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::GridTileCoder;
+/// // mountain car: position in [-1.2, 0.6], velocity in [-0.07, 0.07], each with 8 tiles
+/// let coder = GridTileCoder::new(8, &[(-1.2, 0.6, 8), (-0.07, 0.07, 8)]);
+/// assert_eq!(coder.size(), 8 * 9 * 9);
+///
+/// let indices = coder.tiles(&[-0.5, 0.0]);
+/// assert_eq!(indices.len(), 8);
+/// assert!(indices.iter().all(|&i| i < coder.size()));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridTileCoder {
+    num_tilings: usize,
+    dims: Vec<(f64, f64, usize)>,
+    tiles_per_tiling: usize,
+}
+
+impl GridTileCoder {
+    /// Create a coder with `num_tilings` tilings over dimensions given as `(min, max, tiles)`
+    pub fn new(num_tilings: usize, dims: &[(f64, f64, usize)]) -> GridTileCoder {
+        assert!(num_tilings > 0, "there must be at least one tiling");
+        for &(min, max, tiles) in dims {
+            assert!(min < max, "dimension ranges must have min < max");
+            assert!(tiles > 0, "dimensions must have at least one tile");
+        }
+
+        GridTileCoder {
+            num_tilings,
+            dims: dims.to_vec(),
+            tiles_per_tiling: dims.iter().map(|&(_, _, tiles)| tiles + 1).product(),
+        }
+    }
+
+    /// The number of tilings
+    pub fn num_tilings(&self) -> usize {
+        self.num_tilings
+    }
+
+    /// The total number of distinct indices this coder can produce. Every index returned by `tiles` is `< size`.
+    pub fn size(&self) -> usize {
+        self.num_tilings * self.tiles_per_tiling
+    }
+
+    /// Encode a point as exactly `num_tilings` tile indices, one per tiling
+    pub fn tiles(&self, floats: &[f64]) -> Vec<usize> {
+        assert_eq!(floats.len(), self.dims.len(), "expected one float per dimension");
+        let n = self.num_tilings as isize;
+
+        // quantize each dimension to 1 / num_tilings of a tile, as with the hashed coders
+        let q_floats: Vec<isize> = floats
+            .iter()
+            .zip(self.dims.iter())
+            .map(|(&x, &(min, max, tiles))| {
+                let x = x.max(min).min(max);
+                ((x - min) / (max - min) * (tiles * self.num_tilings) as f64).floor() as isize
+            })
+            .collect();
+
+        (0..self.num_tilings)
+            .map(|tiling| {
+                // same displacement as the hashed coders, reduced modulo the number of tilings so that the
+                // coordinates stay in the range [0, tiles]
+                let mut b = tiling as isize;
+                let mut index = 0;
+                for (q, &(_, _, tiles)) in q_floats.iter().zip(self.dims.iter()) {
+                    let coord = (q + b % n) / n;
+                    index = index * (tiles + 1) + coord as usize;
+                    b += tiling as isize * 2;
+                }
+                tiling * self.tiles_per_tiling + index
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn indices_never_collide() {
+        let coder = GridTileCoder::new(4, &[(0.0, 1.0, 4), (-1.0, 1.0, 2)]);
+        let mut seen = HashSet::new();
+        for i in 0..=40 {
+            for j in 0..=40 {
+                let point = [i as f64 / 40.0, j as f64 / 20.0 - 1.0];
+                for index in coder.tiles(&point) {
+                    assert!(index < coder.size());
+                    seen.insert(index);
+                }
+            }
+        }
+        // every tiling's tiles are disjoint, so covering the range visits most of the grid
+        assert!(seen.len() <= coder.size());
+        assert!(seen.len() > coder.size() / 2);
+    }
+
+    #[test]
+    fn out_of_range_is_clamped() {
+        let coder = GridTileCoder::new(8, &[(0.0, 10.0, 10)]);
+        assert_eq!(coder.tiles(&[-5.0]), coder.tiles(&[0.0]));
+        assert_eq!(coder.tiles(&[15.0]), coder.tiles(&[10.0]));
+    }
+
+    #[test]
+    fn generalizes_like_hashed_tiles() {
+        // a unit-width tile grid should share exactly as many tiles as the hashed coder
+        let coder = GridTileCoder::new(8, &[(0.0, 16.0, 16)]);
+        let a = coder.tiles(&[3.6]);
+        let b = coder.tiles(&[3.7]);
+        let shared = a.iter().zip(b.iter()).filter(|(x, y)| x == y).count();
+        assert_eq!(shared as f64 / 8.0, crate::similarity(8, &[3.6], &[3.7]));
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::hash::{BuildHasher, Hash, Hasher};
+
+use crate::{base_hash, IHT};
+
+/// The hash function an IHT uses for its dictionary and for the indices of tiles it has no room for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HashFunction {
+    /// The std hasher (SipHash), which resists collision attacks on the dictionary; the default
+    #[default]
+    Sip,
+    /// FxHash, as used by rustc: much faster for short keys such as tile coordinates, but not resistant to keys
+    /// chosen to collide
+    Fx,
+}
+
+impl HashFunction {
+    // the hash used for the index of a tile the IHT has no room for
+    pub(crate) fn hash<H: Hash + ?Sized>(self, obj: &H) -> usize {
+        match self {
+            HashFunction::Sip => base_hash(obj),
+            HashFunction::Fx => {
+                let mut hasher = FxHasher(0);
+                obj.hash(&mut hasher);
+                hasher.finish() as usize
+            }
+        }
+    }
+}
+
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+// rustc's FxHash: a rotate, xor, and multiply per word
+pub(crate) struct FxHasher(u64);
+
+impl FxHasher {
+    fn add(&mut self, word: u64) {
+        self.0 = (self.0.rotate_left(5) ^ word).wrapping_mul(SEED);
+    }
+}
+
+impl Hasher for FxHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            let mut word = [0; 8];
+            word.copy_from_slice(chunk);
+            self.add(u64::from_le_bytes(word));
+        }
+        for &byte in chunks.remainder() {
+            self.add(byte as u64);
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add(i as u64)
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.add(i as u64)
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.add(i)
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add(i as u64)
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.add(i as u64)
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.add(i as u64)
+    }
+}
+
+// builds the hashers of an IHT's dictionary, whichever hash function it uses
+#[derive(Clone, Debug)]
+pub(crate) struct KeyState {
+    function: HashFunction,
+    sip: RandomState,
+}
+
+impl KeyState {
+    pub(crate) fn new(function: HashFunction) -> KeyState {
+        KeyState { function, sip: RandomState::new() }
+    }
+
+    pub(crate) fn function(&self) -> HashFunction {
+        self.function
+    }
+}
+
+pub(crate) enum KeyHasher {
+    Sip(DefaultHasher),
+    Fx(FxHasher),
+}
+
+impl Hasher for KeyHasher {
+    fn finish(&self) -> u64 {
+        match self {
+            KeyHasher::Sip(h) => h.finish(),
+            KeyHasher::Fx(h) => h.finish(),
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        match self {
+            KeyHasher::Sip(h) => h.write(bytes),
+            KeyHasher::Fx(h) => h.write(bytes),
+        }
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        match self {
+            KeyHasher::Sip(h) => h.write_usize(i),
+            KeyHasher::Fx(h) => h.write_usize(i),
+        }
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        match self {
+            KeyHasher::Sip(h) => h.write_isize(i),
+            KeyHasher::Fx(h) => h.write_isize(i),
+        }
+    }
+}
+
+impl BuildHasher for KeyState {
+    type Hasher = KeyHasher;
+
+    fn build_hasher(&self) -> KeyHasher {
+        match self.function {
+            HashFunction::Sip => KeyHasher::Sip(self.sip.build_hasher()),
+            HashFunction::Fx => KeyHasher::Fx(FxHasher(0)),
+        }
+    }
+}
+
+impl IHT {
+    /// Use a different hash function for the dictionary and for the indices of tiles the IHT has no room for. Tile
+    /// coordinates are tiny, so hashing them is a large part of the cost of `tiles`, and [`HashFunction::Fx`] is
+    /// usually much faster. The entries already in the IHT keep their indices, but tiles it had no room for will be
+    /// hashed to different indices from now on.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{HashFunction, IHT};
+    /// let mut iht = IHT::new(1024).with_hash_function(HashFunction::Fx);
+    /// assert_eq!(iht.hash_function(), HashFunction::Fx);
+    /// assert_eq!(iht.tiles(8, &[3.6, 7.21], None), vec![0, 1, 2, 3, 4, 5, 6, 7]);
+    /// ```
+    pub fn with_hash_function(mut self, function: HashFunction) -> IHT {
+        let mut dictionary = std::collections::HashMap::with_capacity_and_hasher(self.size, KeyState::new(function));
+        dictionary.extend(self.dictionary.drain());
+        self.dictionary = dictionary;
+        self
+    }
+
+    /// The hash function used by the IHT, see [`IHT::with_hash_function`]
+    pub fn hash_function(&self) -> HashFunction {
+        self.dictionary.hasher().function()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fx_matches_sip_until_overfull() {
+        let mut sip = IHT::new(64);
+        let mut fx = IHT::new(64).with_hash_function(HashFunction::Fx);
+        for i in 0..8 {
+            assert_eq!(sip.tiles(8, &[i as f64], Some(&[2])), fx.tiles(8, &[i as f64], Some(&[2])));
+        }
+        assert!(fx.full());
+        // past that, each hashes the overflow with its own function
+        let overflow = fx.tiles(8, &[100.0], None);
+        assert!(overflow.iter().all(|&i| i < 64));
+        assert_eq!(overflow[0], HashFunction::Fx.hash(&[0isize, 100][..]) % 64);
+
+        // switching keeps the existing entries
+        let switched = fx.clone().with_hash_function(HashFunction::Sip);
+        assert_eq!(switched.tiles_read_only(8, &[3.0], Some(&[2])), fx.tiles_read_only(8, &[3.0], Some(&[2])));
+    }
+}
@@ -0,0 +1,152 @@
+use crate::TileCoder;
+
+// viridis, sampled at five evenly spaced points
+const COLOURS: [[f64; 3]; 5] = [[68.0, 1.0, 84.0], [59.0, 82.0, 139.0], [33.0, 145.0, 140.0], [94.0, 201.0, 98.0], [253.0, 231.0, 37.0]];
+
+fn colour(t: f64) -> [u8; 3] {
+    let t = if t.is_finite() { t.clamp(0.0, 1.0) } else { 0.0 };
+    let x = t * (COLOURS.len() - 1) as f64;
+    let i = (x.floor() as usize).min(COLOURS.len() - 2);
+    let f = x - i as f64;
+    let mut rgb = [0; 3];
+    for (c, out) in rgb.iter_mut().enumerate() {
+        *out = (COLOURS[i][c] + (COLOURS[i + 1][c] - COLOURS[i][c]) * f).round() as u8;
+    }
+    rgb
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+// encode 8-bit RGB pixels as a PNG. The image data is stored in uncompressed deflate blocks, which every PNG reader
+// accepts, so that no compression library is needed.
+fn encode_png(width: usize, height: usize, rgb: &[u8]) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(height * (1 + 3 * width));
+    for row in rgb.chunks(3 * width) {
+        // no filter
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        zlib.push(blocks.peek().is_none() as u8);
+        zlib.extend_from_slice(&(block.len() as u16).to_le_bytes());
+        zlib.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // 8 bits per channel, rgb, default compression, filtering and no interlacing
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    chunk(&mut png, b"IHDR", &header);
+    chunk(&mut png, b"IDAT", &zlib);
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// Evaluate `f` over a `width` by `height` grid of points spanning the raw `ranges` of two dimensions, and render the
+/// values as a heatmap PNG (with the first dimension along the x axis and the second up the y axis), coloured from
+/// the lowest value in dark purple to the highest in yellow. Each pixel is evaluated at its centre.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::heatmap_png;
+/// let png = heatmap_png(64, 32, [(-1.0, 1.0), (-1.0, 1.0)], |x, y| x * x + y * y);
+/// assert!(png.starts_with(b"\x89PNG"));
+/// ```
+pub fn heatmap_png<F: FnMut(f64, f64) -> f64>(width: usize, height: usize, ranges: [(f64, f64); 2], mut f: F) -> Vec<u8> {
+    assert!(width > 0 && height > 0, "the image must have at least one pixel");
+    let mut values = Vec::with_capacity(width * height);
+    for row in 0..height {
+        let y = ranges[1].1 - (ranges[1].1 - ranges[1].0) * (row as f64 + 0.5) / height as f64;
+        for column in 0..width {
+            let x = ranges[0].0 + (ranges[0].1 - ranges[0].0) * (column as f64 + 0.5) / width as f64;
+            values.push(f(x, y));
+        }
+    }
+    let finite = values.iter().copied().filter(|v| v.is_finite());
+    let (min, max) = finite.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    let span = if max > min { max - min } else { 1.0 };
+    let rgb: Vec<u8> = values.iter().flat_map(|&v| colour((v - min) / span).to_vec()).collect();
+    encode_png(width, height, &rgb)
+}
+
+impl TileCoder {
+    /// Render the value surface of a linear function of the coder's features over two dimensions as a heatmap PNG,
+    /// see [`heatmap_png`]. The value of a point is the sum of `weights` over its tiles, with `ints` (e.g. an
+    /// action) passed along to the coder; tiles the coder hasn't seen yet count as zero. For plots like the
+    /// classic Mountain Car cost-to-go, which take the maximum over actions, use `heatmap_png` directly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{Dim, TileCoder};
+    /// let mut coder = TileCoder::new(8, 4096, vec![Dim::bins(-1.2, 0.6, 8), Dim::bins(-0.07, 0.07, 8)]);
+    /// let mut weights = vec![0.0; 4096];
+    /// for i in coder.tiles(&[-0.5, 0.0], None) {
+    ///     weights[i] = 1.0;
+    /// }
+    /// let png = coder.value_heatmap_png(&weights, None, [(-1.2, 0.6), (-0.07, 0.07)], 100, 100);
+    /// # std::fs::write(std::env::temp_dir().join("heatmap.png"), png).unwrap();
+    /// ```
+    pub fn value_heatmap_png(&self, weights: &[f64], ints: Option<&[isize]>, ranges: [(f64, f64); 2], width: usize, height: usize) -> Vec<u8> {
+        assert_eq!(self.dims().len(), 2, "only coders with two float dimensions can be drawn");
+        heatmap_png(width, height, ranges, |x, y| self.tiles_read_only(&[x, y], ints).into_iter().flatten().map(|i| weights[i]).sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_a_valid_png() {
+        // the check value of the standard crc-32
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+
+        let png = heatmap_png(3, 2, [(0.0, 3.0), (0.0, 2.0)], |x, _| x);
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        // the rows are stored uncompressed after the zlib header and the single block's header
+        let idat = 8 + 25;
+        let pixels = &png[idat + 8 + 2 + 5..];
+        assert_eq!(&pixels[..10], &[0, 68, 1, 84, 33, 145, 140, 253, 231, 37]);
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+    }
+}
@@ -0,0 +1,139 @@
+use std::ops::Range;
+
+use crate::{Dim, IHT};
+
+/// A tile coder which stacks several groups of tilings at different resolutions (say coarse, medium, and fine) and
+/// concatenates their indices into a single index space. The coarse levels generalize broadly and learn quickly,
+/// while the fine levels pick up on detail.
+///
+/// Each level has its own IHT, and its indices are offset by the sizes of the levels before it, so that every level
+/// owns a contiguous block of the weight vector and a fine level filling up can never crowd out a coarse one.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{Dim, HierarchicalCoder};
+/// let mut coder = HierarchicalCoder::new(vec![Dim::bins(0.0, 1.0, 2)])
+///     .level(4, 1, 64)
+///     .level(8, 4, 256);
+/// assert_eq!(coder.size(), 320);
+/// assert_eq!(coder.level_range(1), 64..320);
+///
+/// let indices = coder.tiles(&[0.3], None);
+/// assert_eq!(indices, vec![0, 1, 2, 3, 64, 65, 66, 67, 68, 69, 70, 71]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct HierarchicalCoder {
+    dims: Vec<Dim>,
+    // (number of tilings, dimensions at the level's resolution, the level's IHT), coarsest first
+    levels: Vec<(usize, Vec<Dim>, IHT)>,
+}
+
+impl HierarchicalCoder {
+    /// Create a coder over the given dimensions at their base resolution. It has no tilings until levels are added
+    /// with `level`.
+    pub fn new(dims: Vec<Dim>) -> HierarchicalCoder {
+        HierarchicalCoder { dims, levels: Vec::new() }
+    }
+
+    /// Add a level with `num_tilings` tilings at `factor` times the base resolution, with `size` indices of its own
+    pub fn level(mut self, num_tilings: usize, factor: usize, size: usize) -> HierarchicalCoder {
+        assert!(factor > 0, "resolution factors must be at least 1");
+        let dims = self.dims.iter().map(|d| d.refined(factor)).collect();
+        self.levels.push((num_tilings, dims, IHT::new(size)));
+        self
+    }
+
+    /// The number of levels
+    pub fn levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// The total number of tilings, i.e. the number of indices returned by `tiles`
+    pub fn num_tilings(&self) -> usize {
+        self.levels.iter().map(|(num_tilings, _, _)| num_tilings).sum()
+    }
+
+    /// The size of the combined index space; every index is `< size`
+    pub fn size(&self) -> usize {
+        self.levels.iter().map(|(_, _, iht)| iht.size()).sum()
+    }
+
+    /// The block of the combined index space owned by the given level
+    pub fn level_range(&self, level: usize) -> Range<usize> {
+        let start: usize = self.levels[..level].iter().map(|(_, _, iht)| iht.size()).sum();
+        start..start + self.levels[level].2.size()
+    }
+
+    /// The IHT of the given level; its indices are relative to the start of the level's range
+    pub fn iht(&self, level: usize) -> &IHT {
+        &self.levels[level].2
+    }
+
+    /// Encode a raw point as the indices of every level's tilings, coarsest first
+    pub fn tiles(&mut self, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        assert_eq!(floats.len(), self.dims.len(), "expected one float per dimension");
+        let mut tiles = Vec::with_capacity(self.num_tilings());
+        let mut start = 0;
+        for (num_tilings, dims, iht) in self.levels.iter_mut() {
+            let scaled: Vec<f64> = floats.iter().zip(dims.iter()).map(|(&x, d)| d.scale(x)).collect();
+            let wrap_widths: Vec<Option<isize>> = dims.iter().map(|d| d.wrap_width()).collect();
+            let level = if wrap_widths.iter().any(Option::is_some) {
+                iht.tiles_wrap(*num_tilings, &scaled, &wrap_widths, ints)
+            } else {
+                iht.tiles(*num_tilings, &scaled, ints)
+            };
+            tiles.extend(level.into_iter().map(|i| start + i));
+            start += iht.size();
+        }
+        tiles
+    }
+
+    /// The read-only version of `tiles`
+    pub fn tiles_read_only(&self, floats: &[f64], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        assert_eq!(floats.len(), self.dims.len(), "expected one float per dimension");
+        let mut tiles = Vec::with_capacity(self.num_tilings());
+        let mut start = 0;
+        for (num_tilings, dims, iht) in self.levels.iter() {
+            let scaled: Vec<f64> = floats.iter().zip(dims.iter()).map(|(&x, d)| d.scale(x)).collect();
+            let wrap_widths: Vec<Option<isize>> = dims.iter().map(|d| d.wrap_width()).collect();
+            let level = if wrap_widths.iter().any(Option::is_some) {
+                iht.tiles_wrap_read_only(*num_tilings, &scaled, &wrap_widths, ints)
+            } else {
+                iht.tiles_read_only(*num_tilings, &scaled, ints)
+            };
+            tiles.extend(level.into_iter().map(|i| i.map(|i| start + i)));
+            start += iht.size();
+        }
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_stay_in_their_ranges() {
+        let mut coder = HierarchicalCoder::new(vec![Dim::wrapping(0.0, 1.0, 2), Dim::width(1.0)])
+            .level(2, 1, 16)
+            .level(4, 2, 8)
+            .level(4, 8, 32);
+        for i in 0..100 {
+            let indices = coder.tiles(&[i as f64 * 0.137, i as f64 * 0.05], None);
+            assert!(indices[..2].iter().all(|i| coder.level_range(0).contains(i)));
+            assert!(indices[2..6].iter().all(|i| coder.level_range(1).contains(i)));
+            assert!(indices[6..].iter().all(|i| coder.level_range(2).contains(i)));
+        }
+        // the middle level is long since full, but that doesn't affect the others
+        assert_eq!(coder.iht(1).count(), 8);
+    }
+
+    #[test]
+    fn read_only_matches() {
+        let mut coder = HierarchicalCoder::new(vec![Dim::width(0.5)]).level(4, 1, 64).level(4, 3, 64);
+        let indices = coder.tiles(&[1.1], Some(&[2]));
+        assert_eq!(coder.tiles_read_only(&[1.1], Some(&[2])), indices.into_iter().map(Some).collect::<Vec<_>>());
+        assert_eq!(coder.tiles_read_only(&[9.1], Some(&[2])), vec![None; 8]);
+    }
+}
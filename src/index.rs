@@ -0,0 +1,99 @@
+use crate::{tiles, Error, IHT};
+
+/// An unsigned integer type tile indices can be returned as, to match downstream storage
+pub trait TileIndex: Copy + Eq + std::hash::Hash + std::fmt::Debug {
+    /// The name of the type, for error messages
+    const NAME: &'static str;
+    /// The largest index representable by the type
+    const MAX: usize;
+
+    /// Convert an index which is known to be `<= MAX`
+    fn from_index(index: usize) -> Self;
+
+    /// Convert back to a `usize` index
+    fn to_index(self) -> usize;
+}
+
+macro_rules! impl_tile_index {
+    ($($t:ty),*) => {
+        $(
+            impl TileIndex for $t {
+                const NAME: &'static str = stringify!($t);
+                const MAX: usize = if (<$t>::MAX as u128) < (usize::MAX as u128) { <$t>::MAX as usize } else { usize::MAX };
+
+                fn from_index(index: usize) -> $t {
+                    index as $t
+                }
+
+                fn to_index(self) -> usize {
+                    self as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_tile_index!(u8, u16, u32, u64, usize);
+
+/// Check that every index `< size` can be represented as a `T`
+pub fn check_index_type<T: TileIndex>(size: usize) -> Result<(), Error> {
+    if size > 0 && size - 1 > T::MAX {
+        Err(Error::IndexType(format!("size {} doesn't fit in {} indices (max index {})", size, T::NAME, T::MAX)))
+    } else {
+        Ok(())
+    }
+}
+
+impl IHT {
+    /// The same as `tiles`, except that the indices are returned as the integer type `T`. Fails without touching the
+    /// IHT if its size doesn't fit in `T`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// let indices: Vec<u16> = iht.tiles_as(8, &[3.6, 7.21], None).unwrap();
+    /// assert_eq!(indices, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+    ///
+    /// // 1024 indices don't fit in a u8
+    /// assert!(iht.tiles_as::<u8>(8, &[3.6, 7.21], None).is_err());
+    /// ```
+    pub fn tiles_as<T: TileIndex>(&mut self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Result<Vec<T>, Error> {
+        check_index_type::<T>(self.size())?;
+        Ok(self.tiles(num_tilings, floats, ints).into_iter().map(T::from_index).collect())
+    }
+}
+
+/// The same as the `tiles` function, except that the indices are returned as the integer type `T`. Fails if `size`
+/// doesn't fit in `T`.
+pub fn tiles_as<T: TileIndex>(size: usize, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Result<Vec<T>, Error> {
+    check_index_type::<T>(size)?;
+    Ok(tiles(size, num_tilings, floats, ints).into_iter().map(T::from_index).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_limits() {
+        assert!(check_index_type::<u8>(256).is_ok());
+        assert!(check_index_type::<u8>(257).is_err());
+        assert!(check_index_type::<u16>(65536).is_ok());
+        assert!(check_index_type::<u16>(65537).is_err());
+        assert!(check_index_type::<u64>(usize::MAX).is_ok());
+        assert_eq!(
+            check_index_type::<u16>(100_000).unwrap_err().to_string(),
+            "invalid index type: size 100000 doesn't fit in u16 indices (max index 65535)"
+        );
+    }
+
+    #[test]
+    fn typed_indices_match() {
+        let expected = tiles(1 << 20, 8, &[1.5, -0.2], Some(&[3]));
+        let typed: Vec<u32> = tiles_as(1 << 20, 8, &[1.5, -0.2], Some(&[3])).unwrap();
+        assert_eq!(typed.iter().map(|&i| i.to_index()).collect::<Vec<_>>(), expected);
+        assert!(tiles_as::<u16>(1 << 20, 8, &[1.5, -0.2], None).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use std::ops::{Deref, DerefMut};
+
+// enough for the tiling plus a handful of floats and ints, i.e. the overwhelmingly common case
+const INLINE: usize = 8;
+
+// somewhere to build the coordinates of a tile
+pub(crate) trait CoordBuffer {
+    fn clear(&mut self);
+    fn push(&mut self, c: isize);
+    fn extend_from_slice(&mut self, cs: &[isize]);
+}
+
+impl CoordBuffer for Vec<isize> {
+    fn clear(&mut self) {
+        Vec::clear(self)
+    }
+
+    fn push(&mut self, c: isize) {
+        Vec::push(self, c)
+    }
+
+    fn extend_from_slice(&mut self, cs: &[isize]) {
+        Vec::extend_from_slice(self, cs)
+    }
+}
+
+// tile coordinates held on the stack, which only move to the heap if there are more than `INLINE` of them
+pub(crate) struct InlineCoords {
+    len: usize,
+    inline: [isize; INLINE],
+    spilled: Vec<isize>,
+}
+
+impl InlineCoords {
+    pub(crate) fn new() -> InlineCoords {
+        InlineCoords {
+            len: 0,
+            inline: [0; INLINE],
+            spilled: Vec::new(),
+        }
+    }
+
+    fn is_spilled(&self) -> bool {
+        self.len > INLINE
+    }
+}
+
+impl CoordBuffer for InlineCoords {
+    fn clear(&mut self) {
+        self.len = 0;
+        self.spilled.clear();
+    }
+
+    fn push(&mut self, c: isize) {
+        if self.len < INLINE {
+            self.inline[self.len] = c;
+        } else {
+            if self.len == INLINE {
+                self.spilled.extend_from_slice(&self.inline);
+            }
+            self.spilled.push(c);
+        }
+        self.len += 1;
+    }
+
+    fn extend_from_slice(&mut self, cs: &[isize]) {
+        for &c in cs {
+            self.push(c);
+        }
+    }
+}
+
+impl Deref for InlineCoords {
+    type Target = [isize];
+
+    fn deref(&self) -> &[isize] {
+        if self.is_spilled() {
+            &self.spilled
+        } else {
+            &self.inline[..self.len]
+        }
+    }
+}
+
+impl DerefMut for InlineCoords {
+    fn deref_mut(&mut self) -> &mut [isize] {
+        if self.is_spilled() {
+            &mut self.spilled
+        } else {
+            &mut self.inline[..self.len]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spills_past_inline_capacity() {
+        let mut coords = InlineCoords::new();
+        coords.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(&coords[..], &[1, 2, 3]);
+        coords.extend_from_slice(&[4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(&coords[..], &(1..=10).collect::<Vec<isize>>()[..]);
+        coords.clear();
+        coords.push(-1);
+        assert_eq!(&coords[..], &[-1]);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::inline::{CoordBuffer, InlineCoords};
+use crate::{base_hash, IHT};
+
+// the coordinates of an int-only point in the first tiling, `[0, ints...]`. With no floats there is nothing to
+// displace, so each following tiling only changes the first coordinate.
+fn first_coords(ints: &[isize]) -> InlineCoords {
+    let mut coords = InlineCoords::new();
+    coords.push(0);
+    coords.extend_from_slice(ints);
+    coords
+}
+
+/// The int-only version of [`tiles`](crate::tiles), for inputs which are already discrete (grid world cells,
+/// quantized sensors): the same indices as `tiles(size, num_tilings, &[], Some(ints))`, without quantizing floats or
+/// rebuilding the coordinates for every tiling.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{tiles, tiles_ints};
+/// assert_eq!(tiles_ints(1024, 4, &[3, 7]), tiles(1024, 4, &[], Some(&[3, 7])));
+/// ```
+pub fn tiles_ints(size: usize, num_tilings: usize, ints: &[isize]) -> Vec<usize> {
+    let mut coords = first_coords(ints);
+    (0..num_tilings)
+        .map(|tiling| {
+            coords[0] = tiling as isize;
+            base_hash(&coords[..]) % size
+        })
+        .collect()
+}
+
+impl IHT {
+    /// The int-only version of `tiles`, see [`tiles_ints`]: the same indices as `tiles(num_tilings, &[], Some(ints))`.
+    /// Every tiling puts the point in the same cell, so the tilings don't generalize between points; a single tiling
+    /// is usually enough for purely discrete inputs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// // a cell of a grid world
+    /// assert_eq!(iht.tiles_ints(1, &[3, 7]), vec![0]);
+    /// assert_eq!(iht.tiles(1, &[], Some(&[3, 7])), vec![0]);
+    /// ```
+    pub fn tiles_ints(&mut self, num_tilings: usize, ints: &[isize]) -> Vec<usize> {
+        let mut coords = first_coords(ints);
+        (0..num_tilings)
+            .map(|tiling| {
+                coords[0] = tiling as isize;
+                self.get_index_ref(&coords)
+            })
+            .collect()
+    }
+
+    /// The read-only version of `tiles_ints`
+    pub fn tiles_ints_read_only(&self, num_tilings: usize, ints: &[isize]) -> Vec<Option<usize>> {
+        let mut coords = first_coords(ints);
+        (0..num_tilings)
+            .map(|tiling| {
+                coords[0] = tiling as isize;
+                self.get_index_read_only_ref(&coords)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles;
+
+    #[test]
+    fn matches_tiles_without_floats() {
+        let mut iht = IHT::new(64);
+        let mut reference = IHT::new(64);
+        for i in 0..40 {
+            // long enough to spill the inline coordinates too
+            let ints: Vec<isize> = (0..(i % 12)).map(|k| k * i - 5).collect();
+            assert_eq!(tiles_ints(64, 4, &ints), tiles(64, 4, &[], Some(&ints)));
+            assert_eq!(iht.tiles_ints(4, &ints), reference.tiles(4, &[], Some(&ints)));
+            assert_eq!(iht.tiles_ints_read_only(4, &ints), reference.tiles_read_only(4, &[], Some(&ints)));
+        }
+        assert_eq!(iht, reference);
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{calculate_coords, calculate_coords_wrap, calculate_q_floats, IHT};
+
+impl IHT {
+    /// The lazy version of `tiles`, which yields the index of each tiling in turn as it is computed rather than
+    /// collecting them all. Tilings which are never reached (e.g. after an early exit) are never looked up, and so
+    /// are never added to the IHT.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// let first_two: Vec<usize> = iht.tiles_iter(8, &[3.6, 7.21], None).take(2).collect();
+    /// assert_eq!(first_two, vec![0, 1]);
+    /// assert_eq!(iht.count(), 2);
+    ///
+    /// let all: Vec<usize> = iht.tiles_iter(8, &[3.6, 7.21], None).collect();
+    /// assert_eq!(all, iht.tiles(8, &[3.6, 7.21], None));
+    /// ```
+    pub fn tiles_iter<'a>(&'a mut self, num_tilings: usize, floats: &[f64], ints: Option<&'a [isize]>) -> impl Iterator<Item = usize> + 'a {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        (0..num_tilings).map(move |tiling| self.get_index(calculate_coords(tiling, num_tilings, &q_floats, &ints)))
+    }
+
+    /// The lazy version of `tiles_read_only`
+    pub fn tiles_iter_read_only<'a>(&'a self, num_tilings: usize, floats: &[f64], ints: Option<&'a [isize]>) -> impl Iterator<Item = Option<usize>> + 'a {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        (0..num_tilings).map(move |tiling| self.get_index_read_only(calculate_coords(tiling, num_tilings, &q_floats, &ints)))
+    }
+
+    /// The lazy version of `tiles_wrap`
+    pub fn tiles_wrap_iter<'a>(
+        &'a mut self,
+        num_tilings: usize,
+        floats: &[f64],
+        wrap_widths: &'a [Option<isize>],
+        ints: Option<&'a [isize]>,
+    ) -> impl Iterator<Item = usize> + 'a {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        (0..num_tilings).map(move |tiling| self.get_index(calculate_coords_wrap(tiling, num_tilings, &q_floats, wrap_widths, &ints)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iterators_match_vectors() {
+        let mut iht = IHT::new(1024);
+        let lazy: Vec<usize> = iht.tiles_wrap_iter(8, &[1.5, 9.9], &[None, Some(10)], Some(&[4])).collect();
+        assert_eq!(lazy, iht.tiles_wrap(8, &[1.5, 9.9], &[None, Some(10)], Some(&[4])));
+
+        let read_only: Vec<Option<usize>> = iht.tiles_iter_read_only(4, &[1.0], None).collect();
+        assert_eq!(read_only, iht.tiles_read_only(4, &[1.0], None));
+        // stop at the first tile that hasn't been seen
+        assert_eq!(iht.tiles_iter_read_only(4, &[1.0], None).position(|t| t.is_none()), Some(0));
+    }
+}
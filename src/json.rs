@@ -0,0 +1,569 @@
+//! A minimal JSON value, just enough to write the crate's reports and read back its exports without pulling in a
+//! serialization library.
+
+use std::fmt;
+
+use crate::tag::{hash_fingerprint, Tag};
+use crate::{CoderConfig, Error, HashFunction, Overflow, IHT};
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub(crate) fn object<K: Into<String>>(fields: Vec<(K, Json)>) -> Json {
+        Json::Object(fields.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    /// Parse a complete JSON document
+    pub(crate) fn parse(source: &str) -> Result<Json, Error> {
+        let mut parser = Parser {
+            bytes: source.as_bytes(),
+            pos: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos < parser.bytes.len() {
+            return Err(parser.error("trailing characters"));
+        }
+        Ok(value)
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_i64(&self) -> Option<i64> {
+        match self {
+            Json::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Int(i) => Some(*i as f64),
+            Json::Float(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+// arrays and objects nested deeper than this are rejected, rather than overflowing the stack while parsing them
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, msg: &str) -> Error {
+        Error::Format(format!("invalid JSON at byte {}: {}", self.pos, msg))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.bytes.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, literal: &str) -> Result<(), Error> {
+        if self.bytes[self.pos..].starts_with(literal.as_bytes()) {
+            self.pos += literal.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", literal)))
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, Error> {
+        self.skip_whitespace();
+        match self.bytes.get(self.pos) {
+            None => Err(self.error("unexpected end of input")),
+            Some(b'n') => self.expect("null").map(|_| Json::Null),
+            Some(b't') => self.expect("true").map(|_| Json::Bool(true)),
+            Some(b'f') => self.expect("false").map(|_| Json::Bool(false)),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'[') | Some(b'{') => {
+                if self.depth >= MAX_DEPTH {
+                    return Err(Error::Format("JSON nested too deeply".to_string()));
+                }
+                self.depth += 1;
+                let container = self.container();
+                self.depth -= 1;
+                container
+            }
+            Some(_) => self.number(),
+        }
+    }
+
+    // an array or object, starting at its opening bracket
+    fn container(&mut self) -> Result<Json, Error> {
+        match self.bytes.get(self.pos) {
+        Some(b'[') => {
+            self.pos += 1;
+            let mut items = Vec::new();
+            self.skip_whitespace();
+            if self.bytes.get(self.pos) == Some(&b']') {
+                self.pos += 1;
+                return Ok(Json::Array(items));
+            }
+            loop {
+                items.push(self.value()?);
+                self.skip_whitespace();
+                match self.bytes.get(self.pos) {
+                    Some(b',') => self.pos += 1,
+                    Some(b']') => {
+                        self.pos += 1;
+                        return Ok(Json::Array(items));
+                    }
+                    _ => return Err(self.error("expected `,` or `]`")),
+                }
+            }
+        }
+        Some(b'{') => {
+            self.pos += 1;
+            let mut fields = Vec::new();
+            self.skip_whitespace();
+            if self.bytes.get(self.pos) == Some(&b'}') {
+                self.pos += 1;
+                return Ok(Json::Object(fields));
+            }
+            loop {
+                self.skip_whitespace();
+                if self.bytes.get(self.pos) != Some(&b'"') {
+                    return Err(self.error("expected a string key"));
+                }
+                let key = self.string()?;
+                self.skip_whitespace();
+                self.expect(":")?;
+                fields.push((key, self.value()?));
+                self.skip_whitespace();
+                match self.bytes.get(self.pos) {
+                    Some(b',') => self.pos += 1,
+                    Some(b'}') => {
+                        self.pos += 1;
+                        return Ok(Json::Object(fields));
+                    }
+                    _ => return Err(self.error("expected `,` or `}`")),
+                }
+            }
+        }
+            _ => Err(self.error("expected `[` or `{`")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, Error> {
+        let start = self.pos;
+        while let Some(b'-') | Some(b'+') | Some(b'.') | Some(b'e') | Some(b'E') | Some(b'0'..=b'9') = self.bytes.get(self.pos) {
+            self.pos += 1;
+        }
+        // the bytes scanned are all ascii, so this can't split a character
+        let text = std::str::from_utf8(&self.bytes[start..self.pos]).unwrap_or("");
+        if text.is_empty() {
+            return Err(self.error("unexpected character"));
+        }
+        if let Ok(i) = text.parse::<i64>() {
+            return Ok(Json::Int(i));
+        }
+        text.parse::<f64>().map(Json::Float).map_err(|_| {
+            self.pos = start;
+            self.error(&format!("invalid number `{}`", text))
+        })
+    }
+
+    fn hex4(&mut self) -> Result<u32, Error> {
+        let digits = self.bytes.get(self.pos..self.pos + 4).ok_or_else(|| self.error("truncated escape"))?;
+        let code = std::str::from_utf8(digits)
+            .ok()
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(code)
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        // skip the opening quote
+        self.pos += 1;
+        let mut out = Vec::new();
+        loop {
+            match self.bytes.get(self.pos) {
+                None => return Err(self.error("unterminated string")),
+                Some(b'"') => {
+                    self.pos += 1;
+                    return String::from_utf8(out).map_err(|_| self.error("invalid utf-8 in string"));
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let escaped = *self.bytes.get(self.pos).ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+                    let c = match escaped {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code = self.hex4()?;
+                            // a surrogate pair encodes a character outside the basic multilingual plane
+                            if (0xd800..0xdc00).contains(&code) && self.bytes[self.pos..].starts_with(b"\\u") {
+                                self.pos += 2;
+                                let low = self.hex4()?;
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                            }
+                            std::char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))?
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    let mut buf = [0; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                Some(&b) => {
+                    out.push(b);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+}
+
+impl From<usize> for Json {
+    fn from(v: usize) -> Json {
+        Json::Int(v as i64)
+    }
+}
+
+impl From<u64> for Json {
+    fn from(v: u64) -> Json {
+        Json::Int(v as i64)
+    }
+}
+
+impl From<isize> for Json {
+    fn from(v: isize) -> Json {
+        Json::Int(v as i64)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(v: f64) -> Json {
+        Json::Float(v)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(v: bool) -> Json {
+        Json::Bool(v)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(v: &str) -> Json {
+        Json::String(v.to_string())
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(v: Vec<T>) -> Json {
+        Json::Array(v.into_iter().map(Into::into).collect())
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Int(i) => write!(f, "{}", i),
+            // JSON has no representation for NaN or infinities
+            Json::Float(x) if !x.is_finite() => f.write_str("null"),
+            Json::Float(x) if x.fract() == 0.0 && x.abs() < 1e15 => write!(f, "{:.1}", x),
+            Json::Float(x) => write!(f, "{}", x),
+            Json::String(s) => write_string(f, s),
+            Json::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+            Json::Object(fields) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+impl IHT {
+    /// Export the IHT as JSON, for debugging and for tools outside of Rust which need to inspect its tile
+    /// assignments. The entries are sorted by index, and each lists the tile's coordinates as
+    /// `[tiling, float coordinates..., ints...]`. The export also records the version of this crate which wrote it
+    /// and a fingerprint of the hash function, which [`IHT::from_json`] checks.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// iht.tiles(2, &[0.0], Some(&[1]));
+    /// let json = iht.to_json();
+    /// assert!(json.starts_with(r#"{"size":1024,"overfull_count":0,"hash_function":"sip","crate_version":"#));
+    /// assert!(json.ends_with(r#""entries":[{"coords":[0,0,1],"index":0},{"coords":[1,0,1],"index":1}]}"#));
+    /// assert_eq!(IHT::from_json(&json).unwrap().entries_snapshot(), iht.entries_snapshot());
+    /// ```
+    pub fn to_json(&self) -> String {
+        self.tagged_json(0)
+    }
+
+    /// The same as [`IHT::to_json`], but also recording the [`CoderConfig`] the table was filled with, so that
+    /// [`IHT::from_json_with_config`] can check it is imported for the same one
+    pub fn to_json_with_config(&self, config: &CoderConfig) -> String {
+        self.tagged_json(config.fingerprint())
+    }
+
+    fn tagged_json(&self, config: u64) -> String {
+        let entries = self
+            .entries_snapshot()
+            .into_iter()
+            .map(|(coords, index)| Json::object(vec![("coords", Json::from(coords)), ("index", Json::from(index))]))
+            .collect();
+        let function = match self.hash_function() {
+            HashFunction::Sip => "sip",
+            HashFunction::Fx => "fx",
+        };
+        let mut fields = vec![
+            ("size", Json::from(self.size)),
+            ("overfull_count", Json::from(self.overfull_count)),
+            ("hash_function", Json::from(function)),
+        ];
+        // only written when it isn't the default, so that tables which don't use it export exactly as before
+        if self.overflow == Overflow::DoubleHash {
+            fields.push(("overflow", Json::from("double_hash")));
+        }
+        let tag = Tag::new(hash_fingerprint(self.hash_function()), config);
+        fields.push(("crate_version", Json::from(tag.crate_version.as_str())));
+        fields.push(("hash_fingerprint", Json::from(tag.hash)));
+        if config != 0 {
+            fields.push(("config_fingerprint", Json::from(config)));
+        }
+        fields.push(("entries", Json::Array(entries)));
+        Json::object(fields).to_string()
+    }
+
+    /// Import an IHT exported by [`IHT::to_json`]. The entries may be in any order, but their indices must be exactly
+    /// `0..count`, as an IHT assigns them. The hash function and overflow strategy may be left out, in which case they
+    /// are the defaults.
+    ///
+    /// An export without the version and hash fingerprint, or written by a version of this crate with a different
+    /// major or minor version, is an `Error::Format`, as is one of a table which has had to hash tiles it had no room
+    /// for if this build's hash function no longer gives the same hashes.
+    pub fn from_json(source: &str) -> Result<IHT, Error> {
+        IHT::from_tagged_json(source).map(|(iht, _)| iht)
+    }
+
+    /// Import an IHT exported by [`IHT::to_json_with_config`], failing with an `Error::Format` unless it was exported
+    /// for exactly the same configuration, ignoring the names of the dimensions
+    pub fn from_json_with_config(source: &str, config: &CoderConfig) -> Result<IHT, Error> {
+        let (iht, tag) = IHT::from_tagged_json(source)?;
+        tag.check_config("JSON export", config).map(|_| iht)
+    }
+
+    fn from_tagged_json(source: &str) -> Result<(IHT, Tag), Error> {
+        let json = Json::parse(source)?;
+        let field = |key: &str| json.get(key).ok_or_else(|| Error::Format(format!("missing `{}`", key)));
+        let count_field = |key: &str| {
+            field(key)?
+                .as_i64()
+                .filter(|&v| v >= 0)
+                .map(|v| v as usize)
+                .ok_or_else(|| Error::Format(format!("`{}` must be a non-negative integer", key)))
+        };
+        let size = count_field("size")?;
+        let function = match json.get("hash_function").map(|f| f.as_str()) {
+            None | Some(Some("sip")) => HashFunction::Sip,
+            Some(Some("fx")) => HashFunction::Fx,
+            _ => return Err(Error::Format("`hash_function` must be \"sip\" or \"fx\"".to_string())),
+        };
+        let overflow = match json.get("overflow").map(|f| f.as_str()) {
+            None | Some(Some("hash")) => Overflow::Hash,
+            Some(Some("double_hash")) => Overflow::DoubleHash,
+            _ => return Err(Error::Format("`overflow` must be \"hash\" or \"double_hash\"".to_string())),
+        };
+        let mut iht = IHT::new(size).with_hash_function(function).with_overflow(overflow);
+        iht.overfull_count = count_field("overfull_count")?;
+        let fingerprint = |value: &Json, key: &str| {
+            value.as_i64().map(|v| v as u64).ok_or_else(|| Error::Format(format!("`{}` must be an integer", key)))
+        };
+        let tag = Tag {
+            crate_version: field("crate_version")?.as_str().ok_or_else(|| Error::Format("`crate_version` must be a string".to_string()))?.to_string(),
+            hash: fingerprint(field("hash_fingerprint")?, "hash_fingerprint")?,
+            config: json.get("config_fingerprint").map_or(Ok(0), |c| fingerprint(c, "config_fingerprint"))?,
+        };
+        tag.check_version("JSON export")?;
+        // only the indices of tiles the table had no room for come from the hash function
+        if iht.overfull_count > 0 {
+            tag.check_hash("JSON export", hash_fingerprint(function))?;
+        }
+
+        let entries = field("entries")?.as_array().ok_or_else(|| Error::Format("`entries` must be an array".to_string()))?;
+        let mut assigned = vec![false; entries.len()];
+        for entry in entries {
+            let bad_entry = || Error::Format(format!("invalid entry {}", entry));
+            let coords = entry
+                .get("coords")
+                .and_then(Json::as_array)
+                .and_then(|cs| cs.iter().map(|c| c.as_i64().map(|c| c as isize)).collect::<Option<Vec<isize>>>())
+                .ok_or_else(bad_entry)?;
+            let index = entry.get("index").and_then(Json::as_i64).filter(|&i| i >= 0).ok_or_else(bad_entry)? as usize;
+            if index >= entries.len() || index >= size || assigned[index] {
+                return Err(Error::Format(format!("entry index {} is out of sequence", index)));
+            }
+            assigned[index] = true;
+            if iht.dictionary.insert(coords, index).is_some() {
+                return Err(Error::Format(format!("duplicate coordinates in entry {}", entry)));
+            }
+        }
+        Ok((iht, tag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_nested_values() {
+        let value = Json::object(vec![
+            ("name", Json::from("a \"b\"\n")),
+            ("values", Json::from(vec![1usize, 2])),
+            ("fill", Json::from(0.5)),
+            ("whole", Json::from(2.0)),
+            ("nan", Json::from(f64::NAN)),
+            ("flag", Json::from(true)),
+        ]);
+        assert_eq!(
+            value.to_string(),
+            r#"{"name":"a \"b\"\n","values":[1,2],"fill":0.5,"whole":2.0,"nan":null,"flag":true}"#
+        );
+    }
+
+    #[test]
+    fn parses_what_it_writes() {
+        let source = r#" {"a": [1, -2.5e3, null, true], "b\u00e9\ud83d\ude00": {"c": "x\ty"}, "d": []} "#;
+        let value = Json::parse(source).unwrap();
+        assert_eq!(value.get("a"), Some(&Json::Array(vec![Json::Int(1), Json::Float(-2500.0), Json::Null, Json::Bool(true)])));
+        assert_eq!(value.get("b\u{e9}\u{1f600}").and_then(|b| b.get("c")).and_then(Json::as_str), Some("x\ty"));
+        assert_eq!(Json::parse(&value.to_string()).unwrap(), value);
+        assert!(Json::parse("[1, 2").is_err());
+        assert!(Json::parse("{} x").is_err());
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let deep = "[".repeat(200_000);
+        assert_eq!(Json::parse(&deep), Err(Error::Format("JSON nested too deeply".to_string())));
+        assert!(IHT::from_json(&format!("{{\"size\":{}}}", deep)).is_err());
+        let nested = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(Json::parse(&nested).is_ok());
+    }
+
+    #[test]
+    fn iht_round_trips_and_rejects_bad_indices() {
+        let mut iht = IHT::new(8).with_hash_function(HashFunction::Fx).with_overflow(Overflow::DoubleHash);
+        for i in 0..4 {
+            iht.tiles(4, &[i as f64], None);
+        }
+        let restored = IHT::from_json(&iht.to_json()).unwrap();
+        assert_eq!((restored.hash_function(), restored.overflow(), restored.overfull_count), (HashFunction::Fx, Overflow::DoubleHash, iht.overfull_count));
+        assert_eq!(restored.entries_snapshot(), iht.entries_snapshot());
+        let tagged = |entries: &str| format!(r#"{{"size":8,"overfull_count":0,"crate_version":"{}","hash_fingerprint":0,"entries":{}}}"#, env!("CARGO_PKG_VERSION"), entries);
+        assert!(IHT::from_json(&tagged(r#"[{"coords":[0],"index":0}]"#)).is_ok());
+        assert!(IHT::from_json(&tagged(r#"[{"coords":[0],"index":1}]"#)).is_err());
+        // an export without a tag can't be read
+        assert!(IHT::from_json(r#"{"size":8,"overfull_count":0,"entries":[{"coords":[0],"index":0}]}"#).is_err());
+
+        // the tag: the version must be compatible, and the hash unchanged once the table has overflowed
+        let retagged = |iht: &IHT, key: &str, value: Json| {
+            let mut json = Json::parse(&iht.to_json()).unwrap();
+            if let Json::Object(fields) = &mut json {
+                fields.iter_mut().find(|(k, _)| k == key).unwrap().1 = value;
+            }
+            IHT::from_json(&json.to_string())
+        };
+        let mut roomy = IHT::new(8);
+        roomy.tiles(4, &[0.0], None);
+        assert!(retagged(&roomy, "crate_version", Json::from("0.0.1")).is_err());
+        assert!(retagged(&roomy, "hash_fingerprint", Json::from(1usize)).is_ok());
+        assert!(iht.overfull_count > 0);
+        assert!(retagged(&iht, "hash_fingerprint", Json::from(1usize)).is_err());
+        assert!(retagged(&iht, "crate_version", Json::from(env!("CARGO_PKG_VERSION"))).is_ok());
+
+        let config = CoderConfig::new(4, 8, vec![crate::DimensionConfig::new(0.0, 4.0, 4)]).unwrap();
+        assert!(IHT::from_json_with_config(&iht.to_json_with_config(&config), &config).is_ok());
+        assert!(IHT::from_json_with_config(&iht.to_json(), &config).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use crate::offsets::splitmix64;
+
+/// Which prototypes of a [`KanervaCoder`] are active for a point
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Activation {
+    /// Every prototype within the given distance of the point, so the number of active features varies
+    Radius(f64),
+    /// The given number of prototypes nearest to the point, so the number of active features is always the same
+    Nearest(usize),
+}
+
+/// A Kanerva coder (or sparse distributed memory), for inputs with too many dimensions to tile. Prototypes are
+/// scattered uniformly at random over the input ranges, and a point is encoded by the prototypes close to it.
+/// Unlike tilings, the number of features is chosen directly and doesn't grow exponentially with the number of
+/// dimensions.
+///
+/// Distances are Euclidean, measured after scaling every dimension's range to `[0, 1]`. Like the tiles functions,
+/// points are encoded as a list of indices, one per active feature, which are all `< size`.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{Activation, KanervaCoder};
+/// let ranges = vec![(0.0, 1.0); 20];
+/// let coder = KanervaCoder::new(1000, &ranges, Activation::Nearest(10), 42);
+///
+/// let a = coder.tiles(&[0.5; 20]);
+/// assert_eq!(a.len(), 10);
+/// assert!(a.iter().all(|&i| i < coder.size()));
+///
+/// // nearby points share most of their prototypes
+/// let mut nearby = [0.5; 20];
+/// nearby[0] = 0.52;
+/// let b = coder.tiles(&nearby);
+/// assert!(a.iter().filter(|i| b.contains(i)).count() >= 5);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct KanervaCoder {
+    ranges: Vec<(f64, f64)>,
+    activation: Activation,
+    // the prototypes, in normalized units, stored contiguously
+    prototypes: Vec<f64>,
+}
+
+impl KanervaCoder {
+    /// Create a coder with `size` prototypes drawn over the given `(min, max)` ranges using the given seed
+    pub fn new(size: usize, ranges: &[(f64, f64)], activation: Activation, seed: u64) -> KanervaCoder {
+        assert!(!ranges.is_empty(), "at least one dimension is required");
+        assert!(ranges.iter().all(|&(min, max)| min < max && min.is_finite() && max.is_finite()), "dimension ranges must be finite with min < max");
+        if let Activation::Nearest(k) = activation {
+            assert!(k <= size, "can't activate more prototypes than there are");
+        }
+
+        let count = size * ranges.len();
+        let prototypes = (0..count as u64)
+            .map(|i| (splitmix64(seed ^ splitmix64(i)) >> 11) as f64 / (1u64 << 53) as f64)
+            .collect();
+        KanervaCoder {
+            ranges: ranges.to_vec(),
+            activation,
+            prototypes,
+        }
+    }
+
+    /// The number of prototypes; every index is `< size`
+    pub fn size(&self) -> usize {
+        self.prototypes.len() / self.ranges.len()
+    }
+
+    /// The rule deciding which prototypes are active
+    pub fn activation(&self) -> Activation {
+        self.activation
+    }
+
+    /// The location of a prototype, in the natural units of each dimension
+    pub fn prototype(&self, index: usize) -> Vec<f64> {
+        let n = self.ranges.len();
+        self.prototypes[index * n..(index + 1) * n]
+            .iter()
+            .zip(self.ranges.iter())
+            .map(|(&p, &(min, max))| min + p * (max - min))
+            .collect()
+    }
+
+    // the squared distance from the (normalized) point to every prototype
+    fn distances(&self, floats: &[f64]) -> Vec<f64> {
+        assert_eq!(floats.len(), self.ranges.len(), "expected one float per dimension");
+        assert!(!floats.iter().any(|x| x.is_nan()), "can't encode a point with a NaN coordinate");
+        let point: Vec<f64> = floats.iter().zip(self.ranges.iter()).map(|(&x, &(min, max))| (x - min) / (max - min)).collect();
+        self.prototypes
+            .chunks(point.len())
+            .map(|prototype| prototype.iter().zip(point.iter()).map(|(p, x)| (p - x) * (p - x)).sum())
+            .collect()
+    }
+
+    /// Encode a point as the indices of its active prototypes: nearest first for `Activation::Nearest`, and in
+    /// increasing order for `Activation::Radius`. Panics if any coordinate is NaN.
+    pub fn tiles(&self, floats: &[f64]) -> Vec<usize> {
+        let distances = self.distances(floats);
+        match self.activation {
+            Activation::Radius(radius) => (0..distances.len()).filter(|&i| distances[i] <= radius * radius).collect(),
+            Activation::Nearest(k) => {
+                let mut indices: Vec<usize> = (0..distances.len()).collect();
+                indices.sort_by(|&a, &b| distances[a].total_cmp(&distances[b]).then(a.cmp(&b)));
+                indices.truncate(k);
+                indices
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prototypes_span_the_ranges() {
+        let coder = KanervaCoder::new(500, &[(-1.0, 1.0), (10.0, 20.0)], Activation::Nearest(1), 7);
+        let prototypes: Vec<Vec<f64>> = (0..coder.size()).map(|i| coder.prototype(i)).collect();
+        assert!(prototypes.iter().all(|p| (-1.0..1.0).contains(&p[0]) && (10.0..20.0).contains(&p[1])));
+        assert!(prototypes.iter().any(|p| p[0] < -0.9) && prototypes.iter().any(|p| p[0] > 0.9));
+        assert_eq!(coder, KanervaCoder::new(500, &[(-1.0, 1.0), (10.0, 20.0)], Activation::Nearest(1), 7));
+
+        // a prototype's own location activates it
+        assert_eq!(coder.tiles(&coder.prototype(123)), vec![123]);
+    }
+
+    #[test]
+    fn radius_activation() {
+        let coder = KanervaCoder::new(200, &[(0.0, 1.0), (0.0, 1.0)], Activation::Radius(0.2), 1);
+        let point = [0.4, 0.6];
+        let active = coder.tiles(&point);
+        for i in 0..coder.size() {
+            let p = coder.prototype(i);
+            let distance = ((p[0] - point[0]).powi(2) + (p[1] - point[1]).powi(2)).sqrt();
+            assert_eq!(active.contains(&i), distance <= 0.2);
+        }
+        assert!(!active.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "NaN coordinate")]
+    fn nan_points_are_rejected() {
+        KanervaCoder::new(50, &[(0.0, 1.0), (0.0, 1.0)], Activation::Nearest(3), 1).tiles(&[0.5, f64::NAN]);
+    }
+}
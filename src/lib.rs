@@ -3,6 +3,149 @@
 
 use std::collections::HashMap;
 
+use hasher::KeyState;
+use inline::{CoordBuffer, InlineCoords};
+
+mod action;
+mod adaptive;
+mod audit;
+mod batch;
+mod bounds;
+mod builder;
+mod checkpoint;
+mod codegen;
+mod coder;
+mod collisions;
+mod compact;
+mod config;
+mod cross;
+mod csr;
+mod curiosity;
+mod debug;
+mod disk;
+mod ensemble;
+mod error;
+mod estimate;
+mod features;
+pub mod ffi;
+mod frozen;
+mod generic;
+mod geo;
+mod grid;
+mod hasher;
+#[cfg(feature = "viz")]
+mod heatmap;
+mod hierarchical;
+mod index;
+mod inline;
+mod ints;
+mod iter;
+mod json;
+mod kanerva;
+mod neighbors;
+mod normalizer;
+mod novelty;
+mod offsets;
+mod overflow;
+mod packed;
+mod period;
+mod perfect;
+pub mod presets;
+mod progressive;
+mod qfunction;
+mod rbf;
+mod sarsa;
+mod shared;
+mod sharded;
+mod scratch;
+mod signed;
+mod snapshot;
+mod sparse;
+mod spec;
+mod similarity;
+mod stack;
+mod stripes;
+mod tag;
+mod task;
+mod td;
+mod tileable;
+mod tiles3;
+mod time;
+mod token;
+mod transaction;
+mod unh;
+mod unknown;
+mod validate;
+mod vfa;
+#[cfg(feature = "viz")]
+mod viz;
+mod wal;
+mod wrap;
+pub use action::ActionTileCoder;
+pub use adaptive::AdaptiveCoder;
+pub use audit::Audit;
+pub use batch::write_batch_long;
+pub use bounds::{tile_bounds, tile_rectangles, tile_rectangles_geojson, write_tile_rectangles_csv, TileRect};
+pub use builder::{Diagnostic, TileCoderBuilder};
+pub use codegen::StaticIHT;
+pub use coder::{Dim, TileCoder};
+pub use collisions::CollisionAudit;
+pub use compact::CompactIHT;
+pub use config::{CoderConfig, DimensionConfig};
+pub use cross::{cross, CrossCoder};
+pub use csr::CsrMatrix;
+pub use curiosity::Curiosity;
+pub use debug::tiles_debug;
+pub use disk::DiskIHT;
+pub use ensemble::EnsembleCoder;
+pub use error::Error;
+pub use estimate::{estimate_collisions, reachable_tiles, CollisionEstimate};
+pub use features::SparseFeatures;
+pub use frozen::FrozenIHT;
+pub use generic::tiles_from;
+pub use grid::GridTileCoder;
+pub use hasher::HashFunction;
+#[cfg(feature = "viz")]
+pub use heatmap::heatmap_png;
+pub use hierarchical::HierarchicalCoder;
+pub use index::{check_index_type, tiles_as, TileIndex};
+pub use ints::tiles_ints;
+pub use kanerva::{Activation, KanervaCoder};
+pub use neighbors::TileNeighbors;
+pub use normalizer::Normalizer;
+pub use offsets::Offsets;
+pub use overflow::Overflow;
+pub use packed::PackedIHT;
+pub use period::{tiles_wrap_range, WrapRange};
+pub use perfect::PerfectIHT;
+pub use progressive::ProgressiveCoder;
+pub use qfunction::QFunction;
+pub use sarsa::SarsaLambda;
+pub use shared::SharedIHT;
+pub use sharded::{ActionShard, ShardedIHT};
+pub use scratch::TilesScratch;
+pub use signed::{tiles_signed, tiles_wrap_signed};
+pub use snapshot::Snapshot;
+pub use sparse::tiles_sparse;
+pub use spec::CoderSpec;
+pub use similarity::similarity;
+pub use stack::ObservationStack;
+pub use stripes::StripeCoder;
+pub use task::{tiles_task, TaskSharing};
+pub use td::TrueOnlineTdLambda;
+pub use tileable::Tileable;
+pub use tiles3::{python_hash, tiles_python, tiles_wrap_python};
+pub use time::{Cycle, TimeFeatures};
+#[cfg(feature = "derive")]
+pub use tilecoding_derive::TileCode;
+pub use token::{ints_from_bools, ints_from_categories, token};
+pub use transaction::Transaction;
+pub use unh::Unh;
+pub use validate::{try_tiles, try_tiles_wrap};
+pub use vfa::LinearVFA;
+pub use wal::LoggedIHT;
+pub use wrap::tiles_wrap_euclid;
+
 // convenience function for hashing a hashable object using the std hashmap's default hasher
 fn base_hash<H>(obj: H) -> usize
 where
@@ -16,32 +159,88 @@ where
     hasher.finish() as usize
 }
 
+// a hash of tile coordinates which, unlike the std hasher, is guaranteed to be stable across rust versions and
+// platforms, for use in anything that gets persisted (FNV-1a over little-endian 64-bit coordinates)
+fn stable_hash(coords: &[isize]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &c in coords {
+        for byte in (c as i64).to_le_bytes().iter() {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+// the floats are quantized (and the coordinates below computed) in chunks of this many lanes, which the compiler can
+// turn into SIMD instructions for wide inputs
+const LANES: usize = 4;
+
 fn calculate_q_floats(floats: &[f64], num_tilings: usize) -> Vec<isize> {
-    floats
-        .iter()
-        .map(|&x| (x * num_tilings as f64).floor() as isize)
-        .collect::<Vec<isize>>()
+    let n = num_tilings as f64;
+    let mut q_floats = Vec::with_capacity(floats.len());
+    let mut chunks = floats.chunks_exact(LANES);
+    for chunk in &mut chunks {
+        let mut lanes = [0.0; LANES];
+        for (lane, &x) in lanes.iter_mut().zip(chunk) {
+            *lane = (x * n).floor();
+        }
+        q_floats.extend(lanes.iter().map(|&q| q as isize));
+    }
+    q_floats.extend(chunks.remainder().iter().map(|&x| (x * n).floor() as isize));
+    q_floats
 }
 
-fn calculate_coords(tiling: usize, num_tilings: usize, q_floats: &Vec<isize>, ints: &Option<&[isize]>) -> Vec<isize> {
-    let tiling_x2 = tiling as isize * 2;
+// tiling `t` is displaced by `t * (2 * i + 1)` (in units of 1 / num_tilings of a tile) in dimension `i`, i.e. the
+// asymmetric "first odd integers" displacement vector (1, 3, 5, ...) recommended by Sutton & Barto
+fn calculate_coords(tiling: usize, num_tilings: usize, q_floats: &[isize], ints: &Option<&[isize]>) -> Vec<isize> {
     let mut coords = Vec::with_capacity(1 + q_floats.len());
+    calculate_coords_into(&mut coords, tiling, num_tilings, q_floats, ints);
+    coords
+}
+
+// the same as `calculate_coords`, but written into a buffer which can be reused from tiling to tiling
+fn calculate_coords_into<B: CoordBuffer>(coords: &mut B, tiling: usize, num_tilings: usize, q_floats: &[isize], ints: &Option<&[isize]>) {
+    let tiling_x2 = tiling as isize * 2;
+    let n = num_tilings as isize;
+    coords.clear();
     coords.push(tiling as isize);
     let mut b = tiling as isize;
-    for q in q_floats.iter() {
-        coords.push((q + b) / num_tilings as isize);
+    let mut chunks = q_floats.chunks_exact(LANES);
+    for chunk in &mut chunks {
+        let mut lanes = [0; LANES];
+        for (i, (lane, q)) in lanes.iter_mut().zip(chunk).enumerate() {
+            *lane = (q + b + tiling_x2 * i as isize) / n;
+        }
+        coords.extend_from_slice(&lanes);
+        b += tiling_x2 * LANES as isize;
+    }
+    for q in chunks.remainder() {
+        coords.push((q + b) / n);
         b += tiling_x2;
     }
     if let Some(ints) = ints {
-        coords.extend(*ints);
+        coords.extend_from_slice(ints);
     }
+}
 
+fn calculate_coords_wrap(tiling: usize, num_tilings: usize, q_floats: &[isize], wrap_widths: &[Option<isize>], ints: &Option<&[isize]>) -> Vec<isize> {
+    let mut coords = Vec::with_capacity(1 + q_floats.len());
+    calculate_coords_wrap_into(&mut coords, tiling, num_tilings, q_floats, wrap_widths, ints);
     coords
 }
 
-fn calculate_coords_wrap(tiling: usize, num_tilings: usize, q_floats: &Vec<isize>, wrap_widths: &[Option<isize>], ints: &Option<&[isize]>) -> Vec<isize> {
+// the same as `calculate_coords_wrap`, but written into a buffer which can be reused from tiling to tiling
+fn calculate_coords_wrap_into<B: CoordBuffer>(
+    coords: &mut B,
+    tiling: usize,
+    num_tilings: usize,
+    q_floats: &[isize],
+    wrap_widths: &[Option<isize>],
+    ints: &Option<&[isize]>,
+) {
     let tiling_x2 = tiling as isize * 2;
-    let mut coords = Vec::with_capacity(1 + q_floats.len());
+    coords.clear();
     coords.push(tiling as isize);
     let mut b = tiling as isize;
     for (q, width) in q_floats.iter().zip(wrap_widths.iter()) {
@@ -53,19 +252,35 @@ fn calculate_coords_wrap(tiling: usize, num_tilings: usize, q_floats: &Vec<isize
         b += tiling_x2;
     }
     if let Some(ints) = ints {
-        coords.extend(*ints);
+        coords.extend_from_slice(ints);
     }
-
-    coords
 }
 
 /// An index-hash-table, or IHT. It will allow to collect tile indices up to a
 /// certain size, after which collisions will start to occur. The underlying storage
 /// is a HashMap
+///
+/// Two IHTs are equal when they hold the same tiles at the same indices and would place new ones alike; how often
+/// each index has been visited isn't compared.
+#[derive(Clone, Debug)]
 pub struct IHT {
     size: usize,
     overfull_count: usize,
-    dictionary: HashMap<Vec<isize>, usize>,
+    dictionary: HashMap<Vec<isize>, usize, KeyState>,
+    // how many times each index has been returned by a (non-read-only) lookup; grown on demand
+    visits: Vec<u64>,
+    overflow: Overflow,
+    occupants: overflow::OccupantTilings,
+}
+
+impl PartialEq for IHT {
+    fn eq(&self, other: &IHT) -> bool {
+        self.size == other.size
+            && self.overfull_count == other.overfull_count
+            && self.hash_function() == other.hash_function()
+            && self.overflow == other.overflow
+            && self.dictionary == other.dictionary
+    }
 }
 
 impl IHT {
@@ -75,11 +290,27 @@ impl IHT {
         IHT {
             size,
             overfull_count: 0,
-            dictionary: HashMap::with_capacity(size),
+            dictionary: HashMap::with_capacity_and_hasher(size, KeyState::new(HashFunction::Sip)),
+            visits: Vec::new(),
+            overflow: Overflow::Hash,
+            occupants: Default::default(),
         }
     }
 
     fn get_index(&mut self, obj: Vec<isize>) -> usize {
+        let index = self.lookup_index(obj);
+        self.visit(index);
+        index
+    }
+
+    fn visit(&mut self, index: usize) {
+        if index >= self.visits.len() {
+            self.visits.resize(index + 1, 0);
+        }
+        self.visits[index] += 1;
+    }
+
+    fn lookup_index(&mut self, obj: Vec<isize>) -> usize {
         // store the count for later use
         let count = self.dictionary.len();
 
@@ -93,8 +324,9 @@ impl IHT {
                 // the object isn't already stored in the dictionary
                 if count >= self.size {
                     // if we're full, allow collisions (keeping track of this fact)
+                    let obj = v.into_key();
                     self.overfull_count += 1;
-                    base_hash(v.into_key()) % self.size
+                    self.overflow_index(&obj)
                 } else {
                     // otherwise, just insert into the dictionary and return the result
                     *v.insert(count)
@@ -103,8 +335,31 @@ impl IHT {
         }
     }
 
+    // the same as `get_index`, but looking the key up by reference, so that a key which is already in the table (the
+    // common case) is hashed once and never copied, and only a newly inserted key is copied into an owned `Vec`
+    fn get_index_ref(&mut self, obj: &[isize]) -> usize {
+        let index = match self.dictionary.get(obj) {
+            Some(&index) => index,
+            None if self.dictionary.len() >= self.size => {
+                self.overfull_count += 1;
+                self.overflow_index(obj)
+            }
+            None => {
+                let index = self.dictionary.len();
+                self.dictionary.insert(obj.to_vec(), index);
+                index
+            }
+        };
+        self.visit(index);
+        index
+    }
+
     fn get_index_read_only(&self, obj: Vec<isize>) -> Option<usize> {
-        self.dictionary.get(&obj).map(|i| *i)
+        self.get_index_read_only_ref(&obj)
+    }
+
+    fn get_index_read_only_ref(&self, obj: &[isize]) -> Option<usize> {
+        self.dictionary.get(obj).copied()
     }
 
     /// Convenience function to determine if the IHT is full. If it is, new tilings will result in collisions rather than new indices.
@@ -122,6 +377,53 @@ impl IHT {
         self.size
     }
 
+    /// How many times `index` has been returned by one of the (non-read-only) tiling functions
+    pub fn visits(&self, index: usize) -> u64 {
+        self.visits.get(index).copied().unwrap_or(0)
+    }
+
+    /// Find the coordinates of the tile that was assigned `index`, or `None` if no tile has been assigned that index
+    /// yet. The coordinates are laid out as `[tiling, float coordinates..., ints...]`. Note that this scans the
+    /// dictionary, so it is intended for inspection rather than for use in a hot loop.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// let indices = iht.tiles(8, &[3.6, 7.21], Some(&[1]));
+    ///
+    /// assert_eq!(iht.coords_for(indices[3]), Some(&[3, 3, 8, 1][..]));
+    /// assert_eq!(iht.coords_for(1000), None);
+    /// ```
+    pub fn coords_for(&self, index: usize) -> Option<&[isize]> {
+        self.dictionary
+            .iter()
+            .find(|(_, &i)| i == index)
+            .map(|(coords, _)| coords.as_slice())
+    }
+
+    /// A point-in-time copy of every `(coordinates, index)` entry in the IHT, sorted by index. Since the copy
+    /// doesn't borrow the IHT, it can be exported or merged after later insertions without ever observing a
+    /// partially-updated table.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// iht.tiles(2, &[0.0], None);
+    /// let snapshot = iht.entries_snapshot();
+    /// iht.tiles(2, &[5.0], None);
+    ///
+    /// assert_eq!(snapshot, vec![(vec![0, 0], 0), (vec![1, 0], 1)]);
+    /// ```
+    pub fn entries_snapshot(&self) -> Vec<(Vec<isize>, usize)> {
+        let mut entries: Vec<(Vec<isize>, usize)> = self.dictionary.iter().map(|(coords, &index)| (coords.clone(), index)).collect();
+        entries.sort_by_key(|&(_, index)| index);
+        entries
+    }
+
     /// This function takes a series of floating point and integer values, and encodes them as tile indices using the underlying IHT to deal with collisions.
     /// 
     /// # Arguments
@@ -163,9 +465,10 @@ impl IHT {
         let q_floats = calculate_q_floats(floats, num_tilings);
         let mut tiles: Vec<usize> = Vec::with_capacity(num_tilings + ints.unwrap_or(&[]).len());
 
+        let mut coords = InlineCoords::new();
         for tiling in 0..num_tilings {
-            let coords = calculate_coords(tiling, num_tilings, &q_floats, &ints);
-            tiles.push(self.get_index(coords));
+            calculate_coords_into(&mut coords, tiling, num_tilings, &q_floats, &ints);
+            tiles.push(self.get_index_ref(&coords));
         }
 
         tiles
@@ -176,9 +479,10 @@ impl IHT {
         let q_floats = calculate_q_floats(floats, num_tilings);
         let mut tiles: Vec<Option<usize>> = Vec::with_capacity(num_tilings + ints.unwrap_or(&[]).len());
 
+        let mut coords = InlineCoords::new();
         for tiling in 0..num_tilings {
-            let coords = calculate_coords(tiling, num_tilings, &q_floats, &ints);
-            tiles.push(self.get_index_read_only(coords));
+            calculate_coords_into(&mut coords, tiling, num_tilings, &q_floats, &ints);
+            tiles.push(self.get_index_read_only_ref(&coords));
         }
 
         tiles
@@ -239,9 +543,10 @@ impl IHT {
         let q_floats = calculate_q_floats(floats, num_tilings);
         let mut tiles: Vec<usize> = Vec::with_capacity(num_tilings + ints.unwrap_or(&[]).len());
 
+        let mut coords = InlineCoords::new();
         for tiling in 0..num_tilings {
-            let coords = calculate_coords_wrap(tiling, num_tilings, &q_floats, wrap_widths, &ints);
-            tiles.push(self.get_index(coords));
+            calculate_coords_wrap_into(&mut coords, tiling, num_tilings, &q_floats, wrap_widths, &ints);
+            tiles.push(self.get_index_ref(&coords));
         }
 
         tiles
@@ -252,9 +557,10 @@ impl IHT {
         let q_floats = calculate_q_floats(floats, num_tilings);
         let mut tiles: Vec<Option<usize>> = Vec::with_capacity(num_tilings + ints.unwrap_or(&[]).len());
 
+        let mut coords = InlineCoords::new();
         for tiling in 0..num_tilings {
-            let coords = calculate_coords_wrap(tiling, num_tilings, &q_floats, wrap_widths, &ints);
-            tiles.push(self.get_index_read_only(coords));
+            calculate_coords_wrap_into(&mut coords, tiling, num_tilings, &q_floats, wrap_widths, &ints);
+            tiles.push(self.get_index_read_only_ref(&coords));
         }
 
         tiles
@@ -301,9 +607,10 @@ pub fn tiles(size: usize, num_tilings: usize, floats: &[f64], ints: Option<&[isi
     let q_floats = calculate_q_floats(floats, num_tilings);
     let mut tiles: Vec<usize> = Vec::with_capacity(num_tilings + ints.unwrap_or(&[]).len());
 
+    let mut coords = InlineCoords::new();
     for tiling in 0..num_tilings {
-        let coords = calculate_coords(tiling, num_tilings, &q_floats, &ints);
-        tiles.push(base_hash(coords) % size);
+        calculate_coords_into(&mut coords, tiling, num_tilings, &q_floats, &ints);
+        tiles.push(base_hash(&coords[..]) % size);
     }
 
     tiles
@@ -365,9 +672,10 @@ pub fn tiles_wrap(size: usize, num_tilings: usize, floats: &[f64], wrap_widths:
     let q_floats = calculate_q_floats(floats, num_tilings);
     let mut tiles: Vec<usize> = Vec::with_capacity(num_tilings + ints.unwrap_or(&[]).len());
 
+    let mut coords = InlineCoords::new();
     for tiling in 0..num_tilings {
-        let coords = calculate_coords_wrap(tiling, num_tilings, &q_floats, wrap_widths, &ints);
-        tiles.push(base_hash(coords) % size);
+        calculate_coords_wrap_into(&mut coords, tiling, num_tilings, &q_floats, wrap_widths, &ints);
+        tiles.push(base_hash(&coords[..]) % size);
     }
 
     tiles
@@ -433,6 +741,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::needless_range_loop)]
     fn iht_can_collide() {
         const SIZE: usize = 32;
         let mut iht = IHT::new(SIZE);
@@ -463,6 +772,112 @@ mod tests {
         assert_eq!(indices_1, indices_2);
     }
 
+    #[test]
+    fn cloned_iht_is_independent() {
+        let mut iht = IHT::new(32);
+        iht.tiles(4, &[0.0], None);
+        let mut cloned = iht.clone();
+        assert_eq!(iht, cloned);
+
+        cloned.tiles(4, &[10.0], None);
+        assert_ne!(iht, cloned);
+        assert_eq!(iht.count(), 4);
+    }
+
+    #[test]
+    fn coords_round_trip() {
+        let mut iht = IHT::new(32);
+        let indices = iht.tiles(4, &[1.3, -2.2], None);
+        for (tiling, &index) in indices.iter().enumerate() {
+            let coords = iht.coords_for(index).unwrap();
+            assert_eq!(coords[0], tiling as isize);
+            assert_eq!(iht.tiles_read_only(4, &[1.3, -2.2], None)[tiling], Some(index));
+        }
+        assert_eq!(iht.coords_for(indices.len()), None);
+    }
+
+    #[test]
+    fn no_tilings_give_no_tiles() {
+        let mut iht = IHT::new(1024);
+        assert!(tiles(1024, 0, &[1.0], None).is_empty());
+        assert!(iht.tiles(0, &[1.0, -2.5], Some(&[3])).is_empty());
+        assert!(iht.tiles_read_only(0, &[1.0], None).is_empty());
+        assert!(iht.tiles_wrap(0, &[1.0], &[Some(4)], None).is_empty());
+        assert_eq!(iht.count(), 0);
+    }
+
+    #[test]
+    fn visits_are_counted() {
+        let mut iht = IHT::new(32);
+        iht.tiles(4, &[0.0], None);
+        iht.tiles(4, &[0.3], None);
+        iht.tiles_read_only(4, &[0.0], None);
+        assert_eq!(iht.visits(0), 2);
+        assert_eq!(iht.visits(3), 1);
+        assert_eq!(iht.visits(4), 1);
+        assert_eq!(iht.visits(5), 0);
+        assert_eq!(iht.visits(1000), 0);
+
+        let mut revisited = iht.clone();
+        revisited.tiles(4, &[0.0], None);
+        assert_eq!(revisited, iht);
+    }
+
+    #[test]
+    fn snapshot_contains_every_entry() {
+        let mut iht = IHT::new(32);
+        let indices = iht.tiles(4, &[1.0, 2.0], None);
+        let snapshot = iht.entries_snapshot();
+        assert_eq!(snapshot.len(), iht.count());
+        for (tiling, (coords, index)) in snapshot.iter().enumerate() {
+            assert_eq!(*index, indices[tiling]);
+            assert_eq!(iht.coords_for(*index), Some(&coords[..]));
+        }
+    }
+
+    #[test]
+    fn displacement_is_first_odd_integers() {
+        const NUM_TILINGS: isize = 16;
+        let q_floats = calculate_q_floats(&[3.3, 1.7, 0.2, 5.9], NUM_TILINGS as usize);
+        for tiling in 0..NUM_TILINGS {
+            let coords = calculate_coords(tiling as usize, NUM_TILINGS as usize, &q_floats, &None);
+            for (i, q) in q_floats.iter().enumerate() {
+                let displacement = 2 * i as isize + 1;
+                assert_eq!(coords[i + 1], (q + tiling * displacement) / NUM_TILINGS);
+            }
+        }
+    }
+
+    #[test]
+    fn borrowed_lookups_match_owned_ones() {
+        let mut owned = IHT::new(16);
+        let mut borrowed = IHT::new(16);
+        for i in 0..40 {
+            let coords = vec![i % 25, -i];
+            assert_eq!(owned.get_index(coords.clone()), borrowed.get_index_ref(&coords));
+        }
+        assert_eq!(owned, borrowed);
+    }
+
+    #[test]
+    fn chunked_coords_match_scalar() {
+        let floats: Vec<f64> = (0..11).map(|i| i as f64 * 1.37 - 6.0).collect();
+        for &num_tilings in &[1, 3, 32] {
+            let q_floats = calculate_q_floats(&floats, num_tilings);
+            let scalar: Vec<isize> = floats.iter().map(|&x| (x * num_tilings as f64).floor() as isize).collect();
+            assert_eq!(q_floats, scalar);
+            for tiling in 0..num_tilings {
+                let coords = calculate_coords(tiling, num_tilings, &q_floats, &Some(&[7]));
+                let t = tiling as isize;
+                let expected: Vec<isize> = std::iter::once(t)
+                    .chain(q_floats.iter().enumerate().map(|(i, q)| (q + t * (2 * i as isize + 1)) / num_tilings as isize))
+                    .chain(std::iter::once(7))
+                    .collect();
+                assert_eq!(coords, expected);
+            }
+        }
+    }
+
     /*#[bench]
     fn bench_iht_tile_code_small_single_dimension(b: &mut Bencher) {
         let mut iht = IHT::new(32);
@@ -0,0 +1,82 @@
+use crate::{calculate_coords, calculate_q_floats, IHT};
+
+/// The active tile of one tiling, along with the tiles adjacent to it. See [`IHT::tiles_neighbors`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TileNeighbors {
+    /// The index of the active tile, if it exists in the IHT
+    pub active: Option<usize>,
+    /// For each float dimension, the index of the tile one step lower in that dimension, if it exists in the IHT
+    pub lower: Vec<Option<usize>>,
+    /// For each float dimension, the index of the tile one step higher in that dimension, if it exists in the IHT
+    pub upper: Vec<Option<usize>>,
+}
+
+impl IHT {
+    /// Find the active tiles for a point along with the tiles adjacent to them (one step lower and higher in each
+    /// float dimension) in each tiling. This never inserts into the IHT: tiles that haven't been seen are `None`.
+    ///
+    /// This is useful for smoothing value estimates across tile boundaries.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// let left = iht.tiles(4, &[0.0], None);
+    /// let middle = iht.tiles(4, &[1.0], None);
+    ///
+    /// let neighbors = iht.tiles_neighbors(4, &[1.0], None);
+    /// assert_eq!(neighbors.len(), 4);
+    /// assert_eq!(neighbors[0].active, Some(middle[0]));
+    /// assert_eq!(neighbors[0].lower, vec![Some(left[0])]);
+    /// assert_eq!(neighbors[0].upper, vec![None]);
+    /// ```
+    pub fn tiles_neighbors(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<TileNeighbors> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut tiles: Vec<TileNeighbors> = Vec::with_capacity(num_tilings);
+
+        for tiling in 0..num_tilings {
+            let mut coords = calculate_coords(tiling, num_tilings, &q_floats, &ints);
+            let active = self.get_index_read_only(coords.clone());
+            let mut lower = Vec::with_capacity(floats.len());
+            let mut upper = Vec::with_capacity(floats.len());
+            for dim in 1..=floats.len() {
+                coords[dim] -= 1;
+                lower.push(self.get_index_read_only(coords.clone()));
+                coords[dim] += 2;
+                upper.push(self.get_index_read_only(coords.clone()));
+                coords[dim] -= 1;
+            }
+            tiles.push(TileNeighbors { active, lower, upper });
+        }
+
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbors_in_each_dimension() {
+        let mut iht = IHT::new(1024);
+        let centre = iht.tiles(4, &[5.0, 5.0], None);
+        let left = iht.tiles(4, &[4.0, 5.0], None);
+        let up = iht.tiles(4, &[5.0, 6.0], None);
+
+        let neighbors = iht.tiles_neighbors(4, &[5.0, 5.0], None);
+        for (tiling, n) in neighbors.iter().enumerate() {
+            assert_eq!(n.active, Some(centre[tiling]));
+            assert_eq!(n.lower, vec![Some(left[tiling]), None]);
+            assert_eq!(n.upper, vec![None, Some(up[tiling])]);
+        }
+    }
+
+    #[test]
+    fn unseen_point_has_no_tiles() {
+        let iht = IHT::new(16);
+        let neighbors = iht.tiles_neighbors(2, &[0.0], Some(&[1]));
+        assert!(neighbors.iter().all(|n| n.active.is_none() && n.lower == vec![None] && n.upper == vec![None]));
+    }
+}
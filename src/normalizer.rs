@@ -0,0 +1,138 @@
+// below this, a dimension is treated as constant rather than dividing by (nearly) zero
+const MIN_STD_DEV: f64 = 1e-8;
+
+/// Standardizes observations online, using running means and standard deviations per dimension (Welford's
+/// algorithm), so that it can sit in front of a coder whose observation scales drift or aren't known in advance.
+///
+/// Standardized values are in units of standard deviations from the mean, so the coder's dimensions should be
+/// scaled accordingly, e.g. `Dim::width(0.5)` for tiles half a standard deviation wide. Values are clipped to
+/// `[-clip, clip]` (5 by default), so that a wild observation can't blow up into a brand new, far-off tile.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{Dim, Normalizer, TileCoder};
+/// let mut normalizer = Normalizer::new(2);
+/// let mut coder = TileCoder::new(8, 4096, vec![Dim::width(0.5), Dim::width(0.5)]);
+///
+/// for i in 0..100 {
+///     let observation = [1000.0 + i as f64, 0.001 * i as f64];
+///     let indices = coder.tiles(&normalizer.observe(&observation), None);
+///     assert_eq!(indices.len(), 8);
+/// }
+/// // both dimensions are now on the same scale
+/// let standardized = normalizer.normalize(&[1049.5, 0.0495]);
+/// assert!(standardized.iter().all(|x| x.abs() < 1e-9));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Normalizer {
+    count: u64,
+    mean: Vec<f64>,
+    // the sum of squared differences from the mean
+    m2: Vec<f64>,
+    clip: f64,
+}
+
+impl Normalizer {
+    /// Create a normalizer for observations with the given number of dimensions, which hasn't seen any yet
+    pub fn new(dims: usize) -> Normalizer {
+        Normalizer {
+            count: 0,
+            mean: vec![0.0; dims],
+            m2: vec![0.0; dims],
+            clip: 5.0,
+        }
+    }
+
+    /// Clip standardized values to `[-clip, clip]` instead of the default of 5 standard deviations
+    pub fn with_clip(mut self, clip: f64) -> Normalizer {
+        assert!(clip > 0.0, "the clip must be positive");
+        self.clip = clip;
+        self
+    }
+
+    /// The number of observations seen
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The running mean of each dimension
+    pub fn mean(&self) -> &[f64] {
+        &self.mean
+    }
+
+    /// The running (population) standard deviation of each dimension
+    pub fn std_dev(&self) -> Vec<f64> {
+        self.m2
+            .iter()
+            .map(|m2| if self.count == 0 { 0.0 } else { (m2 / self.count as f64).sqrt() })
+            .collect()
+    }
+
+    /// Forget every observation
+    pub fn reset(&mut self) {
+        *self = Normalizer { clip: self.clip, ..Normalizer::new(self.mean.len()) };
+    }
+
+    /// Fold an observation into the running statistics
+    pub fn update(&mut self, observation: &[f64]) {
+        assert_eq!(observation.len(), self.mean.len(), "expected one float per dimension");
+        self.count += 1;
+        let n = self.count as f64;
+        for ((&x, mean), m2) in observation.iter().zip(self.mean.iter_mut()).zip(self.m2.iter_mut()) {
+            let delta = x - *mean;
+            *mean += delta / n;
+            *m2 += delta * (x - *mean);
+        }
+    }
+
+    /// Standardize an observation using the current statistics, without updating them. Dimensions which haven't
+    /// varied yet are only centred.
+    pub fn normalize(&self, observation: &[f64]) -> Vec<f64> {
+        assert_eq!(observation.len(), self.mean.len(), "expected one float per dimension");
+        observation
+            .iter()
+            .zip(self.mean.iter())
+            .zip(self.std_dev())
+            .map(|((&x, mean), std_dev)| {
+                let z = if std_dev < MIN_STD_DEV { x - mean } else { (x - mean) / std_dev };
+                z.max(-self.clip).min(self.clip)
+            })
+            .collect()
+    }
+
+    /// Update the statistics with an observation and then standardize it
+    pub fn observe(&mut self, observation: &[f64]) -> Vec<f64> {
+        self.update(observation);
+        self.normalize(observation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_batch_statistics() {
+        let data = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        let mut normalizer = Normalizer::new(1);
+        for &x in data.iter() {
+            normalizer.update(&[x]);
+        }
+        assert_eq!(normalizer.count(), 8);
+        assert!((normalizer.mean()[0] - 5.0).abs() < 1e-12);
+        assert!((normalizer.std_dev()[0] - 2.0).abs() < 1e-12);
+        assert!((normalizer.normalize(&[9.0])[0] - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn clipping_and_constant_dimensions() {
+        let mut normalizer = Normalizer::new(2).with_clip(3.0);
+        normalizer.update(&[0.0, 1.0]);
+        normalizer.update(&[2.0, 1.0]);
+        assert_eq!(normalizer.normalize(&[100.0, 1.5]), vec![3.0, 0.5]);
+        normalizer.reset();
+        assert_eq!(normalizer.count(), 0);
+        assert_eq!(normalizer.normalize(&[-100.0, 0.0]), vec![-3.0, 0.0]);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{TileCoder, IHT};
+
+// the pseudo-count bonus of a tile visited `n` times; unvisited tiles are as novel as once-visited ones
+fn bonus(visits: u64) -> f64 {
+    1.0 / (visits.max(1) as f64).sqrt()
+}
+
+fn mean(bonuses: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = bonuses.fold((0.0, 0), |(sum, count), b| (sum + b, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+impl IHT {
+    /// A count-based exploration bonus for a point: the mean of `1 / √n` over its tiles, where `n` is the number of
+    /// times each tile has been visited (see [`IHT::visits`]). It is 1 for a point whose tiles have never been seen
+    /// and shrinks towards 0 as they are visited, so it can be added to the reward as an intrinsic signal. This is a
+    /// read-only lookup, so asking for the novelty of a point doesn't visit it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// assert_eq!(iht.novelty(8, &[0.5], None), 1.0);
+    ///
+    /// for _ in 0..16 {
+    ///     iht.tiles(8, &[0.5], None);
+    /// }
+    /// assert_eq!(iht.novelty(8, &[0.5], None), 0.25);
+    /// // nearby points share some of the visited tiles
+    /// assert!(iht.novelty(8, &[1.0], None) > 0.25);
+    /// ```
+    pub fn novelty(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> f64 {
+        mean(
+            self.tiles_read_only(num_tilings, floats, ints)
+                .into_iter()
+                .map(|tile| bonus(tile.map_or(0, |tile| self.visits(tile)))),
+        )
+    }
+
+    /// The exploration bonus of a set of tiles which have already been looked up, see [`IHT::novelty`]
+    pub fn novelty_of(&self, tiles: &[usize]) -> f64 {
+        mean(tiles.iter().map(|&tile| bonus(self.visits(tile))))
+    }
+}
+
+impl TileCoder {
+    /// The count-based exploration bonus of a raw point, see [`IHT::novelty`]
+    pub fn novelty(&self, floats: &[f64], ints: Option<&[isize]>) -> f64 {
+        mean(
+            self.tiles_read_only(floats, ints)
+                .into_iter()
+                .map(|tile| bonus(tile.map_or(0, |tile| self.iht().visits(tile)))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dim;
+
+    #[test]
+    fn novelty_decays_with_visits() {
+        let mut coder = TileCoder::new(4, 64, vec![Dim::width(1.0)]);
+        let tiles = coder.tiles(&[0.0], None);
+        assert_eq!(coder.novelty(&[0.0], None), 1.0);
+        for _ in 0..3 {
+            coder.tiles(&[0.0], None);
+        }
+        assert_eq!(coder.novelty(&[0.0], None), 0.5);
+        assert_eq!(coder.iht().novelty_of(&tiles), 0.5);
+        assert_eq!(coder.novelty(&[10.0], None), 1.0);
+        assert_eq!(coder.iht().novelty_of(&[]), 0.0);
+    }
+}
@@ -0,0 +1,264 @@
+use std::borrow::Cow;
+
+use crate::{calculate_q_floats, IHT};
+
+/// The scheme used to offset the tilings from one another. Offsets are measured in units of `1 / num_tilings` of
+/// a tile, so that tiling `t` of dimension `i` is shifted by `offset(t, i) / num_tilings` tiles.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Offsets {
+    /// The deterministic displacement used by Sutton's tiles3 software: tiling `t` is offset by `t * (2i + 1) / num_tilings`
+    /// of a tile in dimension `i`, i.e. the asymmetric displacement vector `(1, 3, 5, ...)`
+    Tiles3,
+    /// A custom displacement vector, with one entry per float dimension: tiling `t` is offset by
+    /// `t * displacement[i] / num_tilings` of a tile in dimension `i`. The tile-coding literature recommends small odd
+    /// integers which are relatively prime to the number of tilings.
+    Displacement(Vec<isize>),
+    /// Offsets drawn uniformly at random from `0, 1, ..., num_tilings - 1` for every tiling and dimension, using the
+    /// given seed. Random offsets avoid the systematic aliasing that regular displacements can suffer from in high
+    /// dimensions, and the same seed always produces the same offsets.
+    Random(u64),
+    /// The tiles3 idea generalized to any number of tilings: dimension `i` is displaced by the `i`-th smallest
+    /// positive integer which is relatively prime to `num_tilings`, so that every dimension's offsets take all
+    /// `num_tilings` values no matter how many tilings there are. The tiles3 vector `(1, 3, 5, ...)` can repeat
+    /// offsets whenever `num_tilings` isn't a power of two (e.g. with 6 tilings, a displacement of 3 only ever
+    /// offsets by 0 or 3); for a power of two this is exactly [`Offsets::Tiles3`].
+    Coprime,
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// the `dim`-th smallest positive integer which is relatively prime to `num_tilings`
+fn coprime_displacement(num_tilings: usize, dim: usize) -> isize {
+    (1..).filter(|&k| gcd(k, num_tilings) == 1).nth(dim).unwrap() as isize
+}
+
+// the splitmix64 finalizer, used as a counter-based random number generator so that random offsets can be computed
+// on demand rather than stored
+pub(crate) fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl Offsets {
+    /// The offset of `tiling` (out of `num_tilings`) in dimension `dim`, in units of `1 / num_tilings` of a tile. With
+    /// no tilings there is nothing to offset, and the coprime and random offsets are 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::Offsets;
+    /// assert_eq!(Offsets::Tiles3.offset(2, 8, 1), 6);
+    ///
+    /// let random = Offsets::Random(42);
+    /// assert!((0..8).all(|tiling| (0..8).contains(&random.offset(tiling, 8, 3))));
+    /// assert_eq!(random.offset(5, 8, 3), Offsets::Random(42).offset(5, 8, 3));
+    /// ```
+    pub fn offset(&self, tiling: usize, num_tilings: usize, dim: usize) -> isize {
+        match self {
+            Offsets::Tiles3 => tiling as isize * (2 * dim as isize + 1),
+            Offsets::Displacement(displacement) => tiling as isize * displacement[dim],
+            Offsets::Coprime | Offsets::Random(_) if num_tilings == 0 => 0,
+            Offsets::Coprime => tiling as isize * coprime_displacement(num_tilings, dim),
+            Offsets::Random(seed) => {
+                let counter = ((tiling as u64) << 32) | dim as u64;
+                (splitmix64(seed ^ splitmix64(counter)) % num_tilings as u64) as isize
+            }
+        }
+    }
+
+    // the same offsets with anything that depends only on the number of tilings and dimensions worked out up front, to
+    // use in place of them for every tiling of a point: the coprime displacement becomes an explicit displacement
+    // vector, rather than being searched for again for every tiling and dimension
+    pub(crate) fn resolve(&self, num_tilings: usize, dims: usize) -> Cow<'_, Offsets> {
+        match self {
+            Offsets::Coprime if num_tilings == 0 => Cow::Owned(Offsets::Displacement(vec![0; dims])),
+            Offsets::Coprime => Cow::Owned(Offsets::Displacement(
+                (1..).filter(|&k| gcd(k, num_tilings) == 1).take(dims).map(|k| k as isize).collect(),
+            )),
+            _ => Cow::Borrowed(self),
+        }
+    }
+
+    /// The number of float dimensions the offsets are defined for, or `None` if they apply to any number
+    pub fn dimensions(&self) -> Option<usize> {
+        match self {
+            Offsets::Tiles3 | Offsets::Random(_) | Offsets::Coprime => None,
+            Offsets::Displacement(displacement) => Some(displacement.len()),
+        }
+    }
+}
+
+// the generalization of `calculate_coords` and `calculate_coords_wrap` to arbitrary offsets; with the tiles3 offsets
+// this produces exactly the same coordinates as those functions
+pub(crate) fn calculate_coords_offsets(
+    tiling: usize,
+    num_tilings: usize,
+    q_floats: &[isize],
+    offsets: &Offsets,
+    wrap_widths: Option<&[Option<isize>]>,
+    ints: &Option<&[isize]>,
+) -> Vec<isize> {
+    let n = num_tilings as isize;
+    let mut coords = Vec::with_capacity(1 + q_floats.len() + ints.map_or(0, |i| i.len()));
+    coords.push(tiling as isize);
+    for (dim, q) in q_floats.iter().enumerate() {
+        let b = offsets.offset(tiling, num_tilings, dim);
+        coords.push(match wrap_widths {
+            None => (q + b) / n,
+            Some(wrap_widths) => {
+                let c = (q + b % n) / n;
+                match wrap_widths[dim] {
+                    Some(w) => c % w,
+                    None => c,
+                }
+            }
+        });
+    }
+    if let Some(ints) = ints {
+        coords.extend(*ints);
+    }
+
+    coords
+}
+
+fn check_dimensions(offsets: &Offsets, floats: &[f64]) {
+    if let Some(dims) = offsets.dimensions() {
+        assert_eq!(dims, floats.len(), "the displacement vector must have one entry per float");
+    }
+}
+
+impl IHT {
+    /// The same as `tiles`, except that the tilings are offset from one another using the given scheme rather than
+    /// the tiles3 displacement.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{IHT, Offsets};
+    /// let mut iht = IHT::new(1024);
+    ///
+    /// // the tiles3 offsets are the displacement vector (1, 3)
+    /// let a = iht.tiles_with_offsets(8, &[3.6, 7.21], &Offsets::Displacement(vec![1, 3]), None);
+    /// assert_eq!(a, iht.tiles(8, &[3.6, 7.21], None));
+    ///
+    /// // but any other vector can be used instead
+    /// let b = iht.tiles_with_offsets(8, &[3.6, 7.21], &Offsets::Displacement(vec![1, 5]), None);
+    /// assert_eq!(a[0], b[0]);
+    /// assert_ne!(a, b);
+    /// ```
+    pub fn tiles_with_offsets(&mut self, num_tilings: usize, floats: &[f64], offsets: &Offsets, ints: Option<&[isize]>) -> Vec<usize> {
+        check_dimensions(offsets, floats);
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let offsets = offsets.resolve(num_tilings, floats.len());
+        let mut tiles: Vec<usize> = Vec::with_capacity(num_tilings);
+
+        for tiling in 0..num_tilings {
+            let coords = calculate_coords_offsets(tiling, num_tilings, &q_floats, &offsets, None, &ints);
+            tiles.push(self.get_index(coords));
+        }
+
+        tiles
+    }
+
+    /// The read-only version of `tiles_with_offsets`
+    pub fn tiles_with_offsets_read_only(&self, num_tilings: usize, floats: &[f64], offsets: &Offsets, ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        check_dimensions(offsets, floats);
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let offsets = offsets.resolve(num_tilings, floats.len());
+        let mut tiles: Vec<Option<usize>> = Vec::with_capacity(num_tilings);
+
+        for tiling in 0..num_tilings {
+            let coords = calculate_coords_offsets(tiling, num_tilings, &q_floats, &offsets, None, &ints);
+            tiles.push(self.get_index_read_only(coords));
+        }
+
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate_coords, calculate_coords_wrap};
+
+    #[test]
+    fn tiles3_offsets_match_builtin_coords() {
+        let q_floats = calculate_q_floats(&[-3.3, 1.7, 12.2], 8);
+        let wrap_widths = [Some(4), None, Some(7)];
+        for tiling in 0..8 {
+            assert_eq!(
+                calculate_coords_offsets(tiling, 8, &q_floats, &Offsets::Tiles3, None, &Some(&[2])),
+                calculate_coords(tiling, 8, &q_floats, &Some(&[2]))
+            );
+            assert_eq!(
+                calculate_coords_offsets(tiling, 8, &q_floats, &Offsets::Tiles3, Some(&wrap_widths), &None),
+                calculate_coords_wrap(tiling, 8, &q_floats, &wrap_widths, &None)
+            );
+        }
+    }
+
+    #[test]
+    fn read_only_with_offsets() {
+        let offsets = Offsets::Displacement(vec![3, 1]);
+        let mut iht = IHT::new(64);
+        let indices = iht.tiles_with_offsets(4, &[0.5, 0.5], &offsets, None);
+        let read_only = iht.tiles_with_offsets_read_only(4, &[0.5, 0.5], &offsets, None);
+        assert_eq!(read_only, indices.into_iter().map(Some).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn coprime_offsets_are_uniform_for_any_count() {
+        let distinct = |offsets: &Offsets, n: usize, dim: usize| {
+            let mut seen: Vec<isize> = (0..n).map(|t| offsets.offset(t, n, dim).rem_euclid(n as isize)).collect();
+            seen.sort();
+            seen.dedup();
+            seen.len()
+        };
+        // 3 shares a factor with 6, so the tiles3 offsets of the second dimension only take 2 values
+        assert_eq!(distinct(&Offsets::Tiles3, 6, 1), 2);
+        assert!((0..4).all(|dim| distinct(&Offsets::Coprime, 6, dim) == 6));
+        assert!((0..5).all(|dim| distinct(&Offsets::Coprime, 10, dim) == 10));
+        assert_eq!(*Offsets::Coprime.resolve(10, 4), Offsets::Displacement(vec![1, 3, 7, 9]));
+
+        let mut iht = IHT::new(1024);
+        assert_eq!(iht.tiles_with_offsets(8, &[0.3, 1.7, -2.2], &Offsets::Coprime, None), iht.tiles(8, &[0.3, 1.7, -2.2], None));
+    }
+
+    #[test]
+    fn random_offsets_depend_on_seed() {
+        let offsets = |seed| -> Vec<isize> { (0..16).flat_map(|t| (0..4).map(move |d| Offsets::Random(seed).offset(t, 16, d))).collect() };
+        assert_eq!(offsets(1), offsets(1));
+        assert_ne!(offsets(1), offsets(2));
+        // every offset is used by some tiling
+        assert!((0..16).all(|b| offsets(1).contains(&b)));
+
+        let mut iht = IHT::new(1024);
+        let a = iht.tiles_with_offsets(16, &[0.3, 0.7, 0.1, 0.9], &Offsets::Random(1), None);
+        assert_eq!(iht.tiles_with_offsets(16, &[0.3, 0.7, 0.1, 0.9], &Offsets::Random(1), None), a);
+        assert_ne!(iht.tiles_with_offsets(16, &[0.3, 0.7, 0.1, 0.9], &Offsets::Random(2), None), a);
+    }
+
+    #[test]
+    fn no_tilings_have_no_offsets() {
+        for offsets in [Offsets::Coprime, Offsets::Random(7)].iter() {
+            assert_eq!(offsets.offset(0, 0, 3), 0);
+            assert!(IHT::new(64).tiles_with_offsets(0, &[0.5, 0.5], offsets, None).is_empty());
+        }
+        assert_eq!(*Offsets::Coprime.resolve(0, 2), Offsets::Displacement(vec![0, 0]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn displacement_must_match_dimensions() {
+        IHT::new(64).tiles_with_offsets(4, &[0.5, 0.5], &Offsets::Displacement(vec![1]), None);
+    }
+}
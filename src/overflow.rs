@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::{stable_hash, IHT};
+
+/// How an IHT picks the index of a tile it has no room for, once it is full
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Hash the tile's coordinates onto an index, as tiles3 does; the default
+    #[default]
+    Hash,
+    /// Double hashing: a second hash of the coordinates, independent of the dictionary's, picks one of the indices
+    /// belonging to tiles of the same tiling. A tile then only ever collides with tiles of its own tiling, so the
+    /// indices of a point's tiles in different tilings never collide with each other, and the overflow of each tiling
+    /// is spread evenly over that tiling's own indices. A tile of a tiling with no indices at all falls back to
+    /// `Hash`.
+    DoubleHash,
+}
+
+// the indices of the tiles of each tiling, gathered the first time a full table needs them: a full table never gains
+// entries, so this never goes stale. It isn't part of the table's value, so it is ignored when comparing tables.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OccupantTilings(OnceLock<HashMap<isize, Vec<usize>>>);
+
+// new tiles staged on top of an IHT without being added to it, as by a transaction or a snapshot. They get the indices
+// they would get if they were added in the order they were staged, and once they fill the table, the tiles there's no
+// room for get the indices they would get in the table with the staged tiles added.
+#[derive(Debug, Default)]
+pub(crate) struct Staged {
+    // in the order they were staged, so the index of each is the IHT's count plus its position
+    pub(crate) tiles: Vec<Vec<isize>>,
+    indices: HashMap<Vec<isize>, usize>,
+    // nothing more is staged once the table is full, so these stay valid
+    occupants: OccupantTilings,
+}
+
+impl Staged {
+    // the index of a tile which isn't in `iht`, staging it if there's room, along with whether there wasn't
+    pub(crate) fn get_index(&mut self, iht: &IHT, obj: &[isize]) -> (usize, bool) {
+        if let Some(&index) = self.indices.get(obj) {
+            return (index, false);
+        }
+        let count = iht.count() + self.tiles.len();
+        if count >= iht.size() {
+            (iht.overflow_index_with(obj, &self.tiles, &self.occupants), true)
+        } else {
+            self.indices.insert(obj.to_vec(), count);
+            self.tiles.push(obj.to_vec());
+            (count, false)
+        }
+    }
+}
+
+impl IHT {
+    /// Pick the indices of tiles the IHT has no room for with the given strategy, see [`Overflow`]. The entries in the
+    /// table are unaffected, but tiles it had no room for will be given different indices from now on.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{Overflow, IHT};
+    /// let mut iht = IHT::new(64).with_overflow(Overflow::DoubleHash);
+    /// for i in 0..16 {
+    ///     iht.tiles(8, &[i as f64], None);
+    /// }
+    /// assert!(iht.full());
+    ///
+    /// // the overflow tiles of a new point land on tiles of their own tilings
+    /// let tiles = iht.tiles(8, &[100.0], None);
+    /// for (tiling, &index) in tiles.iter().enumerate() {
+    ///     assert_eq!(iht.coords_for(index).unwrap()[0], tiling as isize);
+    /// }
+    /// ```
+    pub fn with_overflow(mut self, overflow: Overflow) -> IHT {
+        self.overflow = overflow;
+        self
+    }
+
+    /// The strategy used for the indices of tiles the IHT has no room for, see [`IHT::with_overflow`]
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    // the index of a tile the IHT has no room for
+    pub(crate) fn overflow_index(&self, obj: &[isize]) -> usize {
+        if self.overflow == Overflow::Hash || self.dictionary.len() < self.size {
+            return self.hash_function().hash(obj) % self.size;
+        }
+        self.overflow_index_with(obj, &[], &self.occupants)
+    }
+
+    // the index of a tile which there is no room for once `staged` (new tiles whose indices follow the IHT's own, as
+    // in a transaction) have been added, so the table is taken to be full. `occupants` caches the indices of each
+    // tiling, which is only valid as long as `staged` doesn't change.
+    pub(crate) fn overflow_index_with(&self, obj: &[isize], staged: &[Vec<isize>], occupants: &OccupantTilings) -> usize {
+        let first = self.hash_function().hash(obj) % self.size;
+        if self.overflow == Overflow::Hash || obj.is_empty() {
+            return first;
+        }
+        let by_tiling = occupants.0.get_or_init(|| {
+            let mut by_tiling: HashMap<isize, Vec<usize>> = HashMap::new();
+            let staged = staged.iter().enumerate().map(|(i, coords)| (coords, self.dictionary.len() + i));
+            for (coords, index) in self.dictionary.iter().map(|(coords, &index)| (coords, index)).chain(staged) {
+                by_tiling.entry(coords.first().copied().unwrap_or(0)).or_default().push(index);
+            }
+            for indices in by_tiling.values_mut() {
+                indices.sort_unstable();
+            }
+            by_tiling
+        });
+        match by_tiling.get(&obj[0]) {
+            Some(indices) => indices[(stable_hash(obj) % indices.len() as u64) as usize],
+            None => first,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn double_hashing_stays_within_the_tiling() {
+        let fill = |overflow| {
+            let mut iht = IHT::new(256).with_overflow(overflow);
+            for i in 0..40 {
+                iht.tiles(8, &[i as f64 * 0.9], Some(&[1]));
+            }
+            iht
+        };
+        let mut hashed = fill(Overflow::Hash);
+        let mut double = fill(Overflow::DoubleHash);
+        assert_eq!(hashed.entries_snapshot(), double.entries_snapshot());
+
+        let mut same_tiling = [0; 2];
+        for i in 0..50 {
+            let point = [1000.0 + i as f64 * 3.0];
+            for (count, iht) in same_tiling.iter_mut().zip([&mut hashed, &mut double].iter_mut()) {
+                let tiles = iht.tiles(8, &point, Some(&[1]));
+                *count += tiles.iter().enumerate().filter(|&(t, &index)| iht.coords_for(index).unwrap()[0] == t as isize).count();
+            }
+            // deterministic
+            assert_eq!(double.tiles(8, &point, Some(&[1])), double.tiles(8, &point, Some(&[1])));
+        }
+        // a single hash lands on the same tiling about one time in eight
+        assert!(same_tiling[0] < 100);
+        assert_eq!(same_tiling[1], 400);
+    }
+}
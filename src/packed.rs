@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use crate::{base_hash, IHT};
+
+// a packed key holds its length in the top 16 bits and up to 7 coordinates in 16 bits each below that
+const MAX_PACKED: usize = 7;
+
+// pack coordinates into a `u128` key, as long as there are few enough of them and each one fits in 16 bits
+fn pack(coords: &[isize]) -> Option<u128> {
+    if coords.len() > MAX_PACKED {
+        return None;
+    }
+    let mut key = 0;
+    for &c in coords {
+        if c < i16::MIN as isize || c > i16::MAX as isize {
+            return None;
+        }
+        key = (key << 16) | (c as u16 as u128);
+    }
+    Some(key | (coords.len() as u128) << (16 * MAX_PACKED))
+}
+
+/// An IHT which stores tile coordinates packed into `u128` keys instead of one `Vec<isize>` per tile, so that
+/// encoding a point doesn't allocate (see [`PackedIHT::tiles_into`]). Coordinates are packed when a tile has at most 7
+/// of them (the tiling, the floats, and the ints) and each fits in 16 bits; any other tile falls back to an owned
+/// `Vec` key, so every input still works.
+///
+/// It hands out exactly the same indices as an [`IHT`] fed the same points, including when it is overfull, and can be
+/// converted to one with [`PackedIHT::to_iht`].
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{PackedIHT, IHT};
+/// let mut packed = PackedIHT::new(1024);
+/// let mut iht = IHT::new(1024);
+/// assert_eq!(packed.tiles(8, &[3.6, 7.21], None), iht.tiles(8, &[3.6, 7.21], None));
+///
+/// let mut indices = [0; 8];
+/// packed.tiles_into(&mut indices, &[3.7, 7.21], Some(&[2]));
+/// assert_eq!(indices.to_vec(), iht.tiles(8, &[3.7, 7.21], Some(&[2])));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct PackedIHT {
+    size: usize,
+    overfull_count: usize,
+    packed: HashMap<u128, usize>,
+    spilled: HashMap<Vec<isize>, usize>,
+}
+
+impl PackedIHT {
+    /// Create a new table with the given size; no index will be `>= size`
+    pub fn new(size: usize) -> PackedIHT {
+        PackedIHT {
+            size,
+            overfull_count: 0,
+            packed: HashMap::with_capacity(size),
+            spilled: HashMap::new(),
+        }
+    }
+
+    /// The size of the table
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of tiles in the table
+    pub fn count(&self) -> usize {
+        self.packed.len() + self.spilled.len()
+    }
+
+    /// Whether the table is full, so that new tiles collide with existing ones
+    pub fn full(&self) -> bool {
+        self.count() >= self.size
+    }
+
+    /// The number of tiles whose coordinates didn't fit in a packed key
+    pub fn spilled_count(&self) -> usize {
+        self.spilled.len()
+    }
+
+    /// The number of lookups that collided because the table was full
+    pub fn overfull_count(&self) -> usize {
+        self.overfull_count
+    }
+
+    fn get_index(&mut self, coords: &[isize]) -> usize {
+        let count = self.count();
+        let existing = match pack(coords) {
+            Some(key) => self.packed.get(&key).copied(),
+            None => self.spilled.get(coords).copied(),
+        };
+        match existing {
+            Some(index) => index,
+            None if count >= self.size => {
+                self.overfull_count += 1;
+                base_hash(coords) % self.size
+            }
+            None => {
+                match pack(coords) {
+                    Some(key) => self.packed.insert(key, count),
+                    None => self.spilled.insert(coords.to_vec(), count),
+                };
+                count
+            }
+        }
+    }
+
+    fn get_index_read_only(&self, coords: &[isize]) -> Option<usize> {
+        match pack(coords) {
+            Some(key) => self.packed.get(&key).copied(),
+            None => self.spilled.get(coords).copied(),
+        }
+    }
+
+    // call `f` with the coordinates of each tiling in turn, built in a stack buffer whenever they fit in one
+    fn for_each_coords(num_tilings: usize, floats: &[f64], ints: Option<&[isize]>, mut f: impl FnMut(usize, &[isize])) {
+        let ints = ints.unwrap_or(&[]);
+        let len = 1 + floats.len() + ints.len();
+        let mut stack = [0isize; MAX_PACKED];
+        let mut heap = Vec::new();
+        let coords: &mut [isize] = if len <= MAX_PACKED {
+            &mut stack[..len]
+        } else {
+            heap.resize(len, 0);
+            &mut heap
+        };
+        for tiling in 0..num_tilings {
+            coords[0] = tiling as isize;
+            let mut b = tiling as isize;
+            for (i, &x) in floats.iter().enumerate() {
+                let q = (x * num_tilings as f64).floor() as isize;
+                coords[i + 1] = (q + b) / num_tilings as isize;
+                b += tiling as isize * 2;
+            }
+            coords[1 + floats.len()..].copy_from_slice(ints);
+            f(tiling, coords);
+        }
+    }
+
+    /// Encode a point as `out.len()` tile indices (i.e. with `out.len()` tilings) written into `out`, without
+    /// allocating unless the point's coordinates don't fit in a packed key. See [`IHT::tiles`].
+    pub fn tiles_into(&mut self, out: &mut [usize], floats: &[f64], ints: Option<&[isize]>) {
+        PackedIHT::for_each_coords(out.len(), floats, ints, |tiling, coords| out[tiling] = self.get_index(coords));
+    }
+
+    /// Encode a point as `num_tilings` tile indices, see [`IHT::tiles`]
+    pub fn tiles(&mut self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        let mut tiles = vec![0; num_tilings];
+        self.tiles_into(&mut tiles, floats, ints);
+        tiles
+    }
+
+    /// The read-only version of `tiles`, see [`IHT::tiles_read_only`]
+    pub fn tiles_read_only(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let mut tiles = vec![None; num_tilings];
+        PackedIHT::for_each_coords(num_tilings, floats, ints, |tiling, coords| tiles[tiling] = self.get_index_read_only(coords));
+        tiles
+    }
+
+    /// Convert to an ordinary IHT with the same entries, e.g. to use the rest of the crate's tools on it
+    pub fn to_iht(&self) -> IHT {
+        let mut entries: Vec<(Vec<isize>, usize)> = self
+            .packed
+            .iter()
+            .map(|(&key, &index)| {
+                let len = (key >> (16 * MAX_PACKED)) as usize;
+                let coords = (0..len).rev().map(|i| (key >> (16 * i)) as u16 as i16 as isize).collect();
+                (coords, index)
+            })
+            .chain(self.spilled.iter().map(|(coords, &index)| (coords.clone(), index)))
+            .collect();
+        entries.sort_by_key(|&(_, index)| index);
+        let mut iht = IHT::new(self.size);
+        for (coords, _) in entries {
+            iht.lookup_index(coords);
+        }
+        iht.overfull_count = self.overfull_count;
+        iht
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_iht_including_spills_and_overflow() {
+        let mut packed = PackedIHT::new(256);
+        let mut iht = IHT::new(256);
+        for i in 0..60 {
+            let x = i as f64 * 0.7 - 20.0;
+            assert_eq!(packed.tiles(8, &[x, 1.0], Some(&[i % 3])), iht.tiles(8, &[x, 1.0], Some(&[i % 3])));
+            // too many coordinates, and too large, to pack
+            assert_eq!(packed.tiles(4, &[x, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0], None), iht.tiles(4, &[x, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0], None));
+            assert_eq!(packed.tiles(4, &[x * 1e6], None), iht.tiles(4, &[x * 1e6], None));
+        }
+        assert!(packed.full() && packed.spilled_count() > 0);
+        assert_eq!(packed.tiles_read_only(8, &[-20.0, 1.0], Some(&[0])), iht.tiles_read_only(8, &[-20.0, 1.0], Some(&[0])));
+        assert_eq!(packed.to_iht().entries_snapshot(), iht.entries_snapshot());
+    }
+
+    #[test]
+    fn packing_is_lossless() {
+        assert_eq!(pack(&[0, -1]), Some((2 << 112) | 0xffff));
+        assert_ne!(pack(&[0, 1]), pack(&[0, 1, 0]));
+        assert_eq!(pack(&[40_000]), None);
+        assert_eq!(pack(&[0; 8]), None);
+    }
+}
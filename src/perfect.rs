@@ -0,0 +1,229 @@
+use crate::inline::{CoordBuffer, InlineCoords};
+use crate::{calculate_coords_wrap_into, calculate_q_floats, stable_hash, IHT};
+
+// the average number of keys per bucket, which trades the size of the displacement table against build time
+const LAMBDA: usize = 5;
+
+// the displacements of each bucket
+type Displacements = Vec<(u64, u64)>;
+
+fn mix(mut x: u64) -> u64 {
+    // the splitmix64 finalizer
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+// the bucket hash and the two displacement hashes of some coordinates under the given seed
+fn hashes(coords: &[isize], seed: u64) -> (u64, u64, u64) {
+    let h1 = mix(stable_hash(coords) ^ seed.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    let h2 = mix(h1);
+    (h1 >> 32, h1 & 0xffff_ffff, h2 & 0xffff_ffff)
+}
+
+fn displace(f1: u64, f2: u64, d1: u64, d2: u64, len: u64) -> usize {
+    (f2.wrapping_add(f1.wrapping_mul(d1)).wrapping_add(d2) % len) as usize
+}
+
+/// An IHT exported as a minimal perfect hash table, see [`IHT::perfect_hash`]. Each tile in the table hashes to a
+/// slot of its own, so a lookup is a hash, one displacement read, and a single comparison of the coordinates, with no
+/// probing. Like a [`FrozenIHT`](crate::FrozenIHT), it can't learn new tiles: those are `None`.
+///
+/// The table uses the "hash, displace and compress" construction (the same as the `phf` crate): the tiles are split
+/// into buckets, and each bucket is given a pair of displacements which move all of its tiles into free slots.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::IHT;
+/// let mut iht = IHT::new(1024);
+/// let indices = iht.tiles(8, &[3.6, 7.21], None);
+///
+/// let perfect = iht.perfect_hash();
+/// assert_eq!(perfect.tiles(8, &[3.6, 7.21], None), indices.into_iter().map(Some).collect::<Vec<_>>());
+/// assert_eq!(perfect.tiles(8, &[-37.2, 7.0], None), vec![None; 8]);
+///
+/// // the lookups themselves don't allocate
+/// let mut out = [None; 8];
+/// perfect.tiles_into(&mut out, &[3.7, 7.21], None);
+/// assert_eq!(out, [Some(0), Some(1), Some(2), None, Some(4), Some(5), Some(6), Some(7)]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct PerfectIHT {
+    size: usize,
+    seed: u64,
+    displacements: Displacements,
+    // slot `s` holds the coordinates `arena[starts[s]..starts[s + 1]]`, which have the index `indices[s]`
+    starts: Vec<usize>,
+    arena: Vec<isize>,
+    indices: Vec<usize>,
+}
+
+impl IHT {
+    /// Export the table as a minimal perfect hash table for allocation-free, probe-free read-only lookups, e.g. for
+    /// serving a trained policy. Building it takes time roughly linear in the number of tiles in practice. The
+    /// indices are exactly those of the IHT, and the construction is deterministic, so the same table always exports
+    /// the same way.
+    pub fn perfect_hash(&self) -> PerfectIHT {
+        let entries = self.entries_snapshot();
+        let len = entries.len().max(1);
+        let buckets = entries.len().div_ceil(LAMBDA).max(1);
+
+        let mut seed = 0;
+        loop {
+            if let Some((displacements, slots)) = try_displace(&entries, seed, buckets, len) {
+                let mut starts = Vec::with_capacity(entries.len() + 1);
+                let mut arena = Vec::new();
+                let mut indices = Vec::with_capacity(entries.len());
+                starts.push(0);
+                for entry in slots.into_iter().take(entries.len()) {
+                    let (coords, index) = &entries[entry];
+                    arena.extend_from_slice(coords);
+                    starts.push(arena.len());
+                    indices.push(*index);
+                }
+                return PerfectIHT {
+                    size: self.size,
+                    seed,
+                    displacements,
+                    starts,
+                    arena,
+                    indices,
+                };
+            }
+            seed += 1;
+        }
+    }
+}
+
+// find displacements for every bucket under `seed`, returning them along with which entry ended up in each slot, or
+// `None` if some bucket can't be placed and a different seed is needed
+fn try_displace(entries: &[(Vec<isize>, usize)], seed: u64, buckets: usize, len: usize) -> Option<(Displacements, Vec<usize>)> {
+    let hashed: Vec<(u64, u64, u64)> = entries.iter().map(|(coords, _)| hashes(coords, seed)).collect();
+    let mut members: Vec<Vec<usize>> = vec![Vec::new(); buckets];
+    for (entry, &(g, _, _)) in hashed.iter().enumerate() {
+        members[g as usize % buckets].push(entry);
+    }
+    // place the biggest buckets first, while there is the most room
+    let mut order: Vec<usize> = (0..buckets).collect();
+    order.sort_by(|&a, &b| members[b].len().cmp(&members[a].len()).then(a.cmp(&b)));
+
+    let mut displacements = vec![(0, 0); buckets];
+    let mut slots = vec![usize::MAX; len];
+    let mut placed = Vec::new();
+    for bucket in order {
+        if members[bucket].is_empty() {
+            break;
+        }
+        let found = (0..len as u64).flat_map(|d1| (0..len as u64).map(move |d2| (d1, d2))).find(|&(d1, d2)| {
+            placed.clear();
+            for &entry in &members[bucket] {
+                let (_, f1, f2) = hashed[entry];
+                let slot = displace(f1, f2, d1, d2, len as u64);
+                if slots[slot] != usize::MAX || placed.contains(&slot) {
+                    return false;
+                }
+                placed.push(slot);
+            }
+            true
+        })?;
+        displacements[bucket] = found;
+        for (&entry, &slot) in members[bucket].iter().zip(placed.iter()) {
+            slots[slot] = entry;
+        }
+    }
+    Some((displacements, slots))
+}
+
+impl PerfectIHT {
+    /// The size of the IHT that was exported
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of tiles in the table
+    pub fn count(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// The index of the tile with the given coordinates, if it is in the table
+    pub fn get(&self, coords: &[isize]) -> Option<usize> {
+        if self.indices.is_empty() {
+            return None;
+        }
+        let (g, f1, f2) = hashes(coords, self.seed);
+        let (d1, d2) = self.displacements[g as usize % self.displacements.len()];
+        let slot = displace(f1, f2, d1, d2, self.indices.len() as u64);
+        if &self.arena[self.starts[slot]..self.starts[slot + 1]] == coords {
+            Some(self.indices[slot])
+        } else {
+            None
+        }
+    }
+
+    /// Encode a point as `out.len()` tile indices (i.e. with `out.len()` tilings) written into `out`, without
+    /// allocating unless the point has more coordinates than fit on the stack. See [`IHT::tiles_read_only`].
+    pub fn tiles_into(&self, out: &mut [Option<usize>], floats: &[f64], ints: Option<&[isize]>) {
+        let num_tilings = out.len() as isize;
+        let mut coords = InlineCoords::new();
+        for (tiling, tile) in out.iter_mut().enumerate() {
+            coords.clear();
+            coords.push(tiling as isize);
+            let mut b = tiling as isize;
+            for &x in floats {
+                let q = (x * num_tilings as f64).floor() as isize;
+                coords.push((q + b) / num_tilings);
+                b += tiling as isize * 2;
+            }
+            if let Some(ints) = ints {
+                coords.extend_from_slice(ints);
+            }
+            *tile = self.get(&coords);
+        }
+    }
+
+    /// The equivalent of [`IHT::tiles_read_only`] on the exported table
+    pub fn tiles(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let mut tiles = vec![None; num_tilings];
+        self.tiles_into(&mut tiles, floats, ints);
+        tiles
+    }
+
+    /// The equivalent of [`IHT::tiles_wrap_read_only`] on the exported table
+    pub fn tiles_wrap(&self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut coords = InlineCoords::new();
+        (0..num_tilings)
+            .map(|tiling| {
+                calculate_coords_wrap_into(&mut coords, tiling, num_tilings, &q_floats, wrap_widths, &ints);
+                self.get(&coords)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perfect_hash_matches_read_only() {
+        let mut iht = IHT::new(4096);
+        for i in 0..300 {
+            iht.tiles(8, &[i as f64 * 0.37, -(i as f64) * 0.11], Some(&[i % 3]));
+            iht.tiles_wrap(8, &[i as f64 * 0.5], &[Some(5)], None);
+        }
+        let perfect = iht.perfect_hash();
+        assert_eq!(perfect.count(), iht.count());
+        for (coords, index) in iht.entries_snapshot() {
+            assert_eq!(perfect.get(&coords), Some(index));
+        }
+        for i in -20..20 {
+            let x = i as f64 * 0.9;
+            assert_eq!(perfect.tiles(8, &[x, 1.0], Some(&[1])), iht.tiles_read_only(8, &[x, 1.0], Some(&[1])));
+            assert_eq!(perfect.tiles_wrap(8, &[x], &[Some(5)], None), iht.tiles_wrap_read_only(8, &[x], &[Some(5)], None));
+        }
+        assert_eq!(perfect, iht.perfect_hash());
+        assert_eq!(IHT::new(16).perfect_hash().tiles(4, &[0.0], None), vec![None; 4]);
+    }
+}
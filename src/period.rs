@@ -0,0 +1,96 @@
+use crate::{tiles_wrap, IHT};
+
+/// The wrap-around of a float in its natural units: the float repeats every `range` (e.g. `2π` for an angle in
+/// radians), and each repetition is covered by `tiles` tiles per tiling. See [`IHT::tiles_wrap_range`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WrapRange {
+    range: f64,
+    tiles: usize,
+}
+
+impl WrapRange {
+    /// Wrap around every `range`, with `tiles` tiles per tiling across it
+    pub fn new(range: f64, tiles: usize) -> WrapRange {
+        assert!(range > 0.0 && range.is_finite(), "the wrap range must be positive and finite");
+        assert!(tiles > 0, "a wrapping dimension needs at least one tile");
+        WrapRange { range, tiles }
+    }
+
+    /// The range the float repeats over, in its own units
+    pub fn range(&self) -> f64 {
+        self.range
+    }
+
+    /// The number of tiles per tiling across the range
+    pub fn tiles(&self) -> usize {
+        self.tiles
+    }
+}
+
+// scale the wrapping floats into tile units and work out their integer wrap widths, as `tiles_wrap` expects
+fn scale(floats: &[f64], wraps: &[Option<WrapRange>]) -> (Vec<f64>, Vec<Option<isize>>) {
+    assert_eq!(floats.len(), wraps.len(), "expected one wrap range (or None) per float");
+    floats
+        .iter()
+        .zip(wraps.iter())
+        .map(|(&x, wrap)| match wrap {
+            Some(w) => (x * w.tiles as f64 / w.range, Some(w.tiles as isize)),
+            None => (x, None),
+        })
+        .unzip()
+}
+
+/// The free function version of [`IHT::tiles_wrap_range`]
+pub fn tiles_wrap_range(size: usize, num_tilings: usize, floats: &[f64], wraps: &[Option<WrapRange>], ints: Option<&[isize]>) -> Vec<usize> {
+    let (floats, wrap_widths) = scale(floats, wraps);
+    tiles_wrap(size, num_tilings, &floats, &wrap_widths, ints)
+}
+
+impl IHT {
+    /// The same as `tiles_wrap`, but with the wrap-around given in the floats' natural units, so there is no need to
+    /// scale the floats so that the wrap width is an integer: each wrapping float is scaled internally so that its
+    /// range spans the requested number of tiles. Floats without a wrap range are used as they are, i.e. already in
+    /// tile units.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{IHT, WrapRange};
+    /// # use std::f64::consts::PI;
+    /// let mut iht = IHT::new(1024);
+    /// // an angle in radians with 10 tiles per revolution, and a float in tile units that doesn't wrap
+    /// let wraps = [Some(WrapRange::new(2.0 * PI, 10)), None];
+    /// let indices = iht.tiles_wrap_range(8, &[0.25 * PI, 3.0], &wraps, None);
+    /// assert_eq!(iht.tiles_wrap_range(8, &[2.25 * PI, 3.0], &wraps, None), indices);
+    ///
+    /// // the same as scaling by hand
+    /// assert_eq!(iht.tiles_wrap(8, &[0.25 * PI * 10.0 / (2.0 * PI), 3.0], &[Some(10), None], None), indices);
+    /// ```
+    pub fn tiles_wrap_range(&mut self, num_tilings: usize, floats: &[f64], wraps: &[Option<WrapRange>], ints: Option<&[isize]>) -> Vec<usize> {
+        let (floats, wrap_widths) = scale(floats, wraps);
+        self.tiles_wrap(num_tilings, &floats, &wrap_widths, ints)
+    }
+
+    /// The read-only version of `tiles_wrap_range`
+    pub fn tiles_wrap_range_read_only(&self, num_tilings: usize, floats: &[f64], wraps: &[Option<WrapRange>], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let (floats, wrap_widths) = scale(floats, wraps);
+        self.tiles_wrap_read_only(num_tilings, &floats, &wrap_widths, ints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_into_tile_units() {
+        let wraps = [None, Some(WrapRange::new(24.0, 6))];
+        let indices = tiles_wrap_range(4096, 8, &[1.5, 13.0], &wraps, Some(&[1]));
+        assert_eq!(indices, tiles_wrap(4096, 8, &[1.5, 13.0 / 4.0], &[None, Some(6)], Some(&[1])));
+        assert_eq!(tiles_wrap_range(4096, 8, &[1.5, 37.0], &wraps, Some(&[1])), indices);
+
+        let mut iht = IHT::new(64);
+        let indices = iht.tiles_wrap_range(4, &[0.0, 5.0], &wraps, None);
+        assert_eq!(iht.tiles_wrap_range_read_only(4, &[0.0, 29.0], &wraps, None), indices.into_iter().map(Some).collect::<Vec<_>>());
+    }
+}
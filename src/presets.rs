@@ -0,0 +1,119 @@
+//! Ready-made coder configurations for classic reinforcement learning benchmarks, so that new experiments can start
+//! from tilings that are known to work instead of guessing at scales.
+//!
+//! Each preset returns a [`TileCoderBuilder`], so any part of it can still be adjusted before building. The
+//! dimensions are listed in the order the environment's state is usually reported in.
+//!
+//! # Example
+//!
+//! ```
+//! # use tilecoding::presets;
+//! let mut coder = presets::mountain_car().size(8192).build();
+//! // position, velocity
+//! assert_eq!(coder.tiles(&[-0.5, 0.0], None).len(), 8);
+//! ```
+
+use std::f64::consts::PI;
+
+use crate::{Dim, TileCoderBuilder};
+
+/// Mountain car (Sutton & Barto, example 10.1): position in `[-1.2, 0.6]` and velocity in `[-0.07, 0.07]`, with 8
+/// tilings of 8×8 tiles
+pub fn mountain_car() -> TileCoderBuilder {
+    TileCoderBuilder::new()
+        .num_tilings(8)
+        .size(4096)
+        .dim(Dim::bins(-1.2, 0.6, 8).clamped())
+        .dim(Dim::bins(-0.07, 0.07, 8).clamped())
+}
+
+/// Cart-pole: cart position in `[-2.4, 2.4]`, cart velocity in `[-3, 3]`, pole angle in `[-0.21, 0.21]` radians (the
+/// ±12° failure threshold), and pole angular velocity in `[-3.5, 3.5]`, with 16 tilings of 6 tiles per dimension
+pub fn cart_pole() -> TileCoderBuilder {
+    TileCoderBuilder::new()
+        .num_tilings(16)
+        .size(65536)
+        .dim(Dim::bins(-2.4, 2.4, 6).clamped())
+        .dim(Dim::bins(-3.0, 3.0, 6).clamped())
+        .dim(Dim::bins(-0.21, 0.21, 6).clamped())
+        .dim(Dim::bins(-3.5, 3.5, 6).clamped())
+}
+
+/// Acrobot (Sutton, 1996): the two joint angles in `[-π, π)`, which wrap, and their angular velocities in
+/// `[-4π, 4π]` and `[-9π, 9π]`, with 16 tilings of 6 tiles per dimension
+pub fn acrobot() -> TileCoderBuilder {
+    TileCoderBuilder::new()
+        .num_tilings(16)
+        .size(65536)
+        .dim(Dim::wrapping(-PI, PI, 6))
+        .dim(Dim::wrapping(-PI, PI, 6))
+        .dim(Dim::bins(-4.0 * PI, 4.0 * PI, 6).clamped())
+        .dim(Dim::bins(-9.0 * PI, 9.0 * PI, 6).clamped())
+}
+
+/// Pendulum swing-up: the angle in `[-π, π)`, which wraps, and the angular velocity in `[-8, 8]`, with 8 tilings of
+/// 10×10 tiles
+pub fn pendulum() -> TileCoderBuilder {
+    TileCoderBuilder::new()
+        .num_tilings(8)
+        .size(4096)
+        .dim(Dim::wrapping(-PI, PI, 10))
+        .dim(Dim::bins(-8.0, 8.0, 10).clamped())
+}
+
+/// Puddle world (Sutton, 1996): the agent's position in the unit square, with 8 tilings of 10×10 tiles
+pub fn puddle_world() -> TileCoderBuilder {
+    TileCoderBuilder::new()
+        .num_tilings(8)
+        .size(4096)
+        .dim(Dim::bins(0.0, 1.0, 10).clamped())
+        .dim(Dim::bins(0.0, 1.0, 10).clamped())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // sweep a preset over a grid spanning its ranges, checking that everything fits in the IHT without collisions
+    fn check_fits(preset: TileCoderBuilder, steps: usize) {
+        let mut coder = preset.build();
+        let ranges: Vec<(f64, f64)> = coder.dims().iter().map(|d| d.range().unwrap()).collect();
+        let points = steps.pow(ranges.len() as u32);
+        for p in 0..points {
+            let mut rest = p;
+            let point: Vec<f64> = ranges
+                .iter()
+                .map(|&(min, max)| {
+                    let step = rest % steps;
+                    rest /= steps;
+                    min + (max - min) * step as f64 / (steps - 1) as f64
+                })
+                .collect();
+            assert_eq!(coder.tiles(&point, None).len(), coder.num_tilings());
+        }
+        assert!(coder.iht().count() < coder.size());
+    }
+
+    #[test]
+    fn presets_fit_their_iht() {
+        check_fits(mountain_car(), 40);
+        check_fits(cart_pole(), 12);
+        check_fits(acrobot(), 12);
+        check_fits(pendulum(), 40);
+        check_fits(puddle_world(), 40);
+    }
+
+    #[test]
+    fn presets_follow_the_guidance() {
+        for preset in [mountain_car(), cart_pole(), acrobot(), pendulum(), puddle_world()] {
+            assert_eq!(preset.diagnose(), vec![]);
+            assert!(preset.build_checked().is_ok());
+        }
+    }
+
+    #[test]
+    fn pendulum_angle_wraps() {
+        let mut coder = pendulum().build();
+        assert_eq!(coder.tiles(&[PI - 0.01, 1.0], None), coder.tiles(&[3.0 * PI - 0.01, 1.0], None));
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{Dim, IHT};
+
+/// A tile coder which starts out coarse and progressively enables finer tilings as training goes on, automating the
+/// coarse-to-fine training trick.
+///
+/// Each stage of the schedule tiles the same dimensions at a multiple of the base resolution, starting at a given
+/// episode. Enabled stages stay enabled, so once a finer stage kicks in the point is encoded by the tiles of every
+/// stage so far. Since freshly-enabled tiles start with zero weight, the value learned by the coarse tiles is
+/// carried over unchanged and the fine tiles only have to learn the residual detail.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{Dim, ProgressiveCoder};
+/// let mut coder = ProgressiveCoder::new(8, 4096, vec![Dim::bins(0.0, 1.0, 4)])
+///     .refine_at(100, 2)
+///     .refine_at(500, 4);
+///
+/// assert_eq!(coder.tiles(&[0.3], None).len(), 8);
+/// for _ in 0..100 {
+///     coder.end_episode();
+/// }
+/// // the 2x stage is now enabled alongside the base stage
+/// assert_eq!(coder.resolutions(), vec![1, 2]);
+/// assert_eq!(coder.tiles(&[0.3], None).len(), 16);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgressiveCoder {
+    num_tilings: usize,
+    // (episode the stage is enabled at, resolution multiplier, dimensions at that resolution)
+    stages: Vec<(usize, usize, Vec<Dim>)>,
+    episode: usize,
+    iht: IHT,
+}
+
+impl ProgressiveCoder {
+    /// Create a coder whose first stage tiles `dims` at their base resolution from the first episode
+    pub fn new(num_tilings: usize, size: usize, dims: Vec<Dim>) -> ProgressiveCoder {
+        ProgressiveCoder {
+            num_tilings,
+            stages: vec![(0, 1, dims)],
+            episode: 0,
+            iht: IHT::new(size),
+        }
+    }
+
+    /// Add a stage which tiles the dimensions at `factor` times the base resolution, starting at `episode`
+    pub fn refine_at(mut self, episode: usize, factor: usize) -> ProgressiveCoder {
+        assert!(factor > 0, "resolution factors must be at least 1");
+        let dims = self.stages[0].2.iter().map(|d| d.refined(factor)).collect();
+        self.stages.push((episode, factor, dims));
+        self.stages.sort_by_key(|&(episode, factor, _)| (episode, factor));
+        self
+    }
+
+    /// The number of stages enabled at the current episode
+    fn enabled(&self) -> usize {
+        self.stages.iter().filter(|&&(start, _, _)| start <= self.episode).count()
+    }
+
+    /// The resolution multipliers of the enabled stages
+    pub fn resolutions(&self) -> Vec<usize> {
+        self.stages[..self.enabled()].iter().map(|&(_, factor, _)| factor).collect()
+    }
+
+    /// The number of episodes that have been completed
+    pub fn episode(&self) -> usize {
+        self.episode
+    }
+
+    /// The underlying IHT, shared by all stages
+    pub fn iht(&self) -> &IHT {
+        &self.iht
+    }
+
+    /// Mark the end of an episode, enabling any stages which start at the next one. Returns `true` if the
+    /// resolution changed.
+    pub fn end_episode(&mut self) -> bool {
+        let before = self.enabled();
+        self.episode += 1;
+        self.enabled() != before
+    }
+
+    /// Encode a raw point as `num_tilings` indices for each enabled stage, coarsest first
+    pub fn tiles(&mut self, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        let enabled = self.enabled();
+        let mut tiles = Vec::with_capacity(self.num_tilings * enabled);
+        for (stage, (_, _, dims)) in self.stages[..enabled].iter().enumerate() {
+            assert_eq!(floats.len(), dims.len(), "expected one float per dimension");
+            let scaled: Vec<f64> = floats.iter().zip(dims.iter()).map(|(&x, d)| d.scale(x)).collect();
+            let wrap_widths: Vec<Option<isize>> = dims.iter().map(|d| d.wrap_width()).collect();
+
+            // salt each stage so that their tiles never coincide
+            let mut stage_ints = ints.unwrap_or(&[]).to_vec();
+            stage_ints.push(stage as isize);
+
+            if wrap_widths.iter().any(Option::is_some) {
+                tiles.extend(self.iht.tiles_wrap(self.num_tilings, &scaled, &wrap_widths, Some(&stage_ints)));
+            } else {
+                tiles.extend(self.iht.tiles(self.num_tilings, &scaled, Some(&stage_ints)));
+            }
+        }
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stages_enable_at_milestones() {
+        let mut coder = ProgressiveCoder::new(4, 1024, vec![Dim::width(1.0)]).refine_at(2, 4).refine_at(1, 2);
+        assert_eq!(coder.resolutions(), vec![1]);
+        assert!(coder.end_episode());
+        assert_eq!(coder.resolutions(), vec![1, 2]);
+        assert!(coder.end_episode());
+        assert_eq!(coder.resolutions(), vec![1, 2, 4]);
+        assert!(!coder.end_episode());
+        assert_eq!(coder.episode(), 3);
+    }
+
+    #[test]
+    fn coarse_tiles_are_kept() {
+        let mut coder = ProgressiveCoder::new(4, 1024, vec![Dim::wrapping(0.0, 1.0, 4)]).refine_at(1, 2);
+        let coarse = coder.tiles(&[0.3], None);
+        coder.end_episode();
+        let both = coder.tiles(&[0.3], None);
+        assert_eq!(both[..4], coarse[..]);
+        assert!(both[4..].iter().all(|i| !coarse.contains(i)));
+        assert_eq!(coder.tiles(&[1.3], None), both);
+    }
+}
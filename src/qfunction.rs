@@ -0,0 +1,129 @@
+/// Linear action values over state tiles, with a separate weight for every (tile index, action) pair. The state is
+/// tiled once, without the action, and the same tiles are used to look up the value of every action.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{IHT, QFunction};
+/// let mut iht = IHT::new(1024);
+/// let mut q = QFunction::new(iht.size(), 3);
+///
+/// let state = iht.tiles(8, &[0.5, 0.2], None);
+/// let next = iht.tiles(8, &[5.6, 0.2], None);
+/// q.update(&next, 2, 10.0, 1.0);
+///
+/// // learn that action 0 leads to the state where action 2 is worth 10
+/// for _ in 0..50 {
+///     q.q_learning(&state, 0, 0.0, Some(&next), 0.9, 0.5);
+/// }
+/// assert_eq!(q.argmax(&state), 0);
+/// assert!((q.max_q(&state) - 9.0).abs() < 0.1);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct QFunction {
+    num_actions: usize,
+    // the weights of every action for a tile are stored together
+    weights: Vec<f64>,
+}
+
+impl QFunction {
+    /// Create action values for tile indices `< size` and `num_actions` actions, with every weight zero
+    pub fn new(size: usize, num_actions: usize) -> QFunction {
+        assert!(num_actions > 0, "there must be at least one action");
+        QFunction {
+            num_actions,
+            weights: vec![0.0; size * num_actions],
+        }
+    }
+
+    /// The number of tile indices there are weights for
+    pub fn size(&self) -> usize {
+        self.weights.len() / self.num_actions
+    }
+
+    /// The number of actions
+    pub fn num_actions(&self) -> usize {
+        self.num_actions
+    }
+
+    /// The weight of `action` for a tile index
+    pub fn weight(&self, tile: usize, action: usize) -> f64 {
+        assert!(action < self.num_actions, "action out of range");
+        self.weights[tile * self.num_actions + action]
+    }
+
+    /// The estimated value of taking `action` in the state with the given tiles
+    pub fn q(&self, tiles: &[usize], action: usize) -> f64 {
+        tiles.iter().map(|&tile| self.weight(tile, action)).sum()
+    }
+
+    /// The estimated value of every action in the state with the given tiles
+    pub fn q_all(&self, tiles: &[usize]) -> Vec<f64> {
+        let mut values = vec![0.0; self.num_actions];
+        for &tile in tiles {
+            let w = &self.weights[tile * self.num_actions..(tile + 1) * self.num_actions];
+            for (v, w) in values.iter_mut().zip(w) {
+                *v += w;
+            }
+        }
+        values
+    }
+
+    /// The action with the highest estimated value (the lowest-numbered one if there are ties)
+    pub fn argmax(&self, tiles: &[usize]) -> usize {
+        let values = self.q_all(tiles);
+        (0..self.num_actions).fold(0, |best, a| if values[a] > values[best] { a } else { best })
+    }
+
+    /// The highest estimated action value
+    pub fn max_q(&self, tiles: &[usize]) -> f64 {
+        self.q_all(tiles).into_iter().fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    /// Take a semi-gradient step moving the value of `action` towards `target`, returning the error before the
+    /// update. `alpha` is divided by the number of active tiles, as for [`LinearVFA::update`](crate::LinearVFA::update).
+    pub fn update(&mut self, tiles: &[usize], action: usize, target: f64, alpha: f64) -> f64 {
+        let error = target - self.q(tiles, action);
+        if !tiles.is_empty() {
+            let step = alpha * error / tiles.len() as f64;
+            for &tile in tiles {
+                self.weights[tile * self.num_actions + action] += step;
+            }
+        }
+        error
+    }
+
+    /// A Q-learning update for taking `action` in the state with `tiles`, getting `reward`, and ending up in the
+    /// state with `next_tiles` (or `None` if the episode ended). Returns the TD error.
+    pub fn q_learning(&mut self, tiles: &[usize], action: usize, reward: f64, next_tiles: Option<&[usize]>, gamma: f64, alpha: f64) -> f64 {
+        let target = reward + next_tiles.map_or(0.0, |next| gamma * self.max_q(next));
+        self.update(tiles, action, target, alpha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_are_independent() {
+        let mut q = QFunction::new(8, 2);
+        assert_eq!(q.update(&[1, 2], 1, 4.0, 1.0), 4.0);
+        assert_eq!(q.q(&[1, 2], 0), 0.0);
+        assert_eq!(q.q(&[1, 2], 1), 4.0);
+        assert_eq!(q.q_all(&[2, 3]), vec![0.0, 2.0]);
+        assert_eq!(q.weight(1, 1), 2.0);
+        assert_eq!(q.size(), 8);
+    }
+
+    #[test]
+    fn terminal_updates_ignore_the_future() {
+        let mut q = QFunction::new(8, 2);
+        q.update(&[5], 0, 100.0, 1.0);
+        assert_eq!(q.q_learning(&[1], 1, 3.0, None, 0.9, 1.0), 3.0);
+        assert_eq!(q.q(&[1], 1), 3.0);
+        assert_eq!(q.q_learning(&[2], 1, 3.0, Some(&[5]), 0.5, 1.0), 53.0);
+        // ties go to the first action
+        assert_eq!(q.argmax(&[7]), 0);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::IHT;
+
+// the key marking a coordinate vector as an RBF center rather than a tile; tile coordinates always start with their
+// (non-negative) tiling number
+const RBF_KEY: isize = -1;
+
+// the centers (on the integer lattice) near enough to a point to be encoded: for every dimension, the nearest
+// integer and the ones either side of it
+fn centers(floats: &[f64]) -> Vec<Vec<isize>> {
+    let mut centers = vec![Vec::with_capacity(floats.len())];
+    for &x in floats {
+        let nearest = x.round() as isize;
+        centers = centers
+            .into_iter()
+            .flat_map(|c| {
+                (nearest - 1..=nearest + 1).map(move |i| {
+                    let mut c = c.clone();
+                    c.push(i);
+                    c
+                })
+            })
+            .collect();
+    }
+    centers
+}
+
+fn activation(floats: &[f64], center: &[isize], sigma: f64) -> f64 {
+    let distance: f64 = floats.iter().zip(center.iter()).map(|(&x, &c)| (x - c as f64) * (x - c as f64)).sum();
+    (-distance / (2.0 * sigma * sigma)).exp()
+}
+
+fn key(center: Vec<isize>, ints: &Option<&[isize]>) -> Vec<isize> {
+    let mut key = Vec::with_capacity(1 + center.len() + ints.map_or(0, |i| i.len()));
+    key.push(RBF_KEY);
+    key.extend(center);
+    if let Some(ints) = ints {
+        key.extend(*ints);
+    }
+    key
+}
+
+impl IHT {
+    /// Radial-basis-function coarse coding: encode a point as `(index, activation)` pairs with graded activations
+    /// rather than binary tiles. Like with the tiles functions, `floats` should be scaled so that the spacing of the
+    /// features is 1; the features are centred on the integer lattice and the activation of each is
+    /// `exp(-d² / 2σ²)`, where `d` is the distance to its center.
+    ///
+    /// For each dimension the nearest center and the ones either side of it are encoded, so that a point has `3^n`
+    /// features for `n` floats. The indices are allocated from the IHT, so RBF features and tiles can share a table
+    /// (and a weight vector) without colliding.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// let features = iht.rbf(&[2.2], 0.5, None);
+    /// assert_eq!(features.iter().map(|&(i, _)| i).collect::<Vec<_>>(), vec![0, 1, 2]);
+    /// // the nearest center is the most active
+    /// assert!(features[1].1 > features[0].1 && features[1].1 > features[2].1);
+    ///
+    /// // tiles from the same IHT get their own indices
+    /// assert_eq!(iht.tiles(2, &[2.2], None), vec![3, 4]);
+    /// ```
+    pub fn rbf(&mut self, floats: &[f64], sigma: f64, ints: Option<&[isize]>) -> Vec<(usize, f64)> {
+        assert!(sigma > 0.0, "sigma must be positive");
+        centers(floats)
+            .into_iter()
+            .map(|center| {
+                let activation = activation(floats, &center, sigma);
+                (self.get_index(key(center, &ints)), activation)
+            })
+            .collect()
+    }
+
+    /// The read-only version of `rbf`; features whose center hasn't been seen are left out
+    pub fn rbf_read_only(&self, floats: &[f64], sigma: f64, ints: Option<&[isize]>) -> Vec<(usize, f64)> {
+        assert!(sigma > 0.0, "sigma must be positive");
+        centers(floats)
+            .into_iter()
+            .filter_map(|center| {
+                let activation = activation(floats, &center, sigma);
+                self.get_index_read_only(key(center, &ints)).map(|index| (index, activation))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn activations_are_gaussian() {
+        let mut iht = IHT::new(1024);
+        let features = iht.rbf(&[0.0, 0.5], 1.0, Some(&[3]));
+        assert_eq!(features.len(), 9);
+        // the center at (0, 0) is half a unit away, as is the one at (0, 1)
+        let expected = (-0.125f64).exp();
+        assert!(features.iter().filter(|&&(_, a)| (a - expected).abs() < 1e-12).count() == 2);
+        assert_eq!(iht.rbf_read_only(&[0.0, 0.5], 1.0, Some(&[3])), features);
+    }
+
+    #[test]
+    fn nearby_points_share_centers() {
+        let mut iht = IHT::new(1024);
+        let a: Vec<usize> = iht.rbf(&[4.4], 0.3, None).into_iter().map(|(i, _)| i).collect();
+        let b: Vec<usize> = iht.rbf(&[4.6], 0.3, None).into_iter().map(|(i, _)| i).collect();
+        assert_eq!(a[1..], b[..2]);
+        assert_eq!(iht.rbf_read_only(&[9.0], 0.3, None), vec![]);
+    }
+}
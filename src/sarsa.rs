@@ -0,0 +1,225 @@
+use crate::offsets::splitmix64;
+use crate::{Curiosity, LinearVFA, TileCoder};
+
+/// An episodic Sarsa(λ) control agent over the tiles of a [`TileCoder`], with replacing traces and ε-greedy action
+/// selection: the canonical use of tile coding, as in Sutton & Barto's mountain car example (section 12.7).
+///
+/// Actions are numbered `0..num_actions` and passed to the coder as an int, so every action has its own tiles in the
+/// same IHT. Each step of an episode is one call to `select_action` with the current state, then one call to
+/// `observe` with the reward that followed.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{Dim, SarsaLambda, TileCoder};
+/// // a corridor from 0 to 10: step left or right, and get a reward of -1 until reaching the end
+/// let coder = TileCoder::new(4, 1024, vec![Dim::bins(0.0, 10.0, 10)]);
+/// let mut agent = SarsaLambda::new(coder, 2, 0.5, 1.0, 0.9);
+///
+/// let mut steps = 0;
+/// for _ in 0..50 {
+///     let mut position = 0.0;
+///     steps = 0;
+///     loop {
+///         let action = agent.select_action(&[position]);
+///         position = if action == 1 { position + 1.0 } else { f64::max(position - 1.0, 0.0) };
+///         steps += 1;
+///         let done = position >= 10.0;
+///         agent.observe(-1.0, done);
+///         if done {
+///             break;
+///         }
+///     }
+/// }
+/// assert_eq!(steps, 10);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SarsaLambda {
+    coder: TileCoder,
+    vfa: LinearVFA,
+    num_actions: usize,
+    alpha: f64,
+    gamma: f64,
+    lambda: f64,
+    epsilon: f64,
+    rng: u64,
+    // the tiles of the most recent state-action pair, and the reward which followed it (if it has been observed)
+    current: Option<Vec<usize>>,
+    reward: Option<f64>,
+    // a forward model whose scaled prediction error is added to the rewards
+    curiosity: Option<Curiosity>,
+}
+
+impl SarsaLambda {
+    /// Create an agent choosing between `num_actions` actions, with the states encoded by `coder`. `alpha` is the step
+    /// size, which is divided by the number of tilings; `gamma` is the discount and `lambda` the trace decay. The
+    /// agent is greedy unless `with_epsilon` is used.
+    pub fn new(coder: TileCoder, num_actions: usize, alpha: f64, gamma: f64, lambda: f64) -> SarsaLambda {
+        assert!(num_actions > 0, "there must be at least one action");
+        let vfa = LinearVFA::new(coder.size());
+        SarsaLambda {
+            coder,
+            vfa,
+            num_actions,
+            alpha,
+            gamma,
+            lambda,
+            epsilon: 0.0,
+            rng: 0,
+            current: None,
+            reward: None,
+            curiosity: None,
+        }
+    }
+
+    /// Explore by choosing an action uniformly at random with probability `epsilon`
+    pub fn with_epsilon(mut self, epsilon: f64) -> SarsaLambda {
+        self.epsilon = epsilon;
+        self
+    }
+
+    /// Seed the random number generator used for exploration and breaking ties
+    pub fn with_seed(mut self, seed: u64) -> SarsaLambda {
+        self.rng = seed;
+        self
+    }
+
+    /// Add an intrinsic reward to every observed reward: `model`'s reward for predicting the next state from the
+    /// tiles of the state-action pair the reward followed (see [`Curiosity::reward`], which scales it with
+    /// [`Curiosity::with_scale`]), learning the model as it goes. The next state is only known once it is passed to
+    /// `select_action`, so that is when the bonus is added; the final step of an episode has no next state, and no
+    /// bonus.
+    pub fn with_curiosity(mut self, model: Curiosity) -> SarsaLambda {
+        assert!(model.size() >= self.coder.size(), "the curiosity model must have weights for every tile");
+        assert_eq!(model.outputs(), self.coder.dims().len(), "the curiosity model must predict every dimension of the state");
+        self.curiosity = Some(model);
+        self
+    }
+
+    /// The forward model providing the intrinsic reward, if there is one
+    pub fn curiosity(&self) -> Option<&Curiosity> {
+        self.curiosity.as_ref()
+    }
+
+    /// The tile coder states are encoded with
+    pub fn coder(&self) -> &TileCoder {
+        &self.coder
+    }
+
+    /// The approximator holding the learned action values and the traces
+    pub fn vfa(&self) -> &LinearVFA {
+        &self.vfa
+    }
+
+    // a uniform random number in [0, 1)
+    fn random(&mut self) -> f64 {
+        self.rng = self.rng.wrapping_add(1);
+        (splitmix64(self.rng) >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// The estimated value of taking `action` in `state`; tiles which have never been visited contribute nothing
+    pub fn q(&self, state: &[f64], action: usize) -> f64 {
+        let tiles: Vec<usize> = self.coder.tiles_read_only(state, Some(&[action as isize])).into_iter().flatten().collect();
+        self.vfa.predict(&tiles)
+    }
+
+    /// The action with the highest estimated value in `state` (the lowest-numbered one if there are ties)
+    pub fn greedy(&self, state: &[f64]) -> usize {
+        let values: Vec<f64> = (0..self.num_actions).map(|a| self.q(state, a)).collect();
+        (0..self.num_actions).fold(0, |best, a| if values[a] > values[best] { a } else { best })
+    }
+
+    /// Choose the action to take in `state`, ε-greedily. If the reward for the previous action has been observed,
+    /// this also learns from that step.
+    pub fn select_action(&mut self, state: &[f64]) -> usize {
+        let action = if self.random() < self.epsilon {
+            (self.random() * self.num_actions as f64) as usize
+        } else {
+            // break ties at random, so that untrained values don't always pick the same action
+            let values: Vec<f64> = (0..self.num_actions).map(|a| self.q(state, a)).collect();
+            let best = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            let ties: Vec<usize> = (0..self.num_actions).filter(|&a| values[a] == best).collect();
+            ties[((self.random() * ties.len() as f64) as usize).min(ties.len() - 1)]
+        };
+        let tiles = self.coder.tiles(state, Some(&[action as isize]));
+        let alpha = self.alpha / self.coder.num_tilings() as f64;
+
+        if let (Some(previous), Some(mut reward)) = (self.current.take(), self.reward.take()) {
+            if let Some(model) = &mut self.curiosity {
+                reward += model.reward(&previous, state);
+            }
+            let delta = reward + self.gamma * self.vfa.predict(&tiles) - self.vfa.predict(&previous);
+            self.vfa.update_traced(delta, alpha);
+            self.vfa.decay(self.gamma * self.lambda);
+        }
+        self.vfa.set_active(&tiles);
+        self.current = Some(tiles);
+        action
+    }
+
+    /// Observe the reward for the action chosen by the last `select_action`. When `done` is set the episode is over,
+    /// so the agent learns from the final step right away and resets its traces for the next episode.
+    pub fn observe(&mut self, reward: f64, done: bool) {
+        if done {
+            if let Some(previous) = self.current.take() {
+                let delta = reward - self.vfa.predict(&previous);
+                self.vfa.update_traced(delta, self.alpha / self.coder.num_tilings() as f64);
+            }
+            self.vfa.clear_traces();
+            self.reward = None;
+        } else {
+            self.reward = Some(reward);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dim;
+
+    #[test]
+    fn learns_the_terminal_reward() {
+        // a single-step episode where action 1 pays more than action 0
+        let coder = TileCoder::new(1, 64, vec![Dim::width(1.0)]);
+        let mut agent = SarsaLambda::new(coder, 2, 0.5, 1.0, 0.0).with_epsilon(0.5).with_seed(3);
+        for _ in 0..200 {
+            let action = agent.select_action(&[0.0]);
+            agent.observe(if action == 1 { 2.0 } else { 1.0 }, true);
+        }
+        assert!((agent.q(&[0.0], 0) - 1.0).abs() < 1e-3);
+        assert!((agent.q(&[0.0], 1) - 2.0).abs() < 1e-3);
+        assert_eq!(agent.greedy(&[0.0]), 1);
+        assert_eq!(agent.vfa().active_traces(), 0);
+    }
+
+    #[test]
+    fn exploration_is_reproducible() {
+        let run = |seed| {
+            let coder = TileCoder::new(1, 64, vec![Dim::width(1.0)]);
+            let mut agent = SarsaLambda::new(coder, 4, 0.1, 1.0, 0.5).with_epsilon(1.0).with_seed(seed);
+            (0..20).map(|i| agent.select_action(&[i as f64])).collect::<Vec<_>>()
+        };
+        assert_eq!(run(1), run(1));
+        assert_ne!(run(1), run(2));
+        assert!(run(1).iter().all(|&a| a < 4));
+    }
+
+    #[test]
+    fn curiosity_adds_to_the_td_target() {
+        let step = |curious: bool| {
+            let coder = TileCoder::new(1, 64, vec![Dim::width(1.0)]);
+            let mut agent = SarsaLambda::new(coder, 1, 1.0, 0.0, 0.0);
+            if curious {
+                agent = agent.with_curiosity(Curiosity::new(64, 1, 0.5).with_scale(2.0));
+            }
+            agent.select_action(&[0.0]);
+            agent.observe(1.0, false);
+            agent.select_action(&[5.0]);
+            agent.q(&[0.0], 0)
+        };
+        // the untrained model predicts 0 for a next state of 5, so the bonus is 2 * 5²
+        assert_eq!(step(false), 1.0);
+        assert_eq!(step(true), 51.0);
+    }
+}
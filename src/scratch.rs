@@ -0,0 +1,100 @@
+use crate::{calculate_coords_into, calculate_coords_wrap_into, IHT};
+
+/// Reusable workspace for encoding points in a hot loop. It owns the buffers that the tiling functions otherwise
+/// allocate on every call (the quantized floats, the coordinates, and the result), so that once they have grown to
+/// fit, encoding a point with [`IHT::tiles_scratch`] doesn't allocate unless it adds a new tile to the IHT.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{TilesScratch, IHT};
+/// let mut iht = IHT::new(1024);
+/// let mut scratch = TilesScratch::new();
+/// for step in 0..100 {
+///     let x = step as f64 * 0.1;
+///     let indices = iht.tiles_scratch(&mut scratch, 8, &[x, 7.21], None);
+///     assert_eq!(indices.len(), 8);
+/// }
+/// assert_eq!(iht.tiles_scratch(&mut scratch, 8, &[3.6, 7.21], None), &iht.tiles(8, &[3.6, 7.21], None)[..]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TilesScratch {
+    q_floats: Vec<isize>,
+    coords: Vec<isize>,
+    tiles: Vec<usize>,
+    read_only: Vec<Option<usize>>,
+}
+
+impl TilesScratch {
+    /// Create an empty workspace; its buffers grow to fit the first few points encoded with it
+    pub fn new() -> TilesScratch {
+        TilesScratch::default()
+    }
+
+    fn quantize(&mut self, floats: &[f64], num_tilings: usize) {
+        self.q_floats.clear();
+        self.q_floats.extend(floats.iter().map(|&x| (x * num_tilings as f64).floor() as isize));
+    }
+}
+
+impl IHT {
+    /// The same as `tiles`, using the buffers of `scratch` instead of allocating, see [`TilesScratch`]. The indices
+    /// are valid until the scratch space is next used.
+    pub fn tiles_scratch<'s>(&mut self, scratch: &'s mut TilesScratch, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> &'s [usize] {
+        scratch.quantize(floats, num_tilings);
+        scratch.tiles.clear();
+        for tiling in 0..num_tilings {
+            calculate_coords_into(&mut scratch.coords, tiling, num_tilings, &scratch.q_floats, &ints);
+            scratch.tiles.push(self.get_index_ref(&scratch.coords));
+        }
+        &scratch.tiles
+    }
+
+    /// The read-only version of `tiles_scratch`
+    pub fn tiles_read_only_scratch<'s>(&self, scratch: &'s mut TilesScratch, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> &'s [Option<usize>] {
+        scratch.quantize(floats, num_tilings);
+        scratch.read_only.clear();
+        for tiling in 0..num_tilings {
+            calculate_coords_into(&mut scratch.coords, tiling, num_tilings, &scratch.q_floats, &ints);
+            scratch.read_only.push(self.get_index_read_only_ref(&scratch.coords));
+        }
+        &scratch.read_only
+    }
+
+    /// The wrap-around version of `tiles_scratch`
+    pub fn tiles_wrap_scratch<'s>(
+        &mut self,
+        scratch: &'s mut TilesScratch,
+        num_tilings: usize,
+        floats: &[f64],
+        wrap_widths: &[Option<isize>],
+        ints: Option<&[isize]>,
+    ) -> &'s [usize] {
+        scratch.quantize(floats, num_tilings);
+        scratch.tiles.clear();
+        for tiling in 0..num_tilings {
+            calculate_coords_wrap_into(&mut scratch.coords, tiling, num_tilings, &scratch.q_floats, wrap_widths, &ints);
+            scratch.tiles.push(self.get_index_ref(&scratch.coords));
+        }
+        &scratch.tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scratch_matches_allocating_versions() {
+        let mut iht = IHT::new(64);
+        let mut reference = IHT::new(64);
+        let mut scratch = TilesScratch::new();
+        for i in 0..20 {
+            let floats = [i as f64 * 0.6, -1.5];
+            assert_eq!(iht.tiles_wrap_scratch(&mut scratch, 8, &floats, &[Some(5), None], Some(&[1])), &reference.tiles_wrap(8, &floats, &[Some(5), None], Some(&[1]))[..]);
+            assert_eq!(iht.tiles_scratch(&mut scratch, 4, &floats, None), &reference.tiles(4, &floats, None)[..]);
+        }
+        assert_eq!(iht, reference);
+        assert_eq!(iht.tiles_read_only_scratch(&mut scratch, 4, &[0.0, -1.5], None), &iht.tiles_read_only(4, &[0.0, -1.5], None)[..]);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::IHT;
+
+/// An IHT for discrete-action agents which is sharded by action: every action has a sub-table of its own, and its
+/// indices are offset into a block of the index space, so action `a` gets the indices
+/// `a * shard_size..(a + 1) * shard_size`. A lookup for one action only touches that action's (smaller) map, and
+/// since the action picks the shard, it doesn't need to be stored in the coordinates at all.
+///
+/// [`ShardedIHT::shards_mut`] splits the table into one handle per action, so that per-action updates can run on
+/// separate threads without any locking.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::ShardedIHT;
+/// let mut iht = ShardedIHT::new(3, 1024);
+/// assert_eq!(iht.size(), 3 * 1024);
+///
+/// assert_eq!(iht.tiles(4, &[3.6, 7.21], 0, None), vec![0, 1, 2, 3]);
+/// assert_eq!(iht.tiles(4, &[3.6, 7.21], 2, None), vec![2048, 2049, 2050, 2051]);
+///
+/// // update every action's shard on its own thread
+/// std::thread::scope(|scope| {
+///     for mut shard in iht.shards_mut() {
+///         scope.spawn(move || shard.tiles(4, &[-1.0, 2.0], None));
+///     }
+/// });
+/// assert_eq!(iht.count(), 3 * 4 + 2 * 4);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShardedIHT {
+    shard_size: usize,
+    shards: Vec<IHT>,
+}
+
+/// A mutable handle to the shard of one action in a [`ShardedIHT`], which encodes points for that action only
+#[derive(Debug)]
+pub struct ActionShard<'a> {
+    action: usize,
+    offset: usize,
+    iht: &'a mut IHT,
+}
+
+impl ShardedIHT {
+    /// Create a table for actions `0..num_actions`, each with a shard of `shard_size` indices
+    pub fn new(num_actions: usize, shard_size: usize) -> ShardedIHT {
+        assert!(num_actions > 0, "there must be at least one action");
+        ShardedIHT {
+            shard_size,
+            shards: (0..num_actions).map(|_| IHT::new(shard_size)).collect(),
+        }
+    }
+
+    /// The number of actions
+    pub fn num_actions(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// The number of indices in each action's shard
+    pub fn shard_size(&self) -> usize {
+        self.shard_size
+    }
+
+    /// The size of the whole index space; no index will be `>= size`
+    pub fn size(&self) -> usize {
+        self.shard_size * self.shards.len()
+    }
+
+    /// The number of tiles in every shard together
+    pub fn count(&self) -> usize {
+        self.shards.iter().map(IHT::count).sum()
+    }
+
+    /// The sub-table of the given action, whose indices are relative to the start of the action's block
+    pub fn shard(&self, action: usize) -> &IHT {
+        assert!(action < self.shards.len(), "action out of range");
+        &self.shards[action]
+    }
+
+    /// A copy of every shard's entries with their indices in the whole index space, sorted by index, see
+    /// [`IHT::entries_snapshot`]. The coordinates don't include the action, which is `index / shard_size`.
+    pub fn entries_snapshot(&self) -> Vec<(Vec<isize>, usize)> {
+        let shard_size = self.shard_size;
+        self.shards
+            .iter()
+            .enumerate()
+            .flat_map(|(action, iht)| iht.entries_snapshot().into_iter().map(move |(coords, index)| (coords, index + action * shard_size)))
+            .collect()
+    }
+
+    /// A mutable handle to the shard of every action, in order, which can be sent to different threads
+    pub fn shards_mut(&mut self) -> Vec<ActionShard<'_>> {
+        let shard_size = self.shard_size;
+        self.shards
+            .iter_mut()
+            .enumerate()
+            .map(|(action, iht)| ActionShard { action, offset: action * shard_size, iht })
+            .collect()
+    }
+
+    /// Encode a point for the given action, see [`IHT::tiles`]
+    pub fn tiles(&mut self, num_tilings: usize, floats: &[f64], action: usize, ints: Option<&[isize]>) -> Vec<usize> {
+        assert!(action < self.shards.len(), "action out of range");
+        let offset = action * self.shard_size;
+        offset_tiles(offset, self.shards[action].tiles(num_tilings, floats, ints))
+    }
+
+    /// Encode a point with wrapping for the given action, see [`IHT::tiles_wrap`]
+    pub fn tiles_wrap(&mut self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], action: usize, ints: Option<&[isize]>) -> Vec<usize> {
+        assert!(action < self.shards.len(), "action out of range");
+        let offset = action * self.shard_size;
+        offset_tiles(offset, self.shards[action].tiles_wrap(num_tilings, floats, wrap_widths, ints))
+    }
+
+    /// The read-only version of `tiles`, see [`IHT::tiles_read_only`]
+    pub fn tiles_read_only(&self, num_tilings: usize, floats: &[f64], action: usize, ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let offset = action * self.shard_size;
+        self.shard(action).tiles_read_only(num_tilings, floats, ints).into_iter().map(|t| t.map(|t| t + offset)).collect()
+    }
+}
+
+impl<'a> ActionShard<'a> {
+    /// The action this shard encodes points for
+    pub fn action(&self) -> usize {
+        self.action
+    }
+
+    /// Encode a point for this shard's action, see [`ShardedIHT::tiles`]
+    pub fn tiles(&mut self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        offset_tiles(self.offset, self.iht.tiles(num_tilings, floats, ints))
+    }
+
+    /// Encode a point with wrapping for this shard's action, see [`ShardedIHT::tiles_wrap`]
+    pub fn tiles_wrap(&mut self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<usize> {
+        offset_tiles(self.offset, self.iht.tiles_wrap(num_tilings, floats, wrap_widths, ints))
+    }
+}
+
+fn offset_tiles(offset: usize, mut tiles: Vec<usize>) -> Vec<usize> {
+    for tile in tiles.iter_mut() {
+        *tile += offset;
+    }
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shards_keep_actions_apart() {
+        let mut iht = ShardedIHT::new(2, 16);
+        let mut plain = IHT::new(16);
+        for i in 0..10 {
+            let x = i as f64 * 0.6;
+            assert_eq!(iht.tiles(4, &[x], 1, Some(&[3])), plain.tiles(4, &[x], Some(&[3])).iter().map(|t| t + 16).collect::<Vec<_>>());
+        }
+        // the second action's shard is full, but the first is untouched
+        assert!(iht.shard(1).full());
+        assert_eq!(iht.shard(0).count(), 0);
+        assert!(iht.tiles_wrap(4, &[0.0], &[Some(2)], 0, None).iter().all(|&t| t < 16));
+        assert_eq!(iht.tiles_read_only(4, &[0.0], 0, Some(&[3])), vec![None; 4]);
+
+        let snapshot = iht.entries_snapshot();
+        assert_eq!(snapshot.len(), iht.count());
+        assert!(snapshot.iter().take(4).all(|&(_, index)| index < 16));
+        assert_eq!(snapshot[4..], plain.entries_snapshot().into_iter().map(|(coords, index)| (coords, index + 16)).collect::<Vec<_>>()[..]);
+    }
+}
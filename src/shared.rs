@@ -0,0 +1,168 @@
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::IHT;
+
+/// An IHT shared between threads, wrapping an `Arc<RwLock<IHT>>`. Cloning it gives another handle to the same table.
+///
+/// Every tiling function first tries the lookup under a read lock, so once the table has settled, threads encoding
+/// states it has already seen don't block one another. Only a call that needs to insert a tile (or to collide into
+/// a full table, which counts towards its overfull count) takes the write lock, and it then redoes the whole lookup,
+/// so the indices are always exactly those the plain IHT would hand out. The one difference is that the visit counts
+/// are only updated by calls which take the write lock.
+///
+/// A thread which panics while holding the lock can't leave the table half way through an insertion, so a poisoned
+/// lock is ignored rather than propagated.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::SharedIHT;
+/// let iht = SharedIHT::new(1024);
+/// let workers: Vec<_> = (0..4)
+///     .map(|i| {
+///         let iht = iht.clone();
+///         std::thread::spawn(move || iht.tiles(8, &[i as f64 * 0.1, 7.21], None))
+///     })
+///     .collect();
+/// for worker in workers {
+///     worker.join().unwrap();
+/// }
+///
+/// // the first point was encoded by one of the workers, whichever ran first
+/// assert!(iht.tiles_read_only(8, &[0.0, 7.21], None).iter().all(Option::is_some));
+/// ```
+#[derive(Clone, Debug)]
+pub struct SharedIHT {
+    inner: Arc<RwLock<IHT>>,
+}
+
+impl SharedIHT {
+    /// Create a shared IHT with the given size, see [`IHT::new`]
+    pub fn new(size: usize) -> SharedIHT {
+        SharedIHT::from(IHT::new(size))
+    }
+
+    /// Lock the table for reading, for anything not covered by the methods here
+    pub fn read(&self) -> RwLockReadGuard<'_, IHT> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Lock the table for writing, for anything not covered by the methods here
+    pub fn write(&self) -> RwLockWriteGuard<'_, IHT> {
+        self.inner.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// See [`IHT::count`]
+    pub fn count(&self) -> usize {
+        self.read().count()
+    }
+
+    /// See [`IHT::size`]
+    pub fn size(&self) -> usize {
+        self.read().size()
+    }
+
+    /// See [`IHT::full`]
+    pub fn full(&self) -> bool {
+        self.read().full()
+    }
+
+    /// A copy of every entry, taken under the read lock so that it is consistent even while other threads are
+    /// inserting tiles, see [`IHT::entries_snapshot`]
+    pub fn entries_snapshot(&self) -> Vec<(Vec<isize>, usize)> {
+        self.read().entries_snapshot()
+    }
+
+    /// Encode a point as tile indices, see [`IHT::tiles`]
+    pub fn tiles(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        // the read guard has to be released before the match, or taking the write lock would deadlock
+        let found = self.read().tiles_read_only(num_tilings, floats, ints).into_iter().collect::<Option<Vec<usize>>>();
+        match found {
+            Some(tiles) => tiles,
+            // another thread may have inserted some of the tiles in between the locks, which the write path handles
+            None => self.write().tiles(num_tilings, floats, ints),
+        }
+    }
+
+    /// Encode a point as tile indices with wrapping, see [`IHT::tiles_wrap`]
+    pub fn tiles_wrap(&self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<usize> {
+        let found = self.read().tiles_wrap_read_only(num_tilings, floats, wrap_widths, ints).into_iter().collect::<Option<Vec<usize>>>();
+        match found {
+            Some(tiles) => tiles,
+            None => self.write().tiles_wrap(num_tilings, floats, wrap_widths, ints),
+        }
+    }
+
+    /// The read-only version of `tiles`, which only ever takes the read lock, see [`IHT::tiles_read_only`]
+    pub fn tiles_read_only(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        self.read().tiles_read_only(num_tilings, floats, ints)
+    }
+
+    /// The read-only version of `tiles_wrap`, which only ever takes the read lock, see [`IHT::tiles_wrap_read_only`]
+    pub fn tiles_wrap_read_only(&self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        self.read().tiles_wrap_read_only(num_tilings, floats, wrap_widths, ints)
+    }
+}
+
+impl From<IHT> for SharedIHT {
+    fn from(iht: IHT) -> SharedIHT {
+        SharedIHT {
+            inner: Arc::new(RwLock::new(iht)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn threads_agree_with_a_single_table() {
+        let shared = SharedIHT::new(4096);
+        std::thread::scope(|scope| {
+            for t in 0..4 {
+                let shared = &shared;
+                scope.spawn(move || {
+                    for i in 0..200 {
+                        let x = ((i * 7 + t * 13) % 50) as f64 * 0.3;
+                        shared.tiles(8, &[x, 1.0], Some(&[t % 2]));
+                        shared.tiles_wrap(8, &[x], &[Some(5)], None);
+                    }
+                });
+            }
+        });
+
+        // every point encoded twice gets the same tiles, and no tile was ever inserted twice
+        let mut plain = IHT::new(4096);
+        for i in 0..50 {
+            let x = i as f64 * 0.3;
+            for t in 0..2 {
+                let tiles = shared.tiles(8, &[x, 1.0], Some(&[t]));
+                assert_eq!(shared.tiles_read_only(8, &[x, 1.0], Some(&[t])), tiles.iter().copied().map(Some).collect::<Vec<_>>());
+                plain.tiles(8, &[x, 1.0], Some(&[t]));
+            }
+            plain.tiles_wrap(8, &[x], &[Some(5)], None);
+        }
+        assert_eq!(shared.count(), plain.count());
+        assert!(!shared.full());
+    }
+
+    #[test]
+    fn snapshots_are_consistent_while_inserting() {
+        let shared = SharedIHT::new(4096);
+        std::thread::scope(|scope| {
+            let writer = shared.clone();
+            scope.spawn(move || {
+                for i in 0..200 {
+                    writer.tiles(8, &[i as f64 * 0.7], None);
+                }
+            });
+            for _ in 0..20 {
+                // every snapshot holds a whole prefix of the indices, never one part way through an insertion
+                let snapshot = shared.entries_snapshot();
+                assert!(snapshot.iter().enumerate().all(|(i, &(_, index))| i == index));
+            }
+        });
+        assert_eq!(shared.entries_snapshot(), shared.read().entries_snapshot());
+    }
+}
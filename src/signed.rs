@@ -0,0 +1,81 @@
+use crate::{base_hash, calculate_coords, calculate_coords_wrap, calculate_q_floats, stable_hash};
+
+// a ±1 sign for a tile from a second hash, independent of the one its index comes from
+fn sign(coords: &[isize]) -> f64 {
+    if stable_hash(coords) >> 63 == 0 {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+/// The signed version of the `tiles` function, i.e. the classic hashing trick: along with its hashed index, each
+/// tile gets a pseudo-random sign of ±1 from a second hash. Features are then weighted by their sign, so that tiles
+/// which collide onto the same index cancel out on average rather than biasing each other, which matters when
+/// `size` is much smaller than the number of distinct tiles.
+///
+/// The indices are exactly those returned by `tiles`.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{tiles, tiles_signed};
+/// let signed = tiles_signed(1024, 8, &[3.6, 7.21], None);
+/// let indices: Vec<usize> = signed.iter().map(|&(i, _)| i).collect();
+/// assert_eq!(indices, tiles(1024, 8, &[3.6, 7.21], None));
+/// assert!(signed.iter().all(|&(_, sign)| sign == 1.0 || sign == -1.0));
+///
+/// // values are computed as the signed sum of weights
+/// let weights = vec![0.5; 1024];
+/// let value: f64 = signed.iter().map(|&(i, sign)| sign * weights[i]).sum();
+/// assert!(value.abs() <= 4.0);
+/// ```
+pub fn tiles_signed(size: usize, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<(usize, f64)> {
+    let q_floats = calculate_q_floats(floats, num_tilings);
+    let mut tiles: Vec<(usize, f64)> = Vec::with_capacity(num_tilings);
+
+    for tiling in 0..num_tilings {
+        let coords = calculate_coords(tiling, num_tilings, &q_floats, &ints);
+        let sign = sign(&coords);
+        tiles.push((base_hash(coords) % size, sign));
+    }
+
+    tiles
+}
+
+/// The signed version of the `tiles_wrap` function, see [`tiles_signed`]
+pub fn tiles_wrap_signed(size: usize, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<(usize, f64)> {
+    let q_floats = calculate_q_floats(floats, num_tilings);
+    let mut tiles: Vec<(usize, f64)> = Vec::with_capacity(num_tilings);
+
+    for tiling in 0..num_tilings {
+        let coords = calculate_coords_wrap(tiling, num_tilings, &q_floats, wrap_widths, &ints);
+        let sign = sign(&coords);
+        tiles.push((base_hash(coords) % size, sign));
+    }
+
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles_wrap;
+
+    #[test]
+    fn signs_are_balanced() {
+        let mut positive = 0;
+        for i in 0..1000 {
+            positive += tiles_signed(64, 8, &[i as f64 * 0.3], None).iter().filter(|&&(_, sign)| sign > 0.0).count();
+        }
+        // 8000 fair coin flips are well within 450 of 4000
+        assert!((3550..4450).contains(&positive));
+    }
+
+    #[test]
+    fn wrap_signed_matches_indices() {
+        let signed = tiles_wrap_signed(64, 4, &[9.5], &[Some(10)], Some(&[1]));
+        assert_eq!(signed.iter().map(|&(i, _)| i).collect::<Vec<_>>(), tiles_wrap(64, 4, &[9.5], &[Some(10)], Some(&[1])));
+        assert_eq!(signed, tiles_wrap_signed(64, 4, &[19.5], &[Some(10)], Some(&[1])));
+    }
+}
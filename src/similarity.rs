@@ -0,0 +1,55 @@
+use crate::{calculate_coords, calculate_q_floats};
+
+/// The fraction of tilings in which the points `a` and `b` fall in the same tile. This is the generalization
+/// kernel induced by tiling with `num_tilings` tilings: an update made at `a` changes the value at `b` in
+/// proportion to it, which makes it handy for tuning tiling counts and scales.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::similarity;
+/// assert_eq!(similarity(8, &[3.6, 7.21], &[3.6, 7.21]), 1.0);
+/// assert_eq!(similarity(8, &[3.6, 7.21], &[3.7, 7.21]), 7.0 / 8.0);
+/// assert_eq!(similarity(8, &[3.6, 7.21], &[-37.2, 7.0]), 0.0);
+/// ```
+pub fn similarity(num_tilings: usize, a: &[f64], b: &[f64]) -> f64 {
+    assert_eq!(a.len(), b.len(), "points must have the same number of dimensions");
+    if num_tilings == 0 {
+        return 0.0;
+    }
+
+    let q_a = calculate_q_floats(a, num_tilings);
+    let q_b = calculate_q_floats(b, num_tilings);
+    let shared = (0..num_tilings)
+        .filter(|&tiling| calculate_coords(tiling, num_tilings, &q_a, &None) == calculate_coords(tiling, num_tilings, &q_b, &None))
+        .count();
+
+    shared as f64 / num_tilings as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IHT;
+
+    #[test]
+    fn matches_shared_iht_tiles() {
+        let mut iht = IHT::new(4096);
+        let points = [[0.0, 0.0], [0.1, 0.3], [0.9, -0.2], [2.5, 1.0]];
+        for a in points.iter() {
+            for b in points.iter() {
+                let ta = iht.tiles(16, a, None);
+                let tb = iht.tiles(16, b, None);
+                let shared = ta.iter().zip(tb.iter()).filter(|(x, y)| x == y).count();
+                assert_eq!(similarity(16, a, b), shared as f64 / 16.0);
+            }
+        }
+    }
+
+    #[test]
+    fn decreases_with_distance() {
+        let near = similarity(8, &[0.5], &[0.6]);
+        let far = similarity(8, &[0.5], &[1.2]);
+        assert!(near > far);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::inline::InlineCoords;
+use crate::overflow::Staged;
+use crate::{calculate_coords_into, calculate_coords_wrap_into, calculate_q_floats, IHT};
+
+/// A copy-on-write view of an IHT, see [`IHT::snapshot`]. Tiles which are already in the table get their usual
+/// indices; new tiles get temporary indices which live only as long as the snapshot.
+///
+/// Unlike a [`Transaction`](crate::Transaction), a snapshot only borrows the table immutably, so any number of them
+/// can be encoding evaluation rollouts at once, and there is no way to write a snapshot's tiles back.
+#[derive(Debug)]
+pub struct Snapshot<'a> {
+    iht: &'a IHT,
+    temporary: Staged,
+}
+
+impl IHT {
+    /// Take a cheap copy-on-write snapshot of the table: nothing is copied up front, and encoding new states through
+    /// the snapshot gives them temporary indices without touching the table. This is for evaluation rollouts which
+    /// shouldn't pollute the training table; drop the snapshot when they're done.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// iht.tiles(4, &[0.0], None);
+    ///
+    /// let mut eval = iht.snapshot();
+    /// assert_eq!(eval.tiles(4, &[0.0], None), vec![0, 1, 2, 3]);
+    /// // an unseen state gets the indices it would get in the table, but only in the snapshot
+    /// assert_eq!(eval.tiles(4, &[10.0], None), vec![4, 5, 6, 7]);
+    /// assert_eq!(eval.temporary(), 4);
+    /// drop(eval);
+    ///
+    /// assert_eq!(iht.count(), 4);
+    /// ```
+    pub fn snapshot(&self) -> Snapshot<'_> {
+        Snapshot {
+            iht: self,
+            temporary: Staged::default(),
+        }
+    }
+}
+
+impl<'a> Snapshot<'a> {
+    fn get_index(&mut self, coords: &[isize]) -> usize {
+        if let Some(index) = self.iht.get_index_read_only_ref(coords) {
+            return index;
+        }
+        self.temporary.get_index(self.iht, coords).0
+    }
+
+    /// The number of temporary tiles created by the snapshot
+    pub fn temporary(&self) -> usize {
+        self.temporary.tiles.len()
+    }
+
+    /// The number of tiles visible through the snapshot: those in the table, plus the temporary ones
+    pub fn count(&self) -> usize {
+        self.iht.count() + self.temporary.tiles.len()
+    }
+
+    /// The snapshot's equivalent of [`IHT::tiles`]
+    pub fn tiles(&mut self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut tiles: Vec<usize> = Vec::with_capacity(num_tilings);
+
+        let mut coords = InlineCoords::new();
+        for tiling in 0..num_tilings {
+            calculate_coords_into(&mut coords, tiling, num_tilings, &q_floats, &ints);
+            tiles.push(self.get_index(&coords));
+        }
+
+        tiles
+    }
+
+    /// The snapshot's equivalent of [`IHT::tiles_wrap`]
+    pub fn tiles_wrap(&mut self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<usize> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut tiles: Vec<usize> = Vec::with_capacity(num_tilings);
+
+        let mut coords = InlineCoords::new();
+        for tiling in 0..num_tilings {
+            calculate_coords_wrap_into(&mut coords, tiling, num_tilings, &q_floats, wrap_widths, &ints);
+            tiles.push(self.get_index(&coords));
+        }
+
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_a_clone_without_touching_the_table() {
+        let mut iht = IHT::new(12);
+        iht.tiles_wrap(4, &[0.0], &[Some(3)], None);
+        let before = iht.clone();
+
+        let mut clone = iht.clone();
+        let mut snapshot = iht.snapshot();
+        for i in 0..6 {
+            let x = i as f64 * 1.3;
+            assert_eq!(snapshot.tiles(4, &[x], Some(&[1])), clone.tiles(4, &[x], Some(&[1])));
+            assert_eq!(snapshot.tiles_wrap(4, &[x], &[Some(3)], None), clone.tiles_wrap(4, &[x], &[Some(3)], None));
+        }
+        assert_eq!(snapshot.count(), clone.count());
+        assert!(clone.full());
+        drop(snapshot);
+        assert_eq!(iht, before);
+    }
+
+    #[test]
+    fn temporary_tiles_overflow_like_a_clone_double_hashing() {
+        let mut iht = IHT::new(16).with_overflow(crate::Overflow::DoubleHash);
+        iht.tiles(8, &[0.0], None);
+
+        let mut clone = iht.clone();
+        let mut snapshot = iht.snapshot();
+        for &x in &[10.0, 50.0, 90.0] {
+            assert_eq!(snapshot.tiles(8, &[x], None), clone.tiles(8, &[x], None));
+        }
+        assert!(clone.full());
+    }
+}
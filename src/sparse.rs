@@ -0,0 +1,122 @@
+use std::borrow::Cow;
+
+use crate::inline::{CoordBuffer, InlineCoords};
+use crate::{base_hash, IHT};
+
+// the active dimensions in increasing order, checking there are no duplicates
+fn sorted(active: &[(usize, f64)]) -> Cow<'_, [(usize, f64)]> {
+    if active.windows(2).all(|w| w[0].0 < w[1].0) {
+        return Cow::Borrowed(active);
+    }
+    let mut sorted = active.to_vec();
+    sorted.sort_by_key(|&(dim, _)| dim);
+    assert!(sorted.windows(2).all(|w| w[0].0 < w[1].0), "a sparse dimension was given more than once");
+    Cow::Owned(sorted)
+}
+
+// the coordinates of a tiling of a sparse point: the tiling, then each active dimension followed by its coordinate,
+// displaced exactly as dimension `dim` of a dense point would be, then the ints
+fn sparse_coords_into<B: CoordBuffer>(coords: &mut B, tiling: usize, num_tilings: usize, active: &[(usize, f64)], ints: &Option<&[isize]>) {
+    let n = num_tilings as isize;
+    coords.clear();
+    coords.push(tiling as isize);
+    for &(dim, x) in active {
+        let q = (x * num_tilings as f64).floor() as isize;
+        let b = tiling as isize * (2 * dim as isize + 1);
+        coords.push(dim as isize);
+        coords.push((q + b) / n);
+    }
+    if let Some(ints) = ints {
+        coords.extend_from_slice(ints);
+    }
+}
+
+/// The sparse version of the `tiles` function, see [`IHT::tiles_sparse`]
+pub fn tiles_sparse(size: usize, num_tilings: usize, active: &[(usize, f64)], ints: Option<&[isize]>) -> Vec<usize> {
+    let active = sorted(active);
+    let mut coords = InlineCoords::new();
+    (0..num_tilings)
+        .map(|tiling| {
+            sparse_coords_into(&mut coords, tiling, num_tilings, &active, &ints);
+            base_hash(&coords[..]) % size
+        })
+        .collect()
+}
+
+impl IHT {
+    /// Encode a sparse point, given as `(dimension, value)` pairs for only its active dimensions, so that a
+    /// high-dimensional observation which is mostly absent costs only as much as the dimensions it has. The pairs may
+    /// come in any order, but a dimension may only appear once.
+    ///
+    /// Each tile spans the active dimensions only, with each dimension displaced from tiling to tiling just as it is in
+    /// `tiles`. The dimension numbers are part of the tile, so points only share tiles when they have the same active
+    /// dimensions; an absent dimension is not the same as a dimension with a value of zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// // dimensions 3 and 250 of an observation with hundreds of dimensions
+    /// let tiles = iht.tiles_sparse(4, &[(250, 0.5), (3, 1.2)], None);
+    /// assert_eq!(iht.tiles_sparse(4, &[(3, 1.2), (250, 0.5)], None), tiles);
+    ///
+    /// // a different set of active dimensions shares nothing
+    /// let other = iht.tiles_sparse(4, &[(3, 1.2)], None);
+    /// assert!(other.iter().all(|t| !tiles.contains(t)));
+    /// ```
+    pub fn tiles_sparse(&mut self, num_tilings: usize, active: &[(usize, f64)], ints: Option<&[isize]>) -> Vec<usize> {
+        let active = sorted(active);
+        let mut coords = InlineCoords::new();
+        (0..num_tilings)
+            .map(|tiling| {
+                sparse_coords_into(&mut coords, tiling, num_tilings, &active, &ints);
+                self.get_index_ref(&coords)
+            })
+            .collect()
+    }
+
+    /// The read-only version of `tiles_sparse`
+    pub fn tiles_sparse_read_only(&self, num_tilings: usize, active: &[(usize, f64)], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let active = sorted(active);
+        let mut coords = InlineCoords::new();
+        (0..num_tilings)
+            .map(|tiling| {
+                sparse_coords_into(&mut coords, tiling, num_tilings, &active, &ints);
+                self.get_index_read_only_ref(&coords)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate_coords, calculate_q_floats};
+
+    #[test]
+    fn sparse_coordinates_follow_the_dense_displacement() {
+        // with every dimension active, the coordinates are the dense ones interleaved with the dimension numbers
+        let dense = [0.3, -1.7, 4.2];
+        let active: Vec<(usize, f64)> = dense.iter().copied().enumerate().collect();
+        let q_floats = calculate_q_floats(&dense, 8);
+        for tiling in 0..8 {
+            let mut coords = Vec::new();
+            sparse_coords_into(&mut coords, tiling, 8, &active, &Some(&[5]));
+            let expected = calculate_coords(tiling, 8, &q_floats, &Some(&[5]));
+            assert_eq!((coords[0], coords[2], coords[4], coords[6], coords[7]), (expected[0], expected[1], expected[2], expected[3], expected[4]));
+            assert_eq!((coords[1], coords[3], coords[5]), (0, 1, 2));
+        }
+
+        let mut iht = IHT::new(256);
+        let tiles = iht.tiles_sparse(8, &[(40, 1.0), (7, 2.5)], Some(&[1]));
+        assert_eq!(iht.tiles_sparse_read_only(8, &[(7, 2.5), (40, 1.0)], Some(&[1])), tiles.into_iter().map(Some).collect::<Vec<_>>());
+        assert_eq!(tiles_sparse(256, 8, &[(7, 2.5), (40, 1.0)], None), tiles_sparse(256, 8, &[(40, 1.0), (7, 2.5)], None));
+    }
+
+    #[test]
+    #[should_panic(expected = "more than once")]
+    fn duplicate_dimensions_panic() {
+        tiles_sparse(256, 4, &[(2, 0.0), (1, 0.0), (2, 1.0)], None);
+    }
+}
@@ -0,0 +1,305 @@
+use crate::json::Json;
+use crate::tag::{hash_fingerprint, Tag};
+use crate::{Dim, Error, HashFunction, Offsets, Overflow, TileCoder, IHT};
+
+/// The complete definition of how a [`TileCoder`] encodes points: the number of tilings, the size, how every
+/// dimension is scaled and wrapped, the offsets, the mask, and the IHT's hash function and overflow strategy.
+/// Everything but the IHT's entries, which a checkpoint saves (see [`IHT::save`]); together they reconstruct a coder
+/// which gives exactly the same tiles.
+///
+/// Specs serialize to a small JSON object, in which every float is written exactly. It also records the version of
+/// this crate which wrote it and a fingerprint of the hash function, since a coder rebuilt by another version, or with
+/// a hash function which has changed, could give different tiles.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{CoderSpec, Dim, Offsets, TileCoder};
+/// let mut coder = TileCoder::new(8, 4096, vec![Dim::bins(-1.2, 0.6, 8).clamped(), Dim::angle_radians(12)])
+///     .with_offsets(Offsets::Displacement(vec![1, 5]));
+/// let json = coder.spec().to_json();
+///
+/// let mut rebuilt = CoderSpec::from_json(&json).unwrap().build();
+/// assert_eq!(rebuilt.tiles(&[-0.5, 3.0], None), coder.tiles(&[-0.5, 3.0], None));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoderSpec {
+    num_tilings: usize,
+    size: usize,
+    dims: Vec<Dim>,
+    offsets: Offsets,
+    mask: Option<Vec<bool>>,
+    hash_function: HashFunction,
+    overflow: Overflow,
+}
+
+fn dim_to_json(dim: &Dim) -> Json {
+    Json::object(vec![
+        ("origin", Json::from(dim.origin)),
+        ("scale", Json::from(dim.scale)),
+        ("wrap", dim.wrap.map_or(Json::Null, Json::from)),
+        ("range", dim.range.map_or(Json::Null, |(min, max)| Json::from(vec![min, max]))),
+        ("clamp", Json::from(dim.clamp)),
+    ])
+}
+
+fn dim_from_json(json: &Json, i: usize) -> Result<Dim, Error> {
+    let bad = |what: &str| Error::Format(format!("dimension {}: {}", i, what));
+    let float = |key: &str| json.get(key).and_then(Json::as_f64).filter(|x| x.is_finite());
+    let origin = float("origin").ok_or_else(|| bad("`origin` must be a finite number"))?;
+    let scale = float("scale").filter(|&s| s > 0.0).ok_or_else(|| bad("`scale` must be a positive number"))?;
+    let wrap = match json.get("wrap") {
+        None | Some(Json::Null) => None,
+        Some(w) => Some(w.as_i64().filter(|&w| w > 0).ok_or_else(|| bad("`wrap` must be a positive integer or null"))? as isize),
+    };
+    let range = match json.get("range") {
+        None | Some(Json::Null) => None,
+        Some(r) => match r.as_array().map(|r| r.iter().map(Json::as_f64).collect::<Option<Vec<f64>>>()) {
+            Some(Some(r)) if r.len() == 2 && r[0] < r[1] => Some((r[0], r[1])),
+            _ => return Err(bad("`range` must be null or [min, max] with min < max")),
+        },
+    };
+    let clamp = match json.get("clamp") {
+        None => false,
+        Some(c) => c.as_bool().ok_or_else(|| bad("`clamp` must be a boolean"))?,
+    };
+    if clamp && (range.is_none() || wrap.is_some()) {
+        return Err(bad("only a dimension with a range which doesn't wrap can be clamped"));
+    }
+    Ok(Dim {
+        origin,
+        scale,
+        wrap,
+        range,
+        clamp,
+    })
+}
+
+fn offsets_to_json(offsets: &Offsets) -> Json {
+    match offsets {
+        Offsets::Tiles3 => Json::from("tiles3"),
+        Offsets::Coprime => Json::from("coprime"),
+        Offsets::Displacement(displacement) => Json::object(vec![("displacement", Json::from(displacement.clone()))]),
+        Offsets::Random(seed) => Json::object(vec![("random", Json::from(*seed))]),
+    }
+}
+
+fn offsets_from_json(json: &Json) -> Result<Offsets, Error> {
+    if let Some(displacement) = json.get("displacement").and_then(Json::as_array) {
+        return displacement
+            .iter()
+            .map(|d| d.as_i64().map(|d| d as isize))
+            .collect::<Option<Vec<isize>>>()
+            .map(Offsets::Displacement)
+            .ok_or_else(|| Error::Format("the displacement vector must be integers".to_string()));
+    }
+    match (json.as_str(), json.get("random").and_then(Json::as_i64)) {
+        (Some("tiles3"), _) => Ok(Offsets::Tiles3),
+        (Some("coprime"), _) => Ok(Offsets::Coprime),
+        (_, Some(seed)) => Ok(Offsets::Random(seed as u64)),
+        _ => Err(Error::Format(format!("unknown offsets {}", json))),
+    }
+}
+
+impl CoderSpec {
+    /// The number of tilings
+    pub fn num_tilings(&self) -> usize {
+        self.num_tilings
+    }
+
+    /// The size of the IHT
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The float dimensions, in the order their values are expected
+    pub fn dims(&self) -> &[Dim] {
+        &self.dims
+    }
+
+    /// Build a coder from the spec, with a fresh IHT
+    pub fn build(&self) -> TileCoder {
+        let iht = IHT::new(self.size).with_hash_function(self.hash_function).with_overflow(self.overflow);
+        self.build_unchecked(iht)
+    }
+
+    /// Build a coder from the spec around an existing IHT, such as one restored from a checkpoint, failing if the IHT
+    /// doesn't have the spec's size, hash function, and overflow strategy
+    pub fn build_with(&self, iht: IHT) -> Result<TileCoder, Error> {
+        if (iht.size(), iht.hash_function(), iht.overflow()) != (self.size, self.hash_function, self.overflow) {
+            return Err(Error::Config(format!(
+                "the IHT (size {}, {:?} hash, {:?} overflow) doesn't match the spec (size {}, {:?} hash, {:?} overflow)",
+                iht.size(),
+                iht.hash_function(),
+                iht.overflow(),
+                self.size,
+                self.hash_function,
+                self.overflow
+            )));
+        }
+        Ok(self.build_unchecked(iht))
+    }
+
+    fn build_unchecked(&self, iht: IHT) -> TileCoder {
+        let mut coder = TileCoder::new(self.num_tilings, self.size, self.dims.clone()).with_offsets(self.offsets.clone());
+        coder.set_mask(self.mask.clone());
+        *coder.iht_mut() = iht;
+        coder
+    }
+
+    /// Serialize the spec as a single-line JSON object
+    pub fn to_json(&self) -> String {
+        let mut fields = vec![
+            ("num_tilings", Json::from(self.num_tilings)),
+            ("size", Json::from(self.size)),
+            ("dims", Json::Array(self.dims.iter().map(dim_to_json).collect())),
+            ("offsets", offsets_to_json(&self.offsets)),
+            (
+                "hash_function",
+                Json::from(match self.hash_function {
+                    HashFunction::Sip => "sip",
+                    HashFunction::Fx => "fx",
+                }),
+            ),
+            (
+                "overflow",
+                Json::from(match self.overflow {
+                    Overflow::Hash => "hash",
+                    Overflow::DoubleHash => "double_hash",
+                }),
+            ),
+        ];
+        if let Some(mask) = &self.mask {
+            fields.push(("mask", Json::from(mask.clone())));
+        }
+        let tag = Tag::new(hash_fingerprint(self.hash_function), 0);
+        fields.push(("crate_version", Json::from(tag.crate_version.as_str())));
+        fields.push(("hash_fingerprint", Json::from(tag.hash)));
+        Json::object(fields).to_string()
+    }
+
+    /// Parse a spec written by [`CoderSpec::to_json`], checking that it describes a valid coder. The version and hash
+    /// fingerprint may be left out, as in specs written by hand; when they are there, a spec written by a version of
+    /// this crate with a different major or minor version, or whose hash function gives different hashes in this
+    /// build, is an `Error::Format`.
+    pub fn from_json(source: &str) -> Result<CoderSpec, Error> {
+        let json = Json::parse(source)?;
+        let field = |key: &str| json.get(key).ok_or_else(|| Error::Format(format!("missing `{}`", key)));
+        let count_field = |key: &str| {
+            field(key)?
+                .as_i64()
+                .filter(|&v| v > 0)
+                .map(|v| v as usize)
+                .ok_or_else(|| Error::Format(format!("`{}` must be a positive integer", key)))
+        };
+        let num_tilings = count_field("num_tilings")?;
+        let size = count_field("size")?;
+        let dims = field("dims")?
+            .as_array()
+            .ok_or_else(|| Error::Format("`dims` must be an array".to_string()))?
+            .iter()
+            .enumerate()
+            .map(|(i, dim)| dim_from_json(dim, i))
+            .collect::<Result<Vec<Dim>, Error>>()?;
+        let offsets = offsets_from_json(field("offsets")?)?;
+        if let Some(len) = offsets.dimensions() {
+            if len != dims.len() {
+                return Err(Error::Format(format!("the displacement vector has {} entries but there are {} dimensions", len, dims.len())));
+            }
+        }
+        let mask = match json.get("mask") {
+            None => None,
+            Some(mask) => match mask.as_array().map(|m| m.iter().map(Json::as_bool).collect::<Option<Vec<bool>>>()) {
+                Some(Some(mask)) if mask.len() == dims.len() => Some(mask),
+                _ => return Err(Error::Format("`mask` must have one boolean per dimension".to_string())),
+            },
+        };
+        let hash_function = match field("hash_function")?.as_str() {
+            Some("sip") => HashFunction::Sip,
+            Some("fx") => HashFunction::Fx,
+            _ => return Err(Error::Format("`hash_function` must be \"sip\" or \"fx\"".to_string())),
+        };
+        let overflow = match field("overflow")?.as_str() {
+            Some("hash") => Overflow::Hash,
+            Some("double_hash") => Overflow::DoubleHash,
+            _ => return Err(Error::Format("`overflow` must be \"hash\" or \"double_hash\"".to_string())),
+        };
+        if let Some(version) = json.get("crate_version") {
+            let tag = Tag {
+                crate_version: version.as_str().ok_or_else(|| Error::Format("`crate_version` must be a string".to_string()))?.to_string(),
+                hash: field("hash_fingerprint")?
+                    .as_i64()
+                    .ok_or_else(|| Error::Format("`hash_fingerprint` must be an integer".to_string()))? as u64,
+                config: 0,
+            };
+            tag.check_version("coder spec")?;
+            tag.check_hash("coder spec", hash_fingerprint(hash_function))?;
+        }
+        Ok(CoderSpec {
+            num_tilings,
+            size,
+            dims,
+            offsets,
+            // stored the way `TileCoder::set_mask` stores it, so that specs compare equal
+            mask: mask.filter(|mask| !mask.iter().all(|&m| m)),
+            hash_function,
+            overflow,
+        })
+    }
+}
+
+impl TileCoder {
+    /// The complete definition of how the coder encodes points, see [`CoderSpec`]
+    pub fn spec(&self) -> CoderSpec {
+        CoderSpec {
+            num_tilings: self.num_tilings(),
+            size: self.size(),
+            dims: self.dims().to_vec(),
+            offsets: self.offsets().clone(),
+            mask: self.mask().map(<[bool]>::to_vec),
+            hash_function: self.iht().hash_function(),
+            overflow: self.iht().overflow(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_every_setting() {
+        let dims = vec![Dim::bins(-1.2, 0.6, 7).clamped(), Dim::wrapping(0.0, 360.0, 12), Dim::width(0.1 + 0.2)];
+        let mut coder = TileCoder::new(6, 512, dims).with_offsets(Offsets::Random(u64::MAX - 3));
+        coder.set_mask(Some(vec![true, false, true]));
+        *coder.iht_mut() = IHT::new(512).with_hash_function(HashFunction::Fx).with_overflow(Overflow::DoubleHash);
+        for i in 0..20 {
+            coder.tiles(&[i as f64 * 0.1 - 1.0, i as f64 * 40.0, i as f64], Some(&[i]));
+        }
+
+        let spec = CoderSpec::from_json(&coder.spec().to_json()).unwrap();
+        assert_eq!(spec, coder.spec());
+        assert_eq!(spec.build_with(coder.iht().clone()).unwrap(), coder);
+        assert!(spec.build_with(IHT::new(512)).is_err());
+        // a fresh coder hands out indices in the same order
+        let mut fresh = spec.build();
+        assert_eq!(fresh.tiles(&[-1.0, 0.0, 0.0], Some(&[0])), coder.tiles(&[-1.0, 0.0, 0.0], Some(&[0])));
+
+        for broken in &[
+            r#"{"num_tilings":0,"size":8,"dims":[],"offsets":"tiles3","hash_function":"sip","overflow":"hash"}"#,
+            r#"{"num_tilings":4,"size":8,"dims":[{"origin":0.0,"scale":-1.0}],"offsets":"tiles3","hash_function":"sip","overflow":"hash"}"#,
+            r#"{"num_tilings":4,"size":8,"dims":[{"origin":0.0,"scale":1.0,"clamp":true}],"offsets":"tiles3","hash_function":"sip","overflow":"hash"}"#,
+            r#"{"num_tilings":4,"size":8,"dims":[{"origin":0.0,"scale":1.0}],"offsets":{"displacement":[1,3]},"hash_function":"sip","overflow":"hash"}"#,
+        ] {
+            assert!(CoderSpec::from_json(broken).is_err(), "{}", broken);
+        }
+
+        // the tag: another minor release, or a changed hash function, can't rebuild the coder
+        let json = coder.spec().to_json();
+        let fingerprint = format!("\"hash_fingerprint\":{}", hash_fingerprint(HashFunction::Fx) as i64);
+        assert!(json.contains(&fingerprint) && json.contains(concat!("\"crate_version\":\"", env!("CARGO_PKG_VERSION"), "\"")));
+        assert!(CoderSpec::from_json(&json.replace(&fingerprint, "\"hash_fingerprint\":1")).is_err());
+        assert!(CoderSpec::from_json(&json.replace(env!("CARGO_PKG_VERSION"), "0.0.1")).is_err());
+        assert!(CoderSpec::from_json(&json.replace(env!("CARGO_PKG_VERSION"), "not a version")).is_err());
+    }
+}
@@ -0,0 +1,82 @@
+/// Augments observations with an exponentially-decayed average of the recent observations, so that
+/// tiling the augmented observation gives the coder velocity-like information even when only
+/// positions are observed.
+///
+/// Each call to `augment` returns the observation followed by the running average, i.e. twice as many
+/// floats as were passed in. The average starts at the first observation seen after creation (or after
+/// a `reset`), and is updated as `average = decay * average + (1 - decay) * observation`.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{IHT, ObservationStack};
+/// let mut iht = IHT::new(1024);
+/// let mut stack = ObservationStack::new(0.5);
+///
+/// assert_eq!(stack.augment(&[1.0]), vec![1.0, 1.0]);
+/// assert_eq!(stack.augment(&[2.0]), vec![2.0, 1.5]);
+///
+/// // the augmented observation is tiled like any other
+/// let indices = iht.tiles(8, &stack.augment(&[3.0]), None);
+/// assert_eq!(indices.len(), 8);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObservationStack {
+    decay: f64,
+    average: Option<Vec<f64>>,
+}
+
+impl ObservationStack {
+    /// Create a new stack with the given decay in `[0, 1)`. Larger decays average over a longer history.
+    pub fn new(decay: f64) -> ObservationStack {
+        assert!((0.0..1.0).contains(&decay), "decay must be in the range [0, 1)");
+        ObservationStack { decay, average: None }
+    }
+
+    /// The current decayed average, or `None` if nothing has been observed since the last reset
+    pub fn average(&self) -> Option<&[f64]> {
+        self.average.as_deref()
+    }
+
+    /// Forget the history, typically at the start of a new episode
+    pub fn reset(&mut self) {
+        self.average = None;
+    }
+
+    /// Fold `observation` into the decayed average and return the observation followed by the average
+    pub fn augment(&mut self, observation: &[f64]) -> Vec<f64> {
+        let decay = self.decay;
+        let average = self.average.get_or_insert_with(|| observation.to_vec());
+        assert_eq!(average.len(), observation.len(), "observations must all have the same number of dimensions");
+        for (a, &x) in average.iter_mut().zip(observation) {
+            *a = decay * *a + (1.0 - decay) * x;
+        }
+
+        let mut augmented = Vec::with_capacity(observation.len() * 2);
+        augmented.extend_from_slice(observation);
+        augmented.extend_from_slice(average);
+        augmented
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_decay_tracks_observation() {
+        let mut stack = ObservationStack::new(0.0);
+        stack.augment(&[1.0, 2.0]);
+        assert_eq!(stack.augment(&[5.0, -1.0]), vec![5.0, -1.0, 5.0, -1.0]);
+    }
+
+    #[test]
+    fn reset_restarts_average() {
+        let mut stack = ObservationStack::new(0.9);
+        stack.augment(&[1.0]);
+        stack.augment(&[10.0]);
+        stack.reset();
+        assert_eq!(stack.average(), None);
+        assert_eq!(stack.augment(&[4.0]), vec![4.0, 4.0]);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{Dim, IHT};
+
+/// A tile coder whose tilings are grouped into blocks, each of which only tiles a subset of the dimensions. For
+/// example, some tilings over `[x]`, some over `[y]`, and some over `[x, y]`: the single-dimension stripes generalize
+/// across the other dimension entirely, while the conjunctive tilings pick up on interactions. This is the standard
+/// way of controlling the structure of generalization (Sutton & Barto, section 9.5.4).
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{Dim, StripeCoder};
+/// let mut coder = StripeCoder::new(4096, vec![Dim::bins(0.0, 1.0, 10), Dim::bins(0.0, 1.0, 10)])
+///     .group(&[0], 4)
+///     .group(&[1], 4)
+///     .group(&[0, 1], 8);
+/// assert_eq!(coder.num_tilings(), 16);
+///
+/// // points with the same x share every tile of the x stripes, whatever their y
+/// let a = coder.tiles(&[0.25, 0.1], None);
+/// let b = coder.tiles(&[0.25, 0.9], None);
+/// assert_eq!(a[..4], b[..4]);
+/// assert!(a[4..].iter().all(|i| !b.contains(i)));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct StripeCoder {
+    dims: Vec<Dim>,
+    // (the dimensions tiled by the group, the number of tilings in the group)
+    groups: Vec<(Vec<usize>, usize)>,
+    iht: IHT,
+}
+
+// what a single group is tiled with
+struct GroupInput {
+    num_tilings: usize,
+    scaled: Vec<f64>,
+    // `None` if none of the group's dimensions wrap
+    wrap_widths: Option<Vec<Option<isize>>>,
+    ints: Vec<isize>,
+}
+
+impl StripeCoder {
+    /// Create a coder over the given dimensions, backed by an IHT with the given size. It has no tilings until groups
+    /// are added with `group`.
+    pub fn new(size: usize, dims: Vec<Dim>) -> StripeCoder {
+        StripeCoder {
+            dims,
+            groups: Vec::new(),
+            iht: IHT::new(size),
+        }
+    }
+
+    /// Add a block of `num_tilings` tilings over the dimensions with the given indices
+    pub fn group(mut self, dims: &[usize], num_tilings: usize) -> StripeCoder {
+        assert!(!dims.is_empty(), "groups must tile at least one dimension");
+        assert!(dims.iter().all(|&d| d < self.dims.len()), "group dimensions must be less than the number of dimensions");
+        self.groups.push((dims.to_vec(), num_tilings));
+        self
+    }
+
+    /// The groups of tilings, as the dimensions each tiles and its number of tilings
+    pub fn groups(&self) -> impl Iterator<Item = (&[usize], usize)> {
+        self.groups.iter().map(|(dims, num_tilings)| (&dims[..], *num_tilings))
+    }
+
+    /// The total number of tilings, i.e. the number of indices returned by `tiles`
+    pub fn num_tilings(&self) -> usize {
+        self.groups.iter().map(|&(_, num_tilings)| num_tilings).sum()
+    }
+
+    /// The underlying IHT, shared by all groups
+    pub fn iht(&self) -> &IHT {
+        &self.iht
+    }
+
+    fn group_inputs(&self, floats: &[f64], ints: Option<&[isize]>) -> Vec<GroupInput> {
+        assert_eq!(floats.len(), self.dims.len(), "expected one float per dimension");
+        self.groups
+            .iter()
+            .enumerate()
+            .map(|(group, (dims, num_tilings))| {
+                let scaled = dims.iter().map(|&d| self.dims[d].scale(floats[d])).collect();
+                let wrap_widths: Vec<Option<isize>> = dims.iter().map(|&d| self.dims[d].wrap_width()).collect();
+
+                // salt each group so that their tiles never coincide
+                let mut group_ints = ints.unwrap_or(&[]).to_vec();
+                group_ints.push(group as isize);
+                GroupInput {
+                    num_tilings: *num_tilings,
+                    scaled,
+                    wrap_widths: if wrap_widths.iter().any(Option::is_some) { Some(wrap_widths) } else { None },
+                    ints: group_ints,
+                }
+            })
+            .collect()
+    }
+
+    /// Encode a raw point as the indices of every group's tilings, in the order the groups were added
+    pub fn tiles(&mut self, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        let mut tiles = Vec::with_capacity(self.num_tilings());
+        for group in self.group_inputs(floats, ints) {
+            match group.wrap_widths {
+                Some(wrap_widths) => tiles.extend(self.iht.tiles_wrap(group.num_tilings, &group.scaled, &wrap_widths, Some(&group.ints))),
+                None => tiles.extend(self.iht.tiles(group.num_tilings, &group.scaled, Some(&group.ints))),
+            }
+        }
+        tiles
+    }
+
+    /// The read-only version of `tiles`
+    pub fn tiles_read_only(&self, floats: &[f64], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let mut tiles = Vec::with_capacity(self.num_tilings());
+        for group in self.group_inputs(floats, ints) {
+            match group.wrap_widths {
+                Some(wrap_widths) => tiles.extend(self.iht.tiles_wrap_read_only(group.num_tilings, &group.scaled, &wrap_widths, Some(&group.ints))),
+                None => tiles.extend(self.iht.tiles_read_only(group.num_tilings, &group.scaled, Some(&group.ints))),
+            }
+        }
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_only_see_their_dimensions() {
+        let mut coder = StripeCoder::new(1024, vec![Dim::width(1.0), Dim::wrapping(0.0, 1.0, 4), Dim::width(1.0)])
+            .group(&[1], 4)
+            .group(&[0, 2], 4);
+        let a = coder.tiles(&[0.0, 0.3, 5.0], Some(&[1]));
+        let b = coder.tiles(&[7.0, 1.3, 5.0], Some(&[1]));
+        assert_eq!(a[..4], b[..4]);
+        assert_ne!(a[4..], b[4..]);
+        assert_eq!(coder.tiles_read_only(&[7.0, 1.3, 5.0], Some(&[1])), b.into_iter().map(Some).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn identical_groups_are_distinct() {
+        let mut coder = StripeCoder::new(1024, vec![Dim::width(1.0)]).group(&[0], 2).group(&[0], 2);
+        assert_eq!(coder.tiles(&[0.5], None), vec![0, 1, 2, 3]);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{CoderConfig, Error, HashFunction};
+
+// coordinates hashed to fingerprint a hash function: if the same function hashes them differently when something is
+// loaded (e.g. because the standard library's SipHash keys changed), every index it placed with that hash moves too
+pub(crate) const PROBE: &[isize] = &[0, 1, -1, 0x5eed];
+
+pub(crate) fn hash_fingerprint(function: HashFunction) -> u64 {
+    function.hash(PROBE) as u64
+}
+
+// the major and minor parts of a `major.minor.patch` version, which are all that must match for one version of the
+// crate to read what another wrote: semver allows changes to the formats in a minor release (the breaking one before
+// 1.0), but not in a patch release
+fn major_minor(version: &str) -> Option<(u64, u64)> {
+    let mut parts = version.split('.');
+    Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
+
+// a version packed into a word as 21 bits each of major, minor, and patch, for fixed-size headers; anything after the
+// patch number (such as a pre-release) is dropped
+fn pack_version(version: &str) -> u64 {
+    let mut parts = version.split(|c: char| !c.is_ascii_digit()).map(|p| p.parse::<u64>().unwrap_or(0) & 0x1f_ffff);
+    let mut part = || parts.next().unwrap_or(0);
+    (part() << 42) | (part() << 21) | part()
+}
+
+fn unpack_version(word: u64) -> String {
+    format!("{}.{}.{}", word >> 42, (word >> 21) & 0x1f_ffff, word & 0x1f_ffff)
+}
+
+// what serialized state records about where it came from: the crate version that wrote it, a fingerprint of the hash
+// its indices depend on, and the fingerprint of the coder configuration it was saved for (see
+// `CoderConfig::fingerprint`), or 0 for none
+pub(crate) struct Tag {
+    pub(crate) crate_version: String,
+    pub(crate) hash: u64,
+    pub(crate) config: u64,
+}
+
+impl Tag {
+    // the tag of something written by this build
+    pub(crate) fn new(hash: u64, config: u64) -> Tag {
+        Tag {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            hash,
+            config,
+        }
+    }
+
+    pub(crate) fn to_words(&self) -> [u64; 3] {
+        [pack_version(&self.crate_version), self.hash, self.config]
+    }
+
+    pub(crate) fn from_words(words: [u64; 3]) -> Tag {
+        Tag {
+            crate_version: unpack_version(words[0]),
+            hash: words[1],
+            config: words[2],
+        }
+    }
+
+    // fail unless `what` was written by a version of the crate which this one can read
+    pub(crate) fn check_version(&self, what: &str) -> Result<(), Error> {
+        let current = env!("CARGO_PKG_VERSION");
+        match (major_minor(&self.crate_version), major_minor(current)) {
+            (Some(written), Some(current)) if written == current => Ok(()),
+            _ => Err(Error::Format(format!(
+                "{} was written by tilecoding {}, which this version ({}) can't read",
+                what, self.crate_version, current
+            ))),
+        }
+    }
+
+    // fail unless `what` was written with the same hash as this build's
+    pub(crate) fn check_hash(&self, what: &str, hash: u64) -> Result<(), Error> {
+        if self.hash == hash {
+            Ok(())
+        } else {
+            Err(Error::Format(format!(
+                "{} was written by tilecoding {} with a hash which differs from this build's, so its tiles would get \
+                 different indices",
+                what, self.crate_version
+            )))
+        }
+    }
+
+    // fail unless `what` was saved for exactly `config`
+    pub(crate) fn check_config(&self, what: &str, config: &CoderConfig) -> Result<(), Error> {
+        match self.config {
+            0 => Err(Error::Format(format!("{} wasn't saved with a coder configuration", what))),
+            saved if saved != config.fingerprint() => Err(Error::Format(format!(
+                "{} was saved by tilecoding {} for a different coder configuration",
+                what, self.crate_version
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_compatible_within_a_minor_release() {
+        let tag = |version: &str| Tag { crate_version: version.to_string(), ..Tag::new(0, 0) };
+        let (major, minor) = major_minor(env!("CARGO_PKG_VERSION")).unwrap();
+        assert!(tag(env!("CARGO_PKG_VERSION")).check_version("test").is_ok());
+        assert!(tag(&format!("{}.{}.999", major, minor)).check_version("test").is_ok());
+        assert!(tag(&format!("{}.{}.0", major, minor + 1)).check_version("test").is_err());
+        assert!(tag(&format!("{}.{}.0", major + 1, minor)).check_version("test").is_err());
+        assert!(tag("garbage").check_version("test").is_err());
+
+        let written = Tag::from_words(tag("1.22.333-beta.4").to_words());
+        assert_eq!((written.crate_version.as_str(), written.hash), ("1.22.333", 0));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{base_hash, calculate_coords, calculate_q_floats, IHT};
+
+/// Controls how much of the feature space is shared between tasks when tiles are
+/// salted with a task identifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskSharing {
+    /// Every tiling is shared: the task identifier is ignored and all tasks see the same tiles
+    Shared,
+    /// No tiling is shared: every task gets its own, disjoint set of tiles
+    Separate,
+    /// The first `n` tilings are shared between all tasks, the remaining tilings are specific to each task
+    Partial(usize),
+}
+
+impl TaskSharing {
+    /// Whether the given tiling is specific to each task (i.e. salted with the task identifier)
+    fn salts(self, tiling: usize) -> bool {
+        match self {
+            TaskSharing::Shared => false,
+            TaskSharing::Separate => true,
+            TaskSharing::Partial(n) => tiling >= n,
+        }
+    }
+}
+
+// task-specific tiles have their tiling negated, as `TileCoder` does for masked tiles, so they never share a key with
+// an unsalted tile whatever its ints are; the task identifier is appended after the ints
+fn calculate_coords_task(tiling: usize, num_tilings: usize, q_floats: &[isize], ints: &Option<&[isize]>, task: isize, sharing: TaskSharing) -> Vec<isize> {
+    let mut coords = calculate_coords(tiling, num_tilings, q_floats, ints);
+    if sharing.salts(tiling) {
+        coords[0] = -(coords[0] + 1);
+        coords.push(task);
+    }
+    coords
+}
+
+impl IHT {
+    /// The same as the `tiles` function, except that the tiles are salted with a task identifier so that multiple
+    /// tasks can share one IHT (and one weight vector) with a controlled amount of overlap.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_tilings`—indicates the number of tile indices to be generated (i.e. the length of the returned `Vec`). This value hould be a power of two greater or equal to four times the number of floats according to the original implementation.
+    /// * `floats`—a list of floating-point numbers to be tiled
+    /// * `ints`—an optional list of integers that will also be tiled; all distinct integers will result in different tilings. In reinforcement learning, discrete actions are often provided here.
+    /// * `task`—the identifier of the task the tiles are being generated for
+    /// * `sharing`—which tilings are shared between tasks, see [`TaskSharing`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{IHT, TaskSharing};
+    /// let mut iht = IHT::new(1024);
+    ///
+    /// // share half of the tilings between the two tasks
+    /// let a = iht.tiles_task(8, &[3.6, 7.21], None, 0, TaskSharing::Partial(4));
+    /// let b = iht.tiles_task(8, &[3.6, 7.21], None, 1, TaskSharing::Partial(4));
+    ///
+    /// assert_eq!(a[..4], b[..4]);
+    /// assert_eq!(a, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+    /// assert_eq!(b, vec![0, 1, 2, 3, 8, 9, 10, 11]);
+    /// ```
+    pub fn tiles_task(&mut self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>, task: isize, sharing: TaskSharing) -> Vec<usize> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut tiles: Vec<usize> = Vec::with_capacity(num_tilings);
+
+        for tiling in 0..num_tilings {
+            let coords = calculate_coords_task(tiling, num_tilings, &q_floats, &ints, task, sharing);
+            tiles.push(self.get_index(coords));
+        }
+
+        tiles
+    }
+
+    /// The read-only version of `tiles_task`
+    pub fn tiles_task_read_only(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>, task: isize, sharing: TaskSharing) -> Vec<Option<usize>> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut tiles: Vec<Option<usize>> = Vec::with_capacity(num_tilings);
+
+        for tiling in 0..num_tilings {
+            let coords = calculate_coords_task(tiling, num_tilings, &q_floats, &ints, task, sharing);
+            tiles.push(self.get_index_read_only(coords));
+        }
+
+        tiles
+    }
+}
+
+/// A task-salted version of `tiles` that doesn't use an IHT, see [`IHT::tiles_task`] for the meaning of `task` and `sharing`
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{tiles_task, TaskSharing};
+/// let a = tiles_task(1024, 8, &[3.6, 7.21], None, 0, TaskSharing::Shared);
+/// let b = tiles_task(1024, 8, &[3.6, 7.21], None, 1, TaskSharing::Shared);
+///
+/// // fully shared tiles are identical to the un-salted version
+/// assert_eq!(a, b);
+/// assert_eq!(a, tilecoding::tiles(1024, 8, &[3.6, 7.21], None));
+/// ```
+pub fn tiles_task(size: usize, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>, task: isize, sharing: TaskSharing) -> Vec<usize> {
+    let q_floats = calculate_q_floats(floats, num_tilings);
+    let mut tiles: Vec<usize> = Vec::with_capacity(num_tilings);
+
+    for tiling in 0..num_tilings {
+        let coords = calculate_coords_task(tiling, num_tilings, &q_floats, &ints, task, sharing);
+        tiles.push(base_hash(coords) % size);
+    }
+
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separate_tasks_share_nothing() {
+        let mut iht = IHT::new(64);
+        let a = iht.tiles_task(8, &[0.0], None, 0, TaskSharing::Separate);
+        let b = iht.tiles_task(8, &[0.0], None, 1, TaskSharing::Separate);
+        assert!(a.iter().all(|i| !b.contains(i)));
+    }
+
+    #[test]
+    fn salted_tiles_never_match_untasked_ints() {
+        let mut iht = IHT::new(64);
+        assert_eq!(iht.tiles(4, &[0.0], Some(&[5])), vec![0, 1, 2, 3]);
+        assert_eq!(iht.tiles_task(4, &[0.0], None, 5, TaskSharing::Separate), vec![4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn shared_tasks_match_untasked_tiles() {
+        let mut iht = IHT::new(64);
+        let a = iht.tiles_task(8, &[0.0, 1.5], Some(&[2]), 7, TaskSharing::Shared);
+        let b = iht.tiles(8, &[0.0, 1.5], Some(&[2]));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn partial_sharing_read_only() {
+        let mut iht = IHT::new(64);
+        iht.tiles_task(4, &[0.0], None, 0, TaskSharing::Partial(2));
+        let indices = iht.tiles_task_read_only(4, &[0.0], None, 1, TaskSharing::Partial(2));
+        assert_eq!(indices, vec![Some(0), Some(1), None, None]);
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{LinearVFA, TileCoder};
+
+/// A true online TD(λ) learner (van Seijen et al., 2016) for state values, over the tiles of a [`TileCoder`]. It uses
+/// dutch traces, so that its weights exactly match those of the online λ-return algorithm, which conventional TD(λ)
+/// only approximates.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{Dim, TileCoder, TrueOnlineTdLambda};
+/// let coder = TileCoder::new(8, 1024, vec![Dim::bins(0.0, 1.0, 4)]);
+/// let mut learner = TrueOnlineTdLambda::new(coder, 0.1, 1.0, 0.9);
+///
+/// // a deterministic chain from 0 to 1, with a reward of 1 at the end
+/// for _ in 0..100 {
+///     for step in 0..10 {
+///         let (state, next) = (step as f64 / 10.0, (step + 1) as f64 / 10.0);
+///         let done = step == 9;
+///         learner.step(&[state], if done { 1.0 } else { 0.0 }, &[next], done);
+///     }
+/// }
+/// assert!((learner.value(&[0.0]) - 1.0).abs() < 0.05);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrueOnlineTdLambda {
+    coder: TileCoder,
+    vfa: LinearVFA,
+    alpha: f64,
+    gamma: f64,
+    lambda: f64,
+    // the value of the current state as of the previous step
+    v_old: f64,
+}
+
+impl TrueOnlineTdLambda {
+    /// Create a learner over the tiles of `coder`. `alpha` is the step size, which is divided by the number of
+    /// tilings; `gamma` is the discount and `lambda` the trace decay.
+    pub fn new(coder: TileCoder, alpha: f64, gamma: f64, lambda: f64) -> TrueOnlineTdLambda {
+        let vfa = LinearVFA::new(coder.size());
+        TrueOnlineTdLambda {
+            coder,
+            vfa,
+            alpha,
+            gamma,
+            lambda,
+            v_old: 0.0,
+        }
+    }
+
+    /// The tile coder states are encoded with
+    pub fn coder(&self) -> &TileCoder {
+        &self.coder
+    }
+
+    /// The approximator holding the learned weights and the traces
+    pub fn vfa(&self) -> &LinearVFA {
+        &self.vfa
+    }
+
+    /// The estimated value of a state; tiles which have never been visited contribute nothing
+    pub fn value(&self, state: &[f64]) -> f64 {
+        let tiles: Vec<usize> = self.coder.tiles_read_only(state, None).into_iter().flatten().collect();
+        self.vfa.predict(&tiles)
+    }
+
+    /// Learn from the transition from `state` to `next_state` with the given reward, returning the TD error. When
+    /// `done` is set the next state is terminal (so has no value) and the traces are reset for the next episode.
+    pub fn step(&mut self, state: &[f64], reward: f64, next_state: &[f64], done: bool) -> f64 {
+        let alpha = self.alpha / self.coder.num_tilings() as f64;
+        let tiles = self.coder.tiles(state, None);
+        let v = self.vfa.predict(&tiles);
+        let v_next = if done {
+            0.0
+        } else {
+            let next = self.coder.tiles(next_state, None);
+            self.vfa.predict(&next)
+        };
+        let delta = reward + self.gamma * v_next - v;
+
+        self.vfa.dutch(&tiles, self.gamma * self.lambda, alpha);
+        self.vfa.update_traced(delta + v - self.v_old, alpha);
+        let correction = alpha * (v - self.v_old);
+        let weights = self.vfa.weights_mut();
+        for &tile in tiles.iter() {
+            weights[tile] -= correction;
+        }
+
+        if done {
+            self.vfa.clear_traces();
+            self.v_old = 0.0;
+        } else {
+            self.v_old = v_next;
+        }
+        delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dim;
+
+    // a minimal linear congruential generator for reproducible random walks
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn coin(&mut self) -> bool {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            self.0 >> 63 == 1
+        }
+    }
+
+    // one state per tile, so the approximation is tabular
+    fn tabular() -> TileCoder {
+        TileCoder::new(1, 64, vec![Dim::width(1.0)])
+    }
+
+    #[test]
+    fn random_walk_values() {
+        // the five-state random walk of Sutton & Barto, example 6.2: start in the middle, step left or right at
+        // random, and get a reward of 1 for leaving on the right; the true values are 1/6, 2/6, ..., 5/6
+        let mut learner = TrueOnlineTdLambda::new(tabular(), 0.01, 1.0, 0.5);
+        let mut rng = Lcg(1);
+        for _ in 0..10_000 {
+            let mut state = 3;
+            loop {
+                let next = if rng.coin() { state + 1 } else { state - 1 };
+                let done = next == 0 || next == 6;
+                learner.step(&[state as f64], if next == 6 { 1.0 } else { 0.0 }, &[next as f64], done);
+                if done {
+                    break;
+                }
+                state = next;
+            }
+        }
+        // the RMS error over the states, as in the book's figures
+        let mse: f64 = (1..6).map(|state| (learner.value(&[state as f64]) - state as f64 / 6.0).powi(2)).sum::<f64>() / 5.0;
+        assert!(mse.sqrt() < 0.05);
+    }
+
+    #[test]
+    fn lambda_zero_is_td_zero() {
+        let mut learner = TrueOnlineTdLambda::new(tabular(), 0.5, 0.9, 0.0);
+        let mut coder = tabular();
+        let mut vfa = LinearVFA::new(64);
+        let transitions = [(0.0, 0.0, 1.0, false), (1.0, 1.0, 2.0, false), (2.0, 0.5, 0.0, false), (0.0, 2.0, 1.0, true)];
+        for _ in 0..3 {
+            for &(state, reward, next, done) in transitions.iter() {
+                learner.step(&[state], reward, &[next], done);
+
+                let tiles = coder.tiles(&[state], None);
+                let next_tiles = coder.tiles(&[next], None);
+                let target = reward + if done { 0.0 } else { 0.9 * vfa.predict(&next_tiles) };
+                vfa.update(&tiles, target, 0.5);
+            }
+        }
+        for (a, b) in learner.vfa().weights().iter().zip(vfa.weights()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{TileCoder, IHT};
+
+/// A type which knows how to describe itself as the inputs of the tiling functions, so that domain types (e.g. an
+/// `Observation` struct) can be encoded directly with [`TileCoder::encode`] and the mapping from fields to floats and
+/// ints lives in one place.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{Dim, TileCoder, Tileable};
+/// struct Observation {
+///     position: f64,
+///     velocity: f64,
+///     gear: u8,
+/// }
+///
+/// impl Tileable for Observation {
+///     fn floats(&self, out: &mut Vec<f64>) {
+///         out.push(self.position);
+///         out.push(self.velocity);
+///     }
+///
+///     fn ints(&self, out: &mut Vec<isize>) {
+///         out.push(self.gear as isize);
+///     }
+/// }
+///
+/// let mut coder = TileCoder::new(8, 4096, vec![Dim::bins(-1.2, 0.6, 8), Dim::bins(-0.07, 0.07, 8)]);
+/// let observation = Observation { position: -0.5, velocity: 0.01, gear: 2 };
+/// assert_eq!(coder.encode(&observation), coder.tiles(&[-0.5, 0.01], Some(&[2])));
+/// ```
+pub trait Tileable {
+    /// Append the float inputs, in the order of the coder's dimensions
+    fn floats(&self, out: &mut Vec<f64>);
+
+    /// Append the int inputs, if there are any
+    fn ints(&self, _out: &mut Vec<isize>) {}
+
+    /// Append the wrap width of each float, see [`IHT::tiles_wrap`]. If nothing is appended, or none of the floats
+    /// wrap, [`IHT::encode`] uses the non-wrapping tilings. [`TileCoder::encode`] ignores this, as the coder's own
+    /// dimensions say which of them wrap.
+    fn wrap_widths(&self, _out: &mut Vec<Option<isize>>) {}
+}
+
+impl Tileable for [f64] {
+    fn floats(&self, out: &mut Vec<f64>) {
+        out.extend_from_slice(self);
+    }
+}
+
+impl Tileable for Vec<f64> {
+    fn floats(&self, out: &mut Vec<f64>) {
+        out.extend_from_slice(self);
+    }
+}
+
+// the floats, ints, and wrap widths of a value, where no ints become `None`
+struct Inputs {
+    floats: Vec<f64>,
+    ints: Vec<isize>,
+    wrap_widths: Vec<Option<isize>>,
+}
+
+impl Inputs {
+    fn of<T: Tileable + ?Sized>(value: &T) -> Inputs {
+        let mut inputs = Inputs { floats: Vec::new(), ints: Vec::new(), wrap_widths: Vec::new() };
+        value.floats(&mut inputs.floats);
+        value.ints(&mut inputs.ints);
+        value.wrap_widths(&mut inputs.wrap_widths);
+        inputs
+    }
+
+    fn ints(&self) -> Option<&[isize]> {
+        if self.ints.is_empty() {
+            None
+        } else {
+            Some(&self.ints)
+        }
+    }
+
+    fn wraps(&self) -> bool {
+        self.wrap_widths.iter().any(Option::is_some)
+    }
+}
+
+impl IHT {
+    /// Tile a value which describes its own inputs, see [`Tileable`]
+    pub fn encode<T: Tileable + ?Sized>(&mut self, num_tilings: usize, value: &T) -> Vec<usize> {
+        let inputs = Inputs::of(value);
+        if inputs.wraps() {
+            self.tiles_wrap(num_tilings, &inputs.floats, &inputs.wrap_widths, inputs.ints())
+        } else {
+            self.tiles(num_tilings, &inputs.floats, inputs.ints())
+        }
+    }
+
+    /// The read-only version of `encode`
+    pub fn encode_read_only<T: Tileable + ?Sized>(&self, num_tilings: usize, value: &T) -> Vec<Option<usize>> {
+        let inputs = Inputs::of(value);
+        if inputs.wraps() {
+            self.tiles_wrap_read_only(num_tilings, &inputs.floats, &inputs.wrap_widths, inputs.ints())
+        } else {
+            self.tiles_read_only(num_tilings, &inputs.floats, inputs.ints())
+        }
+    }
+}
+
+impl TileCoder {
+    /// Tile a value which describes its own (raw) inputs, see [`Tileable`]
+    pub fn encode<T: Tileable + ?Sized>(&mut self, value: &T) -> Vec<usize> {
+        let inputs = Inputs::of(value);
+        self.tiles(&inputs.floats, inputs.ints())
+    }
+
+    /// The read-only version of `encode`
+    pub fn encode_read_only<T: Tileable + ?Sized>(&self, value: &T) -> Vec<Option<usize>> {
+        let inputs = Inputs::of(value);
+        self.tiles_read_only(&inputs.floats, inputs.ints())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slices_encode_as_floats() {
+        let mut iht = IHT::new(256);
+        let encoded = iht.encode(4, &vec![1.5, -2.0]);
+        assert_eq!(encoded, iht.tiles(4, &[1.5, -2.0], None));
+        assert_eq!(iht.encode_read_only(4, &[1.5, -2.0][..]), encoded.into_iter().map(Some).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,325 @@
+use crate::json::Json;
+use crate::{Error, IHT};
+
+// the constants of the xxHash-based tuple hash used by CPython 3.8 onwards, for 64-bit builds
+const XXPRIME_1: u64 = 11_400_714_785_074_694_791;
+const XXPRIME_2: u64 = 14_029_467_366_897_019_727;
+const XXPRIME_5: u64 = 2_870_177_450_012_600_261;
+// python hashes ints modulo this mersenne prime
+const INT_HASH_MODULUS: u64 = (1 << 61) - 1;
+
+// python's `hash` of an int
+fn python_int_hash(x: i64) -> i64 {
+    let h = (x.unsigned_abs() % INT_HASH_MODULUS) as i64;
+    let h = if x < 0 { -h } else { h };
+    // -1 is reserved for errors in CPython
+    if h == -1 {
+        -2
+    } else {
+        h
+    }
+}
+
+/// Python's `hash(tuple(coords))`, i.e. the hash the Python `tiles3` implementation uses for the indices of tiles
+/// once its IHT is full, and for every tile when it is given a size rather than an IHT. This is the xxHash-based tuple
+/// hash of 64-bit CPython 3.8 and later; ints and tuples of ints aren't affected by `PYTHONHASHSEED`.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::python_hash;
+/// // hash((1, 2, 3)) in python
+/// assert_eq!(python_hash(&[1, 2, 3]), 529344067295497451);
+/// ```
+pub fn python_hash(coords: &[isize]) -> i64 {
+    let mut acc = XXPRIME_5;
+    for &c in coords {
+        let lane = python_int_hash(c as i64) as u64;
+        acc = acc.wrapping_add(lane.wrapping_mul(XXPRIME_2));
+        acc = acc.rotate_left(31);
+        acc = acc.wrapping_mul(XXPRIME_1);
+    }
+    acc = acc.wrapping_add(coords.len() as u64 ^ (XXPRIME_5 ^ 3_527_539));
+    if acc == u64::MAX {
+        1_546_275_796
+    } else {
+        acc as i64
+    }
+}
+
+// python's `hash(tuple(coords)) % size`, which is never negative since python's modulo takes the sign of the divisor
+fn python_hash_index(coords: &[isize], size: usize) -> usize {
+    (python_hash(coords) as i128).rem_euclid(size as i128) as usize
+}
+
+// the coordinates exactly as tiles3 computes them: the same as `calculate_coords`, but with python's flooring
+// division (and modulo, when wrapping), which differ from rust's for negative numbers
+fn python_coords(tiling: usize, num_tilings: usize, q_floats: &[isize], wrap_widths: Option<&[Option<isize>]>, ints: Option<&[isize]>) -> Vec<isize> {
+    let n = num_tilings as isize;
+    let mut coords = Vec::with_capacity(1 + q_floats.len() + ints.map_or(0, |i| i.len()));
+    coords.push(tiling as isize);
+    let mut b = tiling as isize;
+    for (i, q) in q_floats.iter().enumerate() {
+        coords.push(match wrap_widths {
+            None => (q + b).div_euclid(n),
+            Some(widths) => {
+                let c = (q + b.rem_euclid(n)).div_euclid(n);
+                match widths.get(i) {
+                    // like python, a width of zero means no wrapping
+                    Some(Some(w)) if *w != 0 => c.rem_euclid(*w),
+                    _ => c,
+                }
+            }
+        });
+        b += tiling as isize * 2;
+    }
+    coords.extend_from_slice(ints.unwrap_or(&[]));
+    coords
+}
+
+fn python_q_floats(floats: &[f64], num_tilings: usize) -> Vec<isize> {
+    floats.iter().map(|&x| (x * num_tilings as f64).floor() as isize).collect()
+}
+
+/// The tiles3 parity version of the free `tiles` function: the indices are exactly those of the Python
+/// `tiles(size, num_tilings, floats, ints)`, including for negative inputs. See [`IHT::tiles_python`].
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::tiles_python;
+/// // tiles(1024, 8, [3.6, 7.21]) in python
+/// assert_eq!(tiles_python(1024, 8, &[3.6, 7.21], None), vec![101, 1010, 377, 156, 358, 989, 935, 302]);
+/// ```
+pub fn tiles_python(size: usize, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+    let q_floats = python_q_floats(floats, num_tilings);
+    (0..num_tilings).map(|tiling| python_hash_index(&python_coords(tiling, num_tilings, &q_floats, None, ints), size)).collect()
+}
+
+/// The tiles3 parity version of the free `tiles_wrap` function, matching the Python `tileswrap(size, ...)`
+pub fn tiles_wrap_python(size: usize, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<usize> {
+    let q_floats = python_q_floats(floats, num_tilings);
+    (0..num_tilings)
+        .map(|tiling| python_hash_index(&python_coords(tiling, num_tilings, &q_floats, Some(wrap_widths), ints), size))
+        .collect()
+}
+
+// parse a key of a Python dictionary whose tuple keys were converted to strings, e.g. "(0, 3, 7)", "(4,)" or "[0, 3]"
+fn parse_tuple_key(key: &str) -> Option<Vec<isize>> {
+    let inner = key.trim();
+    let inner = inner
+        .strip_prefix('(')
+        .and_then(|k| k.strip_suffix(')'))
+        .or_else(|| inner.strip_prefix('[').and_then(|k| k.strip_suffix(']')))
+        .unwrap_or(inner);
+    let mut parts: Vec<&str> = inner.split(',').map(str::trim).collect();
+    // a one element tuple is written with a trailing comma
+    if parts.len() > 1 && parts.last() == Some(&"") {
+        parts.pop();
+    }
+    parts.into_iter().map(|p| p.parse().ok()).collect()
+}
+
+fn non_negative(value: &Json, what: &str) -> Result<usize, Error> {
+    value
+        .as_i64()
+        .filter(|&v| v >= 0)
+        .map(|v| v as usize)
+        .ok_or_else(|| Error::Format(format!("{} must be a non-negative integer", what)))
+}
+
+impl IHT {
+    fn get_index_python(&mut self, coords: Vec<isize>) -> usize {
+        let index = match self.dictionary.get(&coords) {
+            Some(&index) => index,
+            None if self.full() => {
+                self.overfull_count += 1;
+                python_hash_index(&coords, self.size)
+            }
+            None => {
+                let index = self.dictionary.len();
+                self.dictionary.insert(coords, index);
+                index
+            }
+        };
+        self.visit(index);
+        index
+    }
+
+    /// The tiles3 parity version of `tiles`: index assignments and collisions exactly match Sutton's Python
+    /// `tiles3`, for validating a port against existing Python results or for sharing tables with Python (see
+    /// [`IHT::from_tiles3_json`]). Python floors its coordinate divisions where [`IHT::tiles`] truncates them, which
+    /// gives different tiles below zero, and hashes overflowing tiles with Python's tuple hash ([`python_hash`]).
+    ///
+    /// Keep to the `_python` functions for the whole life of a table: mixing them with the others gives the same tile
+    /// two different sets of coordinates for negative inputs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(8);
+    /// // the same as python's tiles(iht, 4, [-3.6, 0.4], [2]), then tiles(iht, 4, [1.0, 2.0]), ...
+    /// assert_eq!(iht.tiles_python(4, &[-3.6, 0.4], Some(&[2])), vec![0, 1, 2, 3]);
+    /// assert_eq!(iht.tiles_python(4, &[1.0, 2.0], None), vec![4, 5, 6, 7]);
+    /// // ... and the collisions once it's full
+    /// assert_eq!(iht.tiles_python(4, &[5.0, 2.0], None), vec![4, 2, 2, 5]);
+    /// ```
+    pub fn tiles_python(&mut self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        let q_floats = python_q_floats(floats, num_tilings);
+        (0..num_tilings).map(|tiling| self.get_index_python(python_coords(tiling, num_tilings, &q_floats, None, ints))).collect()
+    }
+
+    /// The read-only version of `tiles_python`, matching python's `tiles(iht, ..., readonly=True)`
+    pub fn tiles_read_only_python(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let q_floats = python_q_floats(floats, num_tilings);
+        (0..num_tilings)
+            .map(|tiling| self.get_index_read_only_ref(&python_coords(tiling, num_tilings, &q_floats, None, ints)))
+            .collect()
+    }
+
+    /// The tiles3 parity version of `tiles_wrap`, matching python's `tileswrap(iht, ...)`
+    pub fn tiles_wrap_python(&mut self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<usize> {
+        let q_floats = python_q_floats(floats, num_tilings);
+        (0..num_tilings)
+            .map(|tiling| self.get_index_python(python_coords(tiling, num_tilings, &q_floats, Some(wrap_widths), ints)))
+            .collect()
+    }
+
+    /// The read-only version of `tiles_wrap_python`
+    pub fn tiles_wrap_read_only_python(&self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let q_floats = python_q_floats(floats, num_tilings);
+        (0..num_tilings)
+            .map(|tiling| self.get_index_read_only_ref(&python_coords(tiling, num_tilings, &q_floats, Some(wrap_widths), ints)))
+            .collect()
+    }
+
+    /// Import the state of an IHT from the Python `tiles3` implementation, so that a policy trained in Python can be
+    /// served from Rust. Python's `IHT.dictionary` maps coordinate tuples to indices, which JSON can't represent
+    /// directly, so any of these forms is accepted:
+    ///
+    /// * an object with the tuples as string keys, as `json.dumps({str(k): v for k, v in iht.dictionary.items()})`
+    ///   writes them: `{"(0, 3, 7)": 0, ...}`
+    /// * an array of `[coordinates, index]` pairs: `[[[0, 3, 7], 0], ...]`
+    /// * either of those as the `"dictionary"` of an object which also has the IHT's `"size"` and, optionally, its
+    ///   `"overfullCount"`, i.e. `json.dumps` of `vars(iht)` once the dictionary has been converted
+    ///
+    /// The coordinates use the same `[tiling, float coordinates..., ints...]` layout as this crate, so looking up an
+    /// imported point gives the index Python assigned it. Python floors its coordinate divisions while
+    /// [`IHT::tiles`] truncates them, so this holds as long as the scaled floats are non-negative; use
+    /// [`IHT::tiles_python`] and its relatives for exact parity everywhere.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// // exported from Python after `tiles(iht, 2, [1.5])`
+    /// let json = r#"{"size": 1024, "overfullCount": 0, "dictionary": {"(0, 1)": 0, "(1, 2)": 1}}"#;
+    /// let iht = IHT::from_tiles3_json(1024, json).unwrap();
+    /// assert_eq!(iht.tiles_read_only(2, &[1.5], None), vec![Some(0), Some(1)]);
+    /// ```
+    pub fn from_tiles3_json(size: usize, source: &str) -> Result<IHT, Error> {
+        let json = Json::parse(source)?;
+        let mut iht = IHT::new(size);
+        let dictionary = match json.get("dictionary") {
+            Some(dictionary) => {
+                if let Some(saved) = json.get("size") {
+                    let saved = non_negative(saved, "`size`")?;
+                    if saved != size {
+                        return Err(Error::Format(format!("the dictionary is from an IHT of size {}, not {}", saved, size)));
+                    }
+                }
+                if let Some(overfull) = json.get("overfullCount") {
+                    iht.overfull_count = non_negative(overfull, "`overfullCount`")?;
+                }
+                dictionary
+            }
+            None => &json,
+        };
+
+        let entries: Vec<(Vec<isize>, usize)> = match dictionary {
+            Json::Object(fields) => fields
+                .iter()
+                .map(|(key, index)| {
+                    let coords = parse_tuple_key(key).ok_or_else(|| Error::Format(format!("invalid coordinate tuple `{}`", key)))?;
+                    Ok((coords, non_negative(index, "an index")?))
+                })
+                .collect::<Result<_, Error>>()?,
+            Json::Array(pairs) => pairs
+                .iter()
+                .map(|pair| {
+                    let invalid = || Error::Format(format!("invalid [coordinates, index] pair {}", pair));
+                    match pair.as_array() {
+                        Some([coords, index]) => {
+                            let coords = coords
+                                .as_array()
+                                .and_then(|cs| cs.iter().map(|c| c.as_i64().map(|c| c as isize)).collect::<Option<Vec<isize>>>())
+                                .ok_or_else(invalid)?;
+                            Ok((coords, non_negative(index, "an index")?))
+                        }
+                        _ => Err(invalid()),
+                    }
+                })
+                .collect::<Result<_, Error>>()?,
+            _ => return Err(Error::Format("the dictionary must be an object or an array of pairs".to_string())),
+        };
+
+        // python assigns indices in insertion order, so they must be exactly 0..count
+        let mut assigned = vec![false; entries.len()];
+        for (coords, index) in entries {
+            if index >= assigned.len() || index >= size || assigned[index] {
+                return Err(Error::Format(format!("index {} is out of sequence for a dictionary of {} entries", index, assigned.len())));
+            }
+            assigned[index] = true;
+            if iht.dictionary.insert(coords, index).is_some() {
+                return Err(Error::Format("the dictionary has duplicate coordinates".to_string()));
+            }
+        }
+        Ok(iht)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // each expected value was computed by the python tiles3 module, under CPython 3.11
+    #[test]
+    fn matches_python_test_vectors() {
+        assert_eq!(python_hash(&[]), 5740354900026072187);
+        assert_eq!(python_hash(&[0]), -8753497827991233192);
+        assert_eq!(python_hash(&[-1]), 8078679518589016365);
+        assert_eq!(python_hash(&[-2, 5, 1 << 62]), -7262591626861335172);
+        assert_eq!(python_hash(&[(1 << 61) - 1]), python_hash(&[0]));
+
+        assert_eq!(tiles_python(1000, 4, &[-3.6, 0.4], Some(&[2])), vec![309, 908, 6, 855]);
+        assert_eq!(tiles_wrap_python(1000, 4, &[-3.6, 9.9], &[Some(10), None], Some(&[1])), vec![431, 278, 601, 670]);
+
+        let mut iht = IHT::new(8);
+        iht.tiles_python(4, &[-3.6, 0.4], Some(&[2]));
+        iht.tiles_python(4, &[1.0, 2.0], None);
+        assert_eq!(iht.tiles_python(4, &[5.0, 2.0], None), vec![4, 2, 2, 5]);
+        assert_eq!(iht.overfull_count, 4);
+        assert_eq!(iht.tiles_wrap_read_only_python(4, &[-3.6, 0.4], &[Some(3)], Some(&[2])), vec![None; 4]);
+        assert_eq!(iht.tiles_read_only_python(4, &[-3.6, 0.4], Some(&[2])), vec![Some(0), Some(1), Some(2), Some(3)]);
+    }
+
+    #[test]
+    fn imports_every_dictionary_form() {
+        let mut iht = IHT::new(64);
+        iht.tiles(4, &[3.6, 7.21], Some(&[2]));
+        let expected = iht.entries_snapshot();
+
+        let keyed = expected.iter().map(|(c, i)| format!("\"({})\": {}", c.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(", "), i));
+        let keyed = format!("{{{}}}", keyed.collect::<Vec<_>>().join(", "));
+        assert_eq!(IHT::from_tiles3_json(64, &keyed).unwrap().entries_snapshot(), expected);
+
+        let pairs = format!("{{\"size\": 64, \"overfullCount\": 3, \"dictionary\": {:?}}}", expected).replace('(', "[").replace(')', "]");
+        let imported = IHT::from_tiles3_json(64, &pairs).unwrap();
+        assert_eq!((imported.entries_snapshot(), imported.overfull_count), (expected, 3));
+
+        assert_eq!(parse_tuple_key("(4,)"), Some(vec![4]));
+        assert!(IHT::from_tiles3_json(32, &pairs).is_err());
+        assert!(IHT::from_tiles3_json(64, r#"{"(0, 1)": 1}"#).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use crate::IHT;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// A periodic feature of a timestamp, see [`TimeFeatures`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cycle {
+    /// The time of day, from midnight to midnight
+    HourOfDay,
+    /// The day of the week, from Monday to Monday, including the time of day
+    DayOfWeek,
+    /// The day of the (Gregorian) year, from the 1st of January to the next, including the time of day; leap years
+    /// span the same tiles as other years, just a little more slowly
+    DayOfYear,
+}
+
+// the year of a day counted from the Unix epoch, and the day of the 1st of January of that year, using Howard
+// Hinnant's `civil_from_days` and `days_from_civil` algorithms
+fn year_of(days: i64) -> (i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    // the year starts in March, so January and February (`doy >= 306`) belong to the next
+    let year = yoe + era * 400 + (doy >= 306) as i64;
+
+    let y = year - 1;
+    let (era, yoe) = (y.div_euclid(400), y.rem_euclid(400));
+    let jan1 = era * 146_097 + yoe * 365 + yoe / 4 - yoe / 100 + 306 - 719_468;
+    (year, jan1)
+}
+
+fn is_leap(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+impl Cycle {
+    // how far through its period a timestamp is, in `[0, 1)`
+    fn phase(self, seconds: f64) -> f64 {
+        let days = seconds / SECONDS_PER_DAY;
+        let phase = match self {
+            Cycle::HourOfDay => days.rem_euclid(1.0),
+            // the epoch was a Thursday
+            Cycle::DayOfWeek => (days + 3.0).rem_euclid(7.0) / 7.0,
+            Cycle::DayOfYear => {
+                let day = days.floor();
+                let (year, jan1) = year_of(day as i64);
+                let length = if is_leap(year) { 366.0 } else { 365.0 };
+                (day - jan1 as f64 + days.rem_euclid(1.0)) / length
+            }
+        };
+        // rounding can land exactly on the end of the period
+        if phase >= 1.0 {
+            0.0
+        } else {
+            phase
+        }
+    }
+}
+
+/// Periodic features of timestamps, each a dimension which wraps around at the end of its period with a chosen
+/// number of tiles per period, for tiling time series with [`IHT::tiles_wrap`] without working out the scaling and
+/// wrap widths by hand. Timestamps are Unix times in seconds (e.g. `SystemTime::now().duration_since(UNIX_EPOCH)`),
+/// taken as UTC unless an offset is given.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{Cycle, TimeFeatures, IHT};
+/// // hourly tiles over the day, and a tile per day over the week
+/// let features = TimeFeatures::new().with(Cycle::HourOfDay, 24).with(Cycle::DayOfWeek, 7);
+///
+/// // Friday the 1st of March 2024, 13:30 UTC
+/// let t = 1_709_251_200.0 + 13.5 * 3600.0;
+/// assert_eq!(features.scale(t), vec![13.5, 4.0 + 13.5 / 24.0]);
+/// assert_eq!(features.wrap_widths(), vec![Some(24), Some(7)]);
+///
+/// // a week later is the same point
+/// let mut iht = IHT::new(4096);
+/// assert_eq!(features.tiles(&mut iht, 8, t + 7.0 * 86_400.0, None), features.tiles(&mut iht, 8, t, None));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TimeFeatures {
+    cycles: Vec<(Cycle, usize)>,
+    utc_offset: f64,
+}
+
+impl TimeFeatures {
+    /// No features yet, for UTC timestamps
+    pub fn new() -> TimeFeatures {
+        TimeFeatures::default()
+    }
+
+    /// Add a feature with `tiles` tiles (per tiling) over each period
+    pub fn with(mut self, cycle: Cycle, tiles: usize) -> TimeFeatures {
+        assert!(tiles > 0, "a cycle must have at least one tile");
+        self.cycles.push((cycle, tiles));
+        self
+    }
+
+    /// Read timestamps in a local time `offset_seconds` ahead of UTC, e.g. `-5.0 * 3600.0` for US Eastern
+    /// Standard Time, so that the days start at local midnight
+    pub fn with_utc_offset(mut self, offset_seconds: f64) -> TimeFeatures {
+        self.utc_offset = offset_seconds;
+        self
+    }
+
+    /// The features, with the number of tiles over each period
+    pub fn cycles(&self) -> &[(Cycle, usize)] {
+        &self.cycles
+    }
+
+    /// Scale a timestamp to one float per feature, in tile units, to pass to the `tiles_wrap` functions
+    pub fn scale(&self, seconds: f64) -> Vec<f64> {
+        let local = seconds + self.utc_offset;
+        self.cycles.iter().map(|&(cycle, tiles)| cycle.phase(local) * tiles as f64).collect()
+    }
+
+    /// The wrap widths to pass to the `tiles_wrap` functions along with scaled timestamps
+    pub fn wrap_widths(&self) -> Vec<Option<isize>> {
+        self.cycles.iter().map(|&(_, tiles)| Some(tiles as isize)).collect()
+    }
+
+    /// Encode a timestamp (and optional ints) as `num_tilings` tile indices, see [`IHT::tiles_wrap`]
+    pub fn tiles(&self, iht: &mut IHT, num_tilings: usize, seconds: f64, ints: Option<&[isize]>) -> Vec<usize> {
+        iht.tiles_wrap(num_tilings, &self.scale(seconds), &self.wrap_widths(), ints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_of_the_year_follow_the_calendar() {
+        let day = |d: i64| d as f64 * SECONDS_PER_DAY;
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        // the 1st of March 2024 was the 61st day of a leap year, and the 1st of January 2100 starts a common year
+        assert!(close(TimeFeatures::new().with(Cycle::DayOfYear, 366).scale(day(19_783))[0], 60.0));
+        assert_eq!(year_of(47_482), (2100, 47_482));
+        assert_eq!(year_of(47_481), (2099, 47_481 - 364));
+        assert_eq!(year_of(-1), (1969, -365));
+        assert!(close(TimeFeatures::new().with(Cycle::DayOfYear, 365).scale(day(47_482 + 364) + 43_200.0)[0], 364.5));
+
+        // before the epoch, and with an offset: 23:00 UTC on a Wednesday is 01:00 on Thursday two hours ahead
+        let week = TimeFeatures::new().with(Cycle::HourOfDay, 24).with(Cycle::DayOfWeek, 7).with_utc_offset(7200.0);
+        let scaled = week.scale(day(-1) + 23.0 * 3600.0);
+        assert!(close(scaled[0], 1.0) && close(scaled[1], 3.0 + 1.0 / 24.0));
+    }
+}
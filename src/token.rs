@@ -0,0 +1,200 @@
+use std::hash::{Hash, Hasher};
+
+use crate::{TileCoder, IHT};
+
+// FNV-1a, fed with little-endian 64-bit integers, so that a token is the same on every platform and rust version
+struct StableHasher(u64);
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_i8(&mut self, i: i8) {
+        self.write_u64(i as i64 as u64)
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u64(i as i64 as u64)
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u64(i as i64 as u64)
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as i64 as u64)
+    }
+}
+
+/// Turn anything hashable (an enum action, a tuple, an agent ID, ...) into an int which can be passed as one of the
+/// `ints` of the tiling functions. Tokens are stable across platforms and rust versions, so they are safe to use
+/// with an IHT that gets saved and loaded again.
+///
+/// Enum discriminants are hashed as `isize`s, so the tokens of an enum only change if its variants are reordered.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::token;
+/// #[derive(Hash)]
+/// enum Action {
+///     Left,
+///     Right,
+/// }
+///
+/// assert_ne!(token(&Action::Left), token(&Action::Right));
+/// assert_eq!(token(&("agent", 3)), token(&("agent", 3)));
+/// ```
+pub fn token<K: Hash + ?Sized>(key: &K) -> isize {
+    let mut hasher = StableHasher(0xcbf2_9ce4_8422_2325);
+    key.hash(&mut hasher);
+    hasher.finish() as isize
+}
+
+/// Encode categorical features (strings, enums, ...) as ints, one [`token`] per category in order, so that tabular
+/// data can mix them with the continuous features. Each category is its own coordinate, so the same value in two
+/// different columns still gives different tiles.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{ints_from_categories, IHT};
+/// let mut iht = IHT::new(1024);
+/// let red_large = iht.tiles(8, &[0.5, 2.0], Some(&ints_from_categories(&["red", "large"])));
+/// let red_small = iht.tiles(8, &[0.5, 2.0], Some(&ints_from_categories(&["red", "small"])));
+/// assert!(red_large.iter().all(|tile| !red_small.contains(tile)));
+/// ```
+pub fn ints_from_categories<K: Hash>(categories: &[K]) -> Vec<isize> {
+    categories.iter().map(token).collect()
+}
+
+// flags packed into each int: 32 rather than `isize::BITS`, so that the ints are the same on every platform
+const FLAGS_PER_INT: usize = 32;
+
+/// Encode boolean features as ints, bit-packing 32 flags into each int (flag `i` is bit `i % 32` of int `i / 32`), so
+/// that a flag-heavy observation adds a coordinate per 32 flags rather than one per flag. Observations should always
+/// have the same number of flags: trailing `false` flags don't change the packed bits, only the number of ints.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{ints_from_bools, IHT};
+/// let flags = [true, false, true, true];
+/// assert_eq!(ints_from_bools(&flags), vec![0b1101]);
+///
+/// let mut iht = IHT::new(1024);
+/// let door_open = iht.tiles(8, &[0.5], Some(&ints_from_bools(&flags)));
+/// let door_closed = iht.tiles(8, &[0.5], Some(&ints_from_bools(&[true, false, false, true])));
+/// assert!(door_open.iter().all(|tile| !door_closed.contains(tile)));
+/// ```
+pub fn ints_from_bools(flags: &[bool]) -> Vec<isize> {
+    flags
+        .chunks(FLAGS_PER_INT)
+        .map(|chunk| chunk.iter().enumerate().fold(0u32, |bits, (i, &flag)| bits | (flag as u32) << i) as isize)
+        .collect()
+}
+
+impl IHT {
+    /// The same as `tiles`, with any hashable value in place of the ints, see [`token`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// let left = iht.tiles_keyed(8, &[3.6, 7.21], &("left", 0u8));
+    /// let right = iht.tiles_keyed(8, &[3.6, 7.21], &("right", 0u8));
+    /// assert!(left.iter().all(|tile| !right.contains(tile)));
+    /// ```
+    pub fn tiles_keyed<K: Hash + ?Sized>(&mut self, num_tilings: usize, floats: &[f64], key: &K) -> Vec<usize> {
+        self.tiles(num_tilings, floats, Some(&[token(key)]))
+    }
+
+    /// The read-only version of `tiles_keyed`
+    pub fn tiles_read_only_keyed<K: Hash + ?Sized>(&self, num_tilings: usize, floats: &[f64], key: &K) -> Vec<Option<usize>> {
+        self.tiles_read_only(num_tilings, floats, Some(&[token(key)]))
+    }
+
+    /// The wrap-around version of `tiles_keyed`
+    pub fn tiles_wrap_keyed<K: Hash + ?Sized>(&mut self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], key: &K) -> Vec<usize> {
+        self.tiles_wrap(num_tilings, floats, wrap_widths, Some(&[token(key)]))
+    }
+}
+
+impl TileCoder {
+    /// Tile a raw point, with any hashable value in place of the ints, see [`token`]
+    pub fn tiles_keyed<K: Hash + ?Sized>(&mut self, floats: &[f64], key: &K) -> Vec<usize> {
+        self.tiles(floats, Some(&[token(key)]))
+    }
+
+    /// The read-only version of `tiles_keyed`
+    pub fn tiles_read_only_keyed<K: Hash + ?Sized>(&self, floats: &[f64], key: &K) -> Vec<Option<usize>> {
+        self.tiles_read_only(floats, Some(&[token(key)]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_stable() {
+        // pinned, so that a change to the hashing (which would invalidate saved IHTs) fails loudly
+        assert_eq!(token(&0u64), 0xa8c7_f832_281a_39c5u64 as isize);
+        assert_eq!(token(&3u8), token(&3u64));
+        assert_eq!(token(&-1i32), token(&-1isize));
+        assert_ne!(token(&(1, 2)), token(&(2, 1)));
+        assert_eq!(ints_from_categories(&["red", "large"]), vec![token("red"), token("large")]);
+    }
+
+    #[test]
+    fn bools_pack_32_to_an_int() {
+        let mut flags = vec![false; 70];
+        flags[0] = true;
+        flags[31] = true;
+        flags[33] = true;
+        flags[69] = true;
+        assert_eq!(ints_from_bools(&flags), vec![(1 << 31) + 1, 2, 1 << 5]);
+        assert!(ints_from_bools(&[]).is_empty());
+    }
+
+    #[test]
+    fn keyed_tiles_match_token_ints() {
+        let mut iht = IHT::new(256);
+        let keyed = iht.tiles_keyed(4, &[1.5], "agent-7");
+        assert_eq!(keyed, iht.tiles(4, &[1.5], Some(&[token("agent-7")])));
+        assert_eq!(iht.tiles_read_only_keyed(4, &[1.5], "agent-7"), keyed.into_iter().map(Some).collect::<Vec<_>>());
+    }
+}
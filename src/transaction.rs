@@ -0,0 +1,164 @@
+use crate::overflow::Staged;
+use crate::{calculate_coords, calculate_coords_wrap, calculate_q_floats, IHT};
+
+/// A batch of encodings staged against an IHT, which either all take effect (`commit`) or leave the IHT untouched
+/// (`rollback`, or simply dropping the transaction). See [`IHT::transaction`].
+///
+/// While staged, new tiles are given the indices they would receive if the batch were committed, so encodings
+/// within the transaction are consistent with one another.
+#[derive(Debug)]
+pub struct Transaction<'a> {
+    iht: &'a mut IHT,
+    staged: Staged,
+    overfull_count: usize,
+    visited: Vec<usize>,
+}
+
+impl IHT {
+    /// Start a transaction, so that a batch of encodings can be staged and then either committed or rolled back.
+    /// This is useful for speculative planning rollouts, which shouldn't permanently pollute the table.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// iht.tiles(4, &[0.0], None);
+    ///
+    /// let mut rollout = iht.transaction();
+    /// assert_eq!(rollout.tiles(4, &[10.0], None), vec![4, 5, 6, 7]);
+    /// rollout.rollback();
+    /// assert_eq!(iht.count(), 4);
+    ///
+    /// let mut batch = iht.transaction();
+    /// batch.tiles(4, &[10.0], None);
+    /// batch.commit();
+    /// assert_eq!(iht.tiles_read_only(4, &[10.0], None), vec![Some(4), Some(5), Some(6), Some(7)]);
+    /// ```
+    pub fn transaction(&mut self) -> Transaction<'_> {
+        Transaction {
+            iht: self,
+            staged: Staged::default(),
+            overfull_count: 0,
+            visited: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Transaction<'a> {
+    fn get_index(&mut self, obj: Vec<isize>) -> usize {
+        let index = if let Some(index) = self.iht.get_index_read_only_ref(&obj) {
+            index
+        } else {
+            let (index, overflowed) = self.staged.get_index(self.iht, &obj);
+            if overflowed {
+                self.overfull_count += 1;
+            }
+            index
+        };
+        self.visited.push(index);
+        index
+    }
+
+    /// The number of new tiles staged by the transaction
+    pub fn staged(&self) -> usize {
+        self.staged.tiles.len()
+    }
+
+    /// The transactional equivalent of [`IHT::tiles`]
+    pub fn tiles(&mut self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut tiles: Vec<usize> = Vec::with_capacity(num_tilings);
+
+        for tiling in 0..num_tilings {
+            let coords = calculate_coords(tiling, num_tilings, &q_floats, &ints);
+            tiles.push(self.get_index(coords));
+        }
+
+        tiles
+    }
+
+    /// The transactional equivalent of [`IHT::tiles_wrap`]
+    pub fn tiles_wrap(&mut self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<usize> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut tiles: Vec<usize> = Vec::with_capacity(num_tilings);
+
+        for tiling in 0..num_tilings {
+            let coords = calculate_coords_wrap(tiling, num_tilings, &q_floats, wrap_widths, &ints);
+            tiles.push(self.get_index(coords));
+        }
+
+        tiles
+    }
+
+    /// Apply every staged encoding to the IHT, assigning the new tiles the indices they were given in the transaction
+    pub fn commit(self) {
+        let Transaction {
+            iht,
+            staged,
+            overfull_count,
+            visited,
+        } = self;
+        for coords in staged.tiles {
+            let index = iht.dictionary.len();
+            iht.dictionary.insert(coords, index);
+        }
+        iht.overfull_count += overfull_count;
+        for index in visited {
+            iht.visit(index);
+        }
+    }
+
+    /// Discard every staged encoding, leaving the IHT exactly as it was
+    pub fn rollback(self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Overflow;
+
+    #[test]
+    fn commit_matches_direct_encoding() {
+        let mut direct = IHT::new(16);
+        let mut transactional = direct.clone();
+        let points = [0.0, 2.0, 0.5, 7.0, 9.0];
+
+        let expected: Vec<Vec<usize>> = points.iter().map(|&x| direct.tiles(4, &[x], None)).collect();
+        let mut tx = transactional.transaction();
+        let staged: Vec<Vec<usize>> = points.iter().map(|&x| tx.tiles(4, &[x], None)).collect();
+        tx.commit();
+
+        assert_eq!(staged, expected);
+        assert_eq!(transactional, direct);
+    }
+
+    #[test]
+    fn staged_overflow_matches_committed_double_hashing() {
+        let mut iht = IHT::new(16).with_overflow(Overflow::DoubleHash);
+        iht.tiles(8, &[0.0], None);
+        let mut tx = iht.transaction();
+        tx.tiles(8, &[10.0], None);
+        let staged = tx.tiles(8, &[50.0], None);
+        tx.commit();
+        assert_eq!(iht.tiles(8, &[50.0], None), staged);
+        for (tiling, &index) in staged.iter().enumerate() {
+            assert_eq!(iht.coords_for(index).unwrap()[0], tiling as isize);
+        }
+    }
+
+    #[test]
+    fn rollback_leaves_table_untouched() {
+        let mut iht = IHT::new(8);
+        iht.tiles_wrap(4, &[0.0], &[Some(2)], None);
+        let before = iht.clone();
+
+        let mut tx = iht.transaction();
+        for i in 0..10 {
+            tx.tiles_wrap(4, &[i as f64], &[Some(20)], None);
+        }
+        assert_eq!(tx.staged(), 4);
+        drop(tx);
+        assert_eq!(iht, before);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::inline::InlineCoords;
+use crate::offsets::splitmix64;
+use crate::{calculate_coords_into, calculate_coords_wrap_into, calculate_q_floats};
+
+// the length of the table of random numbers, and the step between the table offsets of successive coordinates, both
+// as in Sutton's tiles.C
+const TABLE_LEN: usize = 2048;
+const INCREMENT: isize = 449;
+
+/// The UNH hashing of Sutton's original tile coding software (`hash_UNH` in tiles.C), as an alternative to SipHash
+/// for the non-IHT tiling functions. A table of 2048 random 32-bit numbers is generated up front; each coordinate
+/// selects an entry (offset by its position in the coordinates) and a tile's index is the sum of its entries
+/// modulo the size.
+///
+/// The original filled its table from C's `rand()`, so its indices depend on the platform; here the table comes from
+/// a seed, and the same seed always gives the same indices everywhere.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::Unh;
+/// let unh = Unh::new(42);
+/// let indices = unh.tiles(1024, 8, &[3.6, 7.21], None);
+/// assert!(indices.iter().all(|&i| i < 1024));
+///
+/// // reproducible from the seed
+/// assert_eq!(Unh::new(42).tiles(1024, 8, &[3.6, 7.21], None), indices);
+/// assert_ne!(Unh::new(43).tiles(1024, 8, &[3.6, 7.21], None), indices);
+/// ```
+#[derive(Clone)]
+pub struct Unh {
+    table: Box<[u32; TABLE_LEN]>,
+}
+
+impl std::fmt::Debug for Unh {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Unh").finish_non_exhaustive()
+    }
+}
+
+impl Unh {
+    /// Generate the table of random numbers from a seed
+    pub fn new(seed: u64) -> Unh {
+        let mut table = Box::new([0; TABLE_LEN]);
+        for (k, entry) in table.iter_mut().enumerate() {
+            *entry = (splitmix64(seed ^ splitmix64(k as u64)) >> 32) as u32;
+        }
+        Unh { table }
+    }
+
+    /// Hash some coordinates to an index `< size`, exactly as `hash_UNH` does with an increment of 449
+    pub fn hash(&self, coords: &[isize], size: usize) -> usize {
+        let sum: u64 = coords
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| self.table[(c.wrapping_add(INCREMENT.wrapping_mul(i as isize))).rem_euclid(TABLE_LEN as isize) as usize] as u64)
+            .fold(0, u64::wrapping_add);
+        (sum % size as u64) as usize
+    }
+
+    /// The same as the free `tiles` function, but hashing each tile with UNH
+    pub fn tiles(&self, size: usize, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut coords = InlineCoords::new();
+        (0..num_tilings)
+            .map(|tiling| {
+                calculate_coords_into(&mut coords, tiling, num_tilings, &q_floats, &ints);
+                self.hash(&coords, size)
+            })
+            .collect()
+    }
+
+    /// The same as the free `tiles_wrap` function, but hashing each tile with UNH
+    pub fn tiles_wrap(&self, size: usize, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<usize> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut coords = InlineCoords::new();
+        (0..num_tilings)
+            .map(|tiling| {
+                calculate_coords_wrap_into(&mut coords, tiling, num_tilings, &q_floats, wrap_widths, &ints);
+                self.hash(&coords, size)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_table_entries_like_tiles_c() {
+        let unh = Unh::new(7);
+        let t = &unh.table;
+        // coordinate i selects entry (c + 449 i) mod 2048, wrapping negative coordinates around
+        let expected = (t[5] as u64 + t[449 - 3] as u64 + t[(2048 - 1 + 898) % 2048] as u64) % 1000;
+        assert_eq!(unh.hash(&[5, -3, -1], 1000), expected as usize);
+        assert_eq!(unh.hash(&[5 + 2048, -3 - 4096, -1], 1000), expected as usize);
+
+        // spreads tiles evenly over a small table
+        let mut counts = [0; 16];
+        for i in 0..1600 {
+            for index in unh.tiles(16, 4, &[i as f64 * 0.3], Some(&[i % 3])) {
+                counts[index] += 1;
+            }
+        }
+        assert!(counts.iter().all(|&c| c > 300 && c < 500));
+        assert_eq!(unh.tiles_wrap(16, 4, &[0.5], &[Some(3)], None), unh.tiles_wrap(16, 4, &[3.5], &[Some(3)], None));
+    }
+}
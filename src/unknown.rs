@@ -0,0 +1,91 @@
+use crate::{FrozenIHT, TileCoder, IHT};
+
+fn or_unknown(tiles: Vec<Option<usize>>, unknown: usize) -> Vec<usize> {
+    tiles.into_iter().map(|tile| tile.unwrap_or(unknown)).collect()
+}
+
+impl IHT {
+    /// The index reserved for tiles that aren't in the table, used by the `*_or_unknown` read-only functions. It is
+    /// equal to the size of the IHT, so a weight vector of `size + 1` elements has a dedicated weight for it.
+    pub fn unknown_index(&self) -> usize {
+        self.size
+    }
+
+    /// The same as `tiles_read_only`, except that unseen tiles are mapped to [`IHT::unknown_index`] instead of
+    /// `None`, so that deployed policies degrade gracefully off of the training distribution.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// iht.tiles(4, &[0.0], None);
+    ///
+    /// let mut weights = vec![0.0; iht.size() + 1];
+    /// weights[iht.unknown_index()] = -1.0;
+    ///
+    /// let indices = iht.tiles_or_unknown(4, &[0.5], None);
+    /// assert_eq!(indices, vec![0, 1, 1024, 1024]);
+    /// let value: f64 = indices.iter().map(|&i| weights[i]).sum();
+    /// assert_eq!(value, -2.0);
+    /// ```
+    pub fn tiles_or_unknown(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        or_unknown(self.tiles_read_only(num_tilings, floats, ints), self.unknown_index())
+    }
+
+    /// The wrap-around version of `tiles_or_unknown`
+    pub fn tiles_wrap_or_unknown(&self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<usize> {
+        or_unknown(self.tiles_wrap_read_only(num_tilings, floats, wrap_widths, ints), self.unknown_index())
+    }
+}
+
+impl<'a> FrozenIHT<'a> {
+    /// The index reserved for tiles that aren't in the table, equal to the size of the frozen IHT. See
+    /// [`IHT::unknown_index`].
+    pub fn unknown_index(&self) -> usize {
+        self.size()
+    }
+
+    /// The same as `tiles`, except that unseen tiles are mapped to [`FrozenIHT::unknown_index`]
+    pub fn tiles_or_unknown(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        or_unknown(self.tiles(num_tilings, floats, ints), self.unknown_index())
+    }
+
+    /// The same as `tiles_wrap`, except that unseen tiles are mapped to [`FrozenIHT::unknown_index`]
+    pub fn tiles_wrap_or_unknown(&self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<usize> {
+        or_unknown(self.tiles_wrap(num_tilings, floats, wrap_widths, ints), self.unknown_index())
+    }
+}
+
+impl TileCoder {
+    /// The same as `tiles_read_only`, except that unseen tiles are mapped to [`IHT::unknown_index`]
+    pub fn tiles_or_unknown(&self, floats: &[f64], ints: Option<&[isize]>) -> Vec<usize> {
+        or_unknown(self.tiles_read_only(floats, ints), self.iht().unknown_index())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dim;
+
+    #[test]
+    fn seen_tiles_are_unchanged() {
+        let mut iht = IHT::new(64);
+        let indices = iht.tiles_wrap(8, &[2.5], &[Some(4)], Some(&[1]));
+        assert_eq!(iht.tiles_wrap_or_unknown(8, &[2.5], &[Some(4)], Some(&[1])), indices);
+        assert_eq!(iht.tiles_wrap_or_unknown(8, &[2.5], &[Some(4)], Some(&[2])), vec![64; 8]);
+    }
+
+    #[test]
+    fn frozen_and_coder_use_reserved_index() {
+        let mut coder = TileCoder::new(4, 32, vec![Dim::width(1.0)]);
+        coder.tiles(&[0.0], None);
+        assert_eq!(coder.tiles_or_unknown(&[100.0], None), vec![32; 4]);
+
+        let bytes = coder.iht().freeze();
+        let frozen = FrozenIHT::from_bytes(&bytes).unwrap();
+        assert_eq!(frozen.tiles_or_unknown(4, &[0.0], None), vec![0, 1, 2, 3]);
+        assert_eq!(frozen.tiles_or_unknown(4, &[100.0], None), vec![32; 4]);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{tiles, tiles_wrap, Error, IHT};
+
+// scaled floats beyond this would overflow the coordinate arithmetic once the tiling offsets are added
+const MAX_SCALED: f64 = (1u64 << 62) as f64;
+
+fn check_size(size: usize) -> Result<(), Error> {
+    if size == 0 {
+        return Err(Error::Input("size must be at least 1".to_string()));
+    }
+    Ok(())
+}
+
+fn check(num_tilings: usize, floats: &[f64]) -> Result<(), Error> {
+    if num_tilings == 0 {
+        return Err(Error::Input("num_tilings must be at least 1".to_string()));
+    }
+    for (i, &x) in floats.iter().enumerate() {
+        if !x.is_finite() {
+            return Err(Error::Input(format!("floats[{}] is {}", i, x)));
+        }
+        if (x * num_tilings as f64).abs() >= MAX_SCALED {
+            return Err(Error::Input(format!("floats[{}] = {} is too large to tile", i, x)));
+        }
+    }
+    Ok(())
+}
+
+fn check_wrap(floats: &[f64], wrap_widths: &[Option<isize>]) -> Result<(), Error> {
+    if wrap_widths.len() != floats.len() {
+        return Err(Error::Input(format!("{} wrap widths were given for {} floats", wrap_widths.len(), floats.len())));
+    }
+    if let Some(i) = wrap_widths.iter().position(|w| matches!(w, Some(w) if *w <= 0)) {
+        return Err(Error::Input(format!("wrap_widths[{}] must be positive", i)));
+    }
+    Ok(())
+}
+
+/// The validated version of [`tiles`]: the same indices, or an `Error::Input` describing what is wrong with the
+/// arguments instead of garbage indices or a panic. NaN or infinite floats, floats too large to tile, no tilings, and
+/// a size of zero are all rejected. `tiles` remains the fast path for inputs which are known to be valid.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{tiles, try_tiles, Error};
+/// assert_eq!(try_tiles(1024, 8, &[3.6, 7.21], None), Ok(tiles(1024, 8, &[3.6, 7.21], None)));
+/// assert_eq!(try_tiles(1024, 8, &[f64::NAN], None), Err(Error::Input("floats[0] is NaN".to_string())));
+/// assert!(try_tiles(1024, 0, &[3.6], None).is_err());
+/// ```
+pub fn try_tiles(size: usize, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Result<Vec<usize>, Error> {
+    check_size(size)?;
+    check(num_tilings, floats)?;
+    Ok(tiles(size, num_tilings, floats, ints))
+}
+
+/// The validated version of [`tiles_wrap`], see [`try_tiles`]. There must be exactly one wrap width per float, and
+/// each width must be positive.
+pub fn try_tiles_wrap(size: usize, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Result<Vec<usize>, Error> {
+    check_size(size)?;
+    check(num_tilings, floats)?;
+    check_wrap(floats, wrap_widths)?;
+    Ok(tiles_wrap(size, num_tilings, floats, wrap_widths, ints))
+}
+
+impl IHT {
+    /// The validated version of [`IHT::tiles`], see [`try_tiles`]. Nothing is added to the IHT if the arguments are
+    /// invalid.
+    pub fn try_tiles(&mut self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Result<Vec<usize>, Error> {
+        check_size(self.size)?;
+        check(num_tilings, floats)?;
+        Ok(self.tiles(num_tilings, floats, ints))
+    }
+
+    /// The validated version of [`IHT::tiles_read_only`]
+    pub fn try_tiles_read_only(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Result<Vec<Option<usize>>, Error> {
+        check(num_tilings, floats)?;
+        Ok(self.tiles_read_only(num_tilings, floats, ints))
+    }
+
+    /// The validated version of [`IHT::tiles_wrap`], see [`try_tiles_wrap`]
+    pub fn try_tiles_wrap(&mut self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Result<Vec<usize>, Error> {
+        check_size(self.size)?;
+        check(num_tilings, floats)?;
+        check_wrap(floats, wrap_widths)?;
+        Ok(self.tiles_wrap(num_tilings, floats, wrap_widths, ints))
+    }
+
+    /// The validated version of [`IHT::tiles_wrap_read_only`]
+    pub fn try_tiles_wrap_read_only(&self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Result<Vec<Option<usize>>, Error> {
+        check(num_tilings, floats)?;
+        check_wrap(floats, wrap_widths)?;
+        Ok(self.tiles_wrap_read_only(num_tilings, floats, wrap_widths, ints))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        let input = |msg: &str| Err(Error::Input(msg.to_string()));
+        assert_eq!(try_tiles(0, 8, &[1.0], None), input("size must be at least 1"));
+        assert_eq!(try_tiles(64, 8, &[1.0, f64::NEG_INFINITY], None), input("floats[1] is -inf"));
+        assert!(matches!(try_tiles(64, 8, &[1e300], None), Err(Error::Input(msg)) if msg.ends_with("is too large to tile")));
+        assert_eq!(try_tiles_wrap(64, 8, &[1.0, 2.0], &[Some(4)], None), input("1 wrap widths were given for 2 floats"));
+        assert_eq!(try_tiles_wrap(64, 8, &[1.0], &[Some(0)], None), input("wrap_widths[0] must be positive"));
+
+        let mut iht = IHT::new(64);
+        assert!(iht.try_tiles(0, &[1.0], None).is_err());
+        assert!(iht.try_tiles_wrap(4, &[f64::NAN], &[None], None).is_err());
+        assert_eq!(iht.count(), 0);
+        assert_eq!(iht.try_tiles_wrap(4, &[1.0], &[Some(3)], None), Ok(iht.clone().tiles_wrap(4, &[1.0], &[Some(3)], None)));
+        assert_eq!(iht.try_tiles_read_only(4, &[5.0], None), Ok(vec![None; 4]));
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+// traces which decay below this are dropped, so that only the recently active tiles are stored
+const MIN_TRACE: f64 = 1e-4;
+
+/// A linear value-function approximator over tile features: the value of a point is the sum of the weights of its
+/// active tiles, and updates are semi-gradient steps over just those tiles.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{IHT, LinearVFA};
+/// let mut iht = IHT::new(1024);
+/// let mut vfa = LinearVFA::new(iht.size());
+///
+/// let tiles = iht.tiles(8, &[3.6, 7.21], None);
+/// for _ in 0..20 {
+///     vfa.update(&tiles, 5.0, 0.5);
+/// }
+/// assert!((vfa.predict(&tiles) - 5.0).abs() < 1e-3);
+///
+/// // nearby points generalize from what was learned
+/// let nearby = iht.tiles(8, &[3.7, 7.21], None);
+/// assert!(vfa.predict(&nearby) > 3.0);
+/// ```
+///
+/// For TD(λ), the approximator also keeps a sparse eligibility trace for each recently active tile. Traces are
+/// either accumulating (`accumulate`) or replacing (`set_active`), are decayed with `decay`, and are used by
+/// `update_traced`:
+///
+/// ```
+/// # use tilecoding::{IHT, LinearVFA};
+/// # let mut iht = IHT::new(1024);
+/// let (gamma, lambda, alpha) = (0.99, 0.9, 0.5 / 8.0);
+/// let mut vfa = LinearVFA::new(iht.size());
+///
+/// let states = [0.0, 1.0, 2.0, 3.0];
+/// for step in 0..3 {
+///     let tiles = iht.tiles(8, &[states[step]], None);
+///     let next = iht.tiles(8, &[states[step + 1]], None);
+///     let reward = if step == 2 { 1.0 } else { 0.0 };
+///     let next_value = if step == 2 { 0.0 } else { vfa.predict(&next) };
+///
+///     vfa.decay(gamma * lambda);
+///     vfa.set_active(&tiles);
+///     let error = reward + gamma * next_value - vfa.predict(&tiles);
+///     vfa.update_traced(error, alpha);
+/// }
+/// // the reward at the end of the episode was credited to the first state too
+/// assert!(vfa.predict(&iht.tiles(8, &[0.0], None)) > 0.0);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearVFA {
+    weights: Vec<f64>,
+    traces: HashMap<usize, f64>,
+}
+
+impl LinearVFA {
+    /// Create an approximator with a zero weight for each tile index `< size`. To give the reserved
+    /// [unknown index](crate::IHT::unknown_index) a weight too, use a size of `iht.size() + 1`.
+    pub fn new(size: usize) -> LinearVFA {
+        LinearVFA {
+            weights: vec![0.0; size],
+            traces: HashMap::new(),
+        }
+    }
+
+    /// The number of tile indices the approximator has weights for
+    pub fn size(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// The weight of every tile index
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// Mutable access to the weight of every tile index, e.g. to initialize them optimistically
+    pub fn weights_mut(&mut self) -> &mut [f64] {
+        &mut self.weights
+    }
+
+    /// The approximate value of a point with the given active tiles
+    pub fn predict(&self, tiles: &[usize]) -> f64 {
+        tiles.iter().map(|&tile| self.weights[tile]).sum()
+    }
+
+    /// Take a semi-gradient step moving the value of the active tiles towards `target`, returning the error
+    /// (`target - prediction`) before the update. `alpha` is the step size, which is divided by the number of active
+    /// tiles, so that `alpha = 1` jumps straight to the target.
+    pub fn update(&mut self, tiles: &[usize], target: f64, alpha: f64) -> f64 {
+        let error = target - self.predict(tiles);
+        if !tiles.is_empty() {
+            let step = alpha * error / tiles.len() as f64;
+            for &tile in tiles {
+                self.weights[tile] += step;
+            }
+        }
+        error
+    }
+
+    /// The eligibility trace of a tile index (zero if it hasn't been active recently)
+    pub fn trace(&self, tile: usize) -> f64 {
+        self.traces.get(&tile).copied().unwrap_or(0.0)
+    }
+
+    /// The number of tiles with a non-zero trace
+    pub fn active_traces(&self) -> usize {
+        self.traces.len()
+    }
+
+    /// Multiply every trace by `gamma_lambda`, dropping any that become negligible
+    pub fn decay(&mut self, gamma_lambda: f64) {
+        for trace in self.traces.values_mut() {
+            *trace *= gamma_lambda;
+        }
+        self.traces.retain(|_, trace| trace.abs() >= MIN_TRACE);
+    }
+
+    /// Accumulating traces: add one to the trace of each active tile
+    pub fn accumulate(&mut self, tiles: &[usize]) {
+        for &tile in tiles {
+            *self.traces.entry(tile).or_insert(0.0) += 1.0;
+        }
+    }
+
+    /// Replacing traces: set the trace of each active tile to one
+    pub fn set_active(&mut self, tiles: &[usize]) {
+        for &tile in tiles {
+            self.traces.insert(tile, 1.0);
+        }
+    }
+
+    /// Dutch traces, as used by true online TD(λ): decay every trace by `gamma_lambda` and then add
+    /// `1 - alpha * gamma_lambda * z·x` to the trace of each active tile, where `z·x` is the sum of their traces
+    /// before the decay
+    pub fn dutch(&mut self, tiles: &[usize], gamma_lambda: f64, alpha: f64) {
+        let z_dot_x: f64 = tiles.iter().map(|&tile| self.trace(tile)).sum();
+        self.decay(gamma_lambda);
+        let increment = 1.0 - alpha * gamma_lambda * z_dot_x;
+        for &tile in tiles {
+            *self.traces.entry(tile).or_insert(0.0) += increment;
+        }
+    }
+
+    /// Reset every trace to zero, e.g. at the end of an episode
+    pub fn clear_traces(&mut self) {
+        self.traces.clear();
+    }
+
+    /// Move every weight along its trace by `alpha * error`. Unlike `update`, `alpha` isn't divided by the number of
+    /// active tiles, since the traces span several points; `alpha / num_tilings` is the usual choice.
+    pub fn update_traced(&mut self, error: f64, alpha: f64) {
+        for (&tile, &trace) in self.traces.iter() {
+            self.weights[tile] += alpha * error * trace;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IHT;
+
+    #[test]
+    fn full_step_reaches_target() {
+        let mut vfa = LinearVFA::new(16);
+        assert_eq!(vfa.update(&[1, 2, 3, 4], 2.0, 1.0), 2.0);
+        assert!((vfa.predict(&[1, 2, 3, 4]) - 2.0).abs() < 1e-12);
+        assert_eq!(vfa.weights()[0], 0.0);
+        assert!(vfa.update(&[1, 2, 3, 4], 2.0, 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn trace_kinds() {
+        let mut vfa = LinearVFA::new(8);
+        vfa.accumulate(&[1, 2]);
+        vfa.accumulate(&[2, 3]);
+        assert_eq!((vfa.trace(1), vfa.trace(2), vfa.trace(3)), (1.0, 2.0, 1.0));
+        vfa.set_active(&[2]);
+        assert_eq!(vfa.trace(2), 1.0);
+
+        vfa.decay(0.5);
+        assert_eq!(vfa.trace(1), 0.5);
+        vfa.update_traced(2.0, 0.1);
+        assert!((vfa.weights()[1] - 0.1).abs() < 1e-12);
+        assert_eq!(vfa.weights()[0], 0.0);
+
+        for _ in 0..20 {
+            vfa.decay(0.5);
+        }
+        assert_eq!(vfa.active_traces(), 0);
+        vfa.dutch(&[4, 5], 0.5, 0.1);
+        vfa.dutch(&[5, 6], 0.5, 0.1);
+        assert_eq!(vfa.trace(4), 0.5);
+        assert!((vfa.trace(5) - 1.45).abs() < 1e-12 && (vfa.trace(6) - 0.95).abs() < 1e-12);
+        vfa.clear_traces();
+        assert_eq!(vfa.trace(4), 0.0);
+    }
+
+    #[test]
+    fn fits_a_function() {
+        let mut iht = IHT::new(4096);
+        let mut vfa = LinearVFA::new(iht.size());
+        let f = |x: f64| (x * 2.0).sin();
+        for i in 0..20_000 {
+            let x = (i * 7919 % 1000) as f64 / 1000.0 * 6.0;
+            let tiles = iht.tiles(8, &[x * 2.0], None);
+            vfa.update(&tiles, f(x), 0.1);
+        }
+        for i in 0..60 {
+            let x = i as f64 * 0.1;
+            let tiles = iht.tiles(8, &[x * 2.0], None);
+            assert!((vfa.predict(&tiles) - f(x)).abs() < 0.1);
+        }
+    }
+}
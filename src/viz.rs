@@ -0,0 +1,119 @@
+use std::fmt::Write;
+
+use crate::TileCoder;
+
+const SIZE: f64 = 600.0;
+
+// the raw values in `[lo, hi]` at which the coordinate of dimension `dim` changes in each tiling. Scaled values are
+// quantized to `1 / num_tilings` of a tile, so the coordinates are compared between consecutive quanta.
+fn boundaries(coder: &TileCoder, dim: usize, (lo, hi): (f64, f64), at: [f64; 2]) -> Vec<Vec<f64>> {
+    let d = &coder.dims()[dim];
+    let n = coder.num_tilings() as f64;
+    let (start, end) = ((d.scale(lo) * n).floor() as isize, (d.scale(hi) * n).ceil() as isize);
+    let mut point = at;
+    let mut coords_at = |quantum: isize| {
+        point[dim] = d.unscale((quantum as f64 + 0.5) / n);
+        coder.coords(&point, None)
+    };
+    let mut lines = vec![Vec::new(); coder.num_tilings()];
+    let mut previous = coords_at(start);
+    for quantum in start + 1..=end {
+        let coords = coords_at(quantum);
+        let x = d.unscale(quantum as f64 / n);
+        for (tiling, line) in lines.iter_mut().enumerate() {
+            if coords[tiling][1 + dim] != previous[tiling][1 + dim] && x > lo && x < hi {
+                line.push(x);
+            }
+        }
+        previous = coords;
+    }
+    lines
+}
+
+// the boundaries on either side of `x`, clipped to the range
+fn enclosing(lines: &[f64], x: f64, (lo, hi): (f64, f64)) -> (f64, f64) {
+    let below = lines.iter().copied().filter(|&b| b <= x).fold(lo, f64::max);
+    let above = lines.iter().copied().filter(|&b| b > x).fold(hi, f64::min);
+    (below, above)
+}
+
+impl TileCoder {
+    /// Render the tilings of a coder with two float dimensions over the given raw ranges as an SVG image: each
+    /// tiling's grid is drawn in its own colour, and if a point is given, it is marked along with the tile it
+    /// activates in every tiling. This is the quickest way to see how wide the generalization of a coder really is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::{Dim, TileCoder};
+    /// let coder = TileCoder::new(4, 1024, vec![Dim::bins(0.0, 1.0, 4), Dim::width(0.5)]);
+    /// let svg = coder.tilings_svg([(0.0, 1.0), (-1.0, 1.0)], Some([0.3, 0.2]));
+    /// assert!(svg.starts_with("<svg"));
+    /// // an active tile for each tiling
+    /// assert_eq!(svg.matches("<rect class=\"active\"").count(), 4);
+    /// # std::fs::write(std::env::temp_dir().join("tilings.svg"), svg).unwrap();
+    /// ```
+    pub fn tilings_svg(&self, ranges: [(f64, f64); 2], point: Option<[f64; 2]>) -> String {
+        assert_eq!(self.dims().len(), 2, "only coders with two float dimensions can be drawn");
+        assert!(ranges.iter().all(|&(lo, hi)| lo < hi), "ranges must have min < max");
+        let at = point.unwrap_or([ranges[0].0, ranges[1].0]);
+        let lines = [boundaries(self, 0, ranges[0], at), boundaries(self, 1, ranges[1], at)];
+        let px = |x: f64| (x - ranges[0].0) / (ranges[0].1 - ranges[0].0) * SIZE;
+        // svg's y axis points down
+        let py = |y: f64| (ranges[1].1 - y) / (ranges[1].1 - ranges[1].0) * SIZE;
+
+        let mut svg = String::new();
+        let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#, SIZE);
+        let _ = writeln!(svg, r#"<rect width="{0}" height="{0}" fill="white" stroke="black"/>"#, SIZE);
+        for (tiling, (xs, ys)) in lines[0].iter().zip(lines[1].iter()).enumerate() {
+            let colour = format!("hsl({:.0}, 70%, 45%)", 360.0 * tiling as f64 / self.num_tilings() as f64);
+            if let Some(p) = point {
+                let (x0, x1) = enclosing(xs, p[0], ranges[0]);
+                let (y0, y1) = enclosing(ys, p[1], ranges[1]);
+                let _ = writeln!(
+                    svg,
+                    r#"<rect class="active" x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}" fill-opacity="0.15"/>"#,
+                    px(x0),
+                    py(y1),
+                    px(x1) - px(x0),
+                    py(y0) - py(y1),
+                    colour
+                );
+            }
+            let _ = writeln!(svg, r#"<g class="tiling" stroke="{}" stroke-opacity="0.7">"#, colour);
+            for &x in xs {
+                let _ = writeln!(svg, r#"<line x1="{0:.2}" y1="0" x2="{0:.2}" y2="{1}"/>"#, px(x), SIZE);
+            }
+            for &y in ys {
+                let _ = writeln!(svg, r#"<line x1="0" y1="{0:.2}" x2="{1}" y2="{0:.2}"/>"#, py(y), SIZE);
+            }
+            svg.push_str("</g>\n");
+        }
+        if let Some(p) = point {
+            let _ = writeln!(svg, r#"<circle cx="{:.2}" cy="{:.2}" r="4" fill="black"/>"#, px(p[0]), py(p[1]));
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dim;
+
+    #[test]
+    fn boundaries_follow_the_offsets() {
+        let coder = TileCoder::new(4, 1024, vec![Dim::bins(0.0, 4.0, 4), Dim::bins(0.0, 4.0, 4)]);
+        let lines = boundaries(&coder, 0, (0.0, 4.0), [0.0, 0.0]);
+        // tiling 0 has tiles on the integers, and each later tiling is shifted down by a quarter of a tile
+        assert_eq!(lines[0], vec![1.0, 2.0, 3.0]);
+        assert_eq!(lines[1], vec![0.75, 1.75, 2.75, 3.75]);
+        // the second dimension is displaced three times as far
+        assert_eq!(boundaries(&coder, 1, (0.0, 4.0), [0.0, 0.0])[1], vec![0.25, 1.25, 2.25, 3.25]);
+
+        let svg = coder.tilings_svg([(0.0, 4.0), (0.0, 4.0)], Some([1.1, 2.9]));
+        assert_eq!(svg.matches("<line").count(), 3 + 3 + 4 * 6);
+        assert!(svg.contains(r#"<rect class="active" x="150.00" y="150.00" width="150.00" height="150.00""#));
+    }
+}
@@ -0,0 +1,335 @@
+use std::io::{self, Read, Write};
+
+use crate::tag::{Tag, PROBE};
+use crate::{calculate_coords_into, calculate_coords_wrap_into, calculate_q_floats, stable_hash, CoderConfig, Error, IHT};
+
+// each record is the number of coordinates (u32), the coordinates (i64s), the index (u64), and a checksum (u64), all
+// little-endian, so that a record torn by a crash is detected rather than replayed
+fn checksum(coords: &[isize], index: u64) -> u64 {
+    stable_hash(coords) ^ index.wrapping_mul(0x9e37_79b9_7f4a_7c15)
+}
+
+// a fingerprint of the hash the checksums are computed with
+fn checksum_fingerprint() -> u64 {
+    stable_hash(PROBE)
+}
+
+// every logger writes a header before its first record: this magic, then the tag as crate version, checksum hash
+// fingerprint, and config fingerprint. Read as a record's length, the start of the magic is far past `MAX_COORDS`, so
+// the two can't be confused.
+const TAG_MAGIC: &[u8; 8] = b"TCWALTAG";
+const TAG_LEN: usize = 8 + 3 * 8;
+
+fn encode_tag(config: u64) -> Vec<u8> {
+    let mut header = TAG_MAGIC.to_vec();
+    for word in Tag::new(checksum_fingerprint(), config).to_words().iter() {
+        header.extend_from_slice(&word.to_le_bytes());
+    }
+    header
+}
+
+fn encode_record(coords: &[isize], index: usize) -> Vec<u8> {
+    let mut record = Vec::with_capacity(20 + 8 * coords.len());
+    record.extend_from_slice(&(coords.len() as u32).to_le_bytes());
+    for &c in coords {
+        record.extend_from_slice(&(c as i64).to_le_bytes());
+    }
+    record.extend_from_slice(&(index as u64).to_le_bytes());
+    record.extend_from_slice(&checksum(coords, index as u64).to_le_bytes());
+    record
+}
+
+// fill `buf` from the reader, returning false if the reader ended before it was full
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => return Ok(false),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+fn u64_at(bytes: &[u8]) -> u64 {
+    let mut word = [0; 8];
+    word.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(word)
+}
+
+// no tile has anywhere near this many coordinates, so a longer record can only come from a corrupt length
+const MAX_COORDS: usize = 1 << 20;
+
+// whether the bytes after the length of an incomplete record at the end of a log start with a complete record of some
+// other length with the expected index and a valid checksum. If so, the record's length was corrupted and the bytes
+// read from the log after it are later records, rather than the record having been torn by a crash. The checksum is
+// folded in a coordinate at a time, so every length is tried in a single pass.
+fn holds_record(partial: &[u8], index: u64) -> bool {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut len = 0;
+    while 8 * len + 16 <= partial.len() {
+        let rest = &partial[8 * len..];
+        if u64_at(rest) == index && u64_at(&rest[8..]) == hash ^ index.wrapping_mul(0x9e37_79b9_7f4a_7c15) {
+            return true;
+        }
+        for &byte in &rest[..8] {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        len += 1;
+    }
+    false
+}
+
+/// An IHT which appends every new `(coordinates, index)` assignment to a write-ahead log, so that a long training run
+/// can persist the table incrementally and rebuild it with [`IHT::replay_log`] after a crash. Lookups of tiles which
+/// are already in the table don't touch the log.
+///
+/// Records reach the log as soon as the writer passes them on; wrap a file in a `BufWriter` for speed, and call
+/// [`LoggedIHT::flush`] at whatever points the table should be recoverable from. Before its first record, each
+/// `LoggedIHT` writes a header recording the version of this crate, which `replay_log` checks.
+///
+/// # Example
+///
+/// ```
+/// # use tilecoding::{LoggedIHT, IHT};
+/// let mut logged = LoggedIHT::new(IHT::new(1024), Vec::new());
+/// logged.tiles(8, &[3.6, 7.21], None).unwrap();
+/// logged.tiles(8, &[3.6, 7.21], None).unwrap();
+/// let (iht, log) = logged.into_parts();
+///
+/// // the log holds only the 8 new tiles, and rebuilds the same table
+/// let (replayed, valid) = IHT::replay_log(1024, &log[..]).unwrap();
+/// assert_eq!(valid, log.len() as u64);
+/// assert_eq!(replayed.entries_snapshot(), iht.entries_snapshot());
+/// ```
+#[derive(Debug)]
+pub struct LoggedIHT<W: Write> {
+    iht: IHT,
+    log: W,
+    // the header, until it has been written
+    header: Option<Vec<u8>>,
+}
+
+impl<W: Write> LoggedIHT<W> {
+    /// Log every new assignment made to `iht` to `log`. To resume a run, pass the table replayed from the log, and
+    /// the same log opened for appending (truncated to the valid length `replay_log` reported).
+    pub fn new(iht: IHT, log: W) -> LoggedIHT<W> {
+        LoggedIHT {
+            iht,
+            log,
+            header: Some(encode_tag(0)),
+        }
+    }
+
+    /// The same as [`LoggedIHT::new`], but also recording the [`CoderConfig`] the table is filled with, so that
+    /// [`IHT::replay_log_with_config`] can check the log is replayed for the same one
+    pub fn with_config(iht: IHT, log: W, config: &CoderConfig) -> LoggedIHT<W> {
+        LoggedIHT {
+            iht,
+            log,
+            header: Some(encode_tag(config.fingerprint())),
+        }
+    }
+
+    /// The underlying IHT
+    pub fn iht(&self) -> &IHT {
+        &self.iht
+    }
+
+    /// Flush the log, so that everything assigned so far can be replayed
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.log.flush()
+    }
+
+    /// Stop logging, returning the IHT and the log
+    pub fn into_parts(self) -> (IHT, W) {
+        (self.iht, self.log)
+    }
+
+    fn get_index(&mut self, coords: &[isize]) -> io::Result<usize> {
+        let count = self.iht.count();
+        let index = self.iht.get_index_ref(coords);
+        if self.iht.count() > count {
+            if let Some(header) = &self.header {
+                self.log.write_all(header)?;
+                self.header = None;
+            }
+            self.log.write_all(&encode_record(coords, index))?;
+        }
+        Ok(index)
+    }
+
+    /// Encode a point as tile indices, see [`IHT::tiles`], logging any new tiles
+    pub fn tiles(&mut self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> io::Result<Vec<usize>> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut tiles = Vec::with_capacity(num_tilings);
+        let mut coords = Vec::new();
+        for tiling in 0..num_tilings {
+            calculate_coords_into(&mut coords, tiling, num_tilings, &q_floats, &ints);
+            tiles.push(self.get_index(&coords)?);
+        }
+        Ok(tiles)
+    }
+
+    /// Encode a point as tile indices with wrapping, see [`IHT::tiles_wrap`], logging any new tiles
+    pub fn tiles_wrap(&mut self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> io::Result<Vec<usize>> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut tiles = Vec::with_capacity(num_tilings);
+        let mut coords = Vec::new();
+        for tiling in 0..num_tilings {
+            calculate_coords_wrap_into(&mut coords, tiling, num_tilings, &q_floats, wrap_widths, &ints);
+            tiles.push(self.get_index(&coords)?);
+        }
+        Ok(tiles)
+    }
+
+    /// The read-only version of `tiles`, which never logs anything, see [`IHT::tiles_read_only`]
+    pub fn tiles_read_only(&self, num_tilings: usize, floats: &[f64], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        self.iht.tiles_read_only(num_tilings, floats, ints)
+    }
+}
+
+impl IHT {
+    /// Rebuild an IHT of the given size by replaying a write-ahead log written by a [`LoggedIHT`]. Along with the
+    /// table, this returns the length in bytes of the complete records in the log: a record torn by a crash at the
+    /// very end of the log is ignored, and the log should be truncated to that length before appending to it again.
+    ///
+    /// A record that fails its checksum, that doesn't continue the sequence of indices, or whose length is corrupt, is
+    /// an `Error::Format`, as are records before the first header and a header written by a version of this crate with
+    /// a different major or minor version.
+    pub fn replay_log<R: Read>(size: usize, log: R) -> Result<(IHT, u64), Error> {
+        IHT::replay_tagged_log(size, log, None)
+    }
+
+    /// The same as [`IHT::replay_log`] for a log written by [`LoggedIHT::with_config`], failing with an
+    /// `Error::Format` unless every part of it was written for exactly the same configuration, ignoring the names of
+    /// the dimensions
+    pub fn replay_log_with_config<R: Read>(size: usize, log: R, config: &CoderConfig) -> Result<(IHT, u64), Error> {
+        IHT::replay_tagged_log(size, log, Some(config))
+    }
+
+    fn replay_tagged_log<R: Read>(size: usize, mut log: R, config: Option<&CoderConfig>) -> Result<(IHT, u64), Error> {
+        let io_error = |e: io::Error| Error::Format(format!("couldn't read log: {}", e));
+        let mut iht = IHT::new(size);
+        let mut valid = 0u64;
+        let mut tagged = false;
+        loop {
+            let mut len = [0; 4];
+            if !read_full(&mut log, &mut len).map_err(io_error)? {
+                break;
+            }
+            if len[..] == TAG_MAGIC[..4] {
+                let mut rest = [0; TAG_LEN - 4];
+                // a header torn by a crash, before any of its records were written
+                if !read_full(&mut log, &mut rest).map_err(io_error)? {
+                    break;
+                }
+                if rest[..4] != TAG_MAGIC[4..] {
+                    return Err(Error::Format(format!("log header at byte {} is corrupt", valid)));
+                }
+                let tag = Tag::from_words([u64_at(&rest[4..]), u64_at(&rest[12..]), u64_at(&rest[20..])]);
+                tag.check_version("log")?;
+                tag.check_hash("log", checksum_fingerprint())?;
+                if let Some(config) = config {
+                    tag.check_config("log", config)?;
+                }
+                tagged = true;
+                valid += TAG_LEN as u64;
+                continue;
+            }
+            if !tagged {
+                return Err(Error::Format(format!("log record at byte {} comes before any header", valid)));
+            }
+            let len = u32::from_le_bytes(len) as usize;
+            if len > MAX_COORDS {
+                return Err(Error::Format(format!("log record at byte {} has an impossible length {}", valid, len)));
+            }
+            // read as far as the log goes rather than allocating the whole record up front, so a corrupt length can't
+            // allocate more than the log holds
+            let mut body = Vec::new();
+            (&mut log).take(8 * len as u64 + 16).read_to_end(&mut body).map_err(io_error)?;
+            if body.len() < 8 * len + 16 {
+                if holds_record(&body, iht.count() as u64) {
+                    return Err(Error::Format(format!("log record at byte {} has a corrupt length", valid)));
+                }
+                break;
+            }
+            let coords: Vec<isize> = body[..8 * len].chunks_exact(8).map(|c| u64_at(c) as i64 as isize).collect();
+            let index = u64_at(&body[8 * len..]);
+            if u64_at(&body[8 * len + 8..]) != checksum(&coords, index) {
+                return Err(Error::Format(format!("log record at byte {} failed its checksum", valid)));
+            }
+            if index as usize != iht.count() || index as usize >= size {
+                return Err(Error::Format(format!("log record at byte {} has index {}, expected {}", valid, index, iht.count())));
+            }
+            iht.dictionary.insert(coords, index as usize);
+            valid += 4 + body.len() as u64;
+        }
+        Ok((iht, valid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_ignores_torn_tail_and_rejects_corruption() {
+        let mut logged = LoggedIHT::new(IHT::new(64), Vec::new());
+        for i in 0..10 {
+            logged.tiles(4, &[i as f64], Some(&[1])).unwrap();
+            logged.tiles_wrap(4, &[i as f64 * 0.3], &[Some(2)], None).unwrap();
+        }
+        let (iht, log) = logged.into_parts();
+        let (replayed, valid) = IHT::replay_log(64, &log[..]).unwrap();
+        assert_eq!((replayed.entries_snapshot(), valid), (iht.entries_snapshot(), log.len() as u64));
+
+        // a crash part way through writing the last record
+        let torn = &log[..log.len() - 5];
+        let (replayed, valid) = IHT::replay_log(64, torn).unwrap();
+        assert_eq!(replayed.count(), iht.count() - 1);
+        assert!(valid < torn.len() as u64);
+
+        // a corrupt length in a middle record, either enormous or running just past the end of the log
+        let second = TAG_LEN + 4 + 8 * 3 + 16;
+        let mut huge = log.clone();
+        huge[second + 3] = 0x7f;
+        assert!(IHT::replay_log(64, &huge[..]).is_err());
+        let mut longer = log.clone();
+        longer[second] += 20;
+        assert!(IHT::replay_log(64, &longer[..]).is_err());
+        longer.truncate(second + 4 + 8 * 20);
+        assert!(IHT::replay_log(64, &longer[..]).is_err());
+
+        let mut flipped = log.clone();
+        flipped[TAG_LEN + 6] ^= 1;
+        assert!(IHT::replay_log(64, &flipped[..]).is_err());
+
+        // a log without a header can't be replayed, but one resumed by another logger has a header for each part
+        assert!(IHT::replay_log(64, &log[TAG_LEN..]).is_err());
+        let mut resumed = LoggedIHT::new(iht, log);
+        resumed.tiles(4, &[-3.0], None).unwrap();
+        let (iht, log) = resumed.into_parts();
+        let (replayed, valid) = IHT::replay_log(64, &log[..]).unwrap();
+        assert_eq!((replayed.entries_snapshot(), valid), (iht.entries_snapshot(), log.len() as u64));
+        let mut other_minor = log.clone();
+        let word = u64_at(&log[8..]);
+        other_minor[8..16].copy_from_slice(&(word + (1 << 21)).to_le_bytes());
+        assert!(IHT::replay_log(64, &other_minor[..]).is_err());
+    }
+
+    #[test]
+    fn replay_checks_the_configuration() {
+        let config = CoderConfig::new(4, 64, vec![crate::DimensionConfig::new(0.0, 1.0, 4)]).unwrap();
+        let mut logged = LoggedIHT::with_config(IHT::new(64), Vec::new(), &config);
+        logged.tiles(4, &config.scale(&[0.5]), None).unwrap();
+        let (iht, log) = logged.into_parts();
+        assert_eq!(IHT::replay_log_with_config(64, &log[..], &config).unwrap().0.entries_snapshot(), iht.entries_snapshot());
+        let finer = CoderConfig::new(4, 64, vec![crate::DimensionConfig::new(0.0, 1.0, 8)]).unwrap();
+        assert!(IHT::replay_log_with_config(64, &log[..], &finer).is_err());
+        assert!(IHT::replay_log_with_config(64, &log[TAG_LEN..], &config).is_err());
+    }
+}
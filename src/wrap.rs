@@ -0,0 +1,108 @@
+use crate::inline::{CoordBuffer, InlineCoords};
+use crate::{base_hash, calculate_q_floats, IHT};
+
+// the coordinates with correct wrapping: the offset is applied exactly as in `calculate_coords`, but the division
+// floors and the wrap is a euclidean modulo, so negative inputs land on the same tiles as the equivalent positive ones
+fn calculate_coords_wrap_euclid_into<B: CoordBuffer>(
+    coords: &mut B,
+    tiling: usize,
+    num_tilings: usize,
+    q_floats: &[isize],
+    wrap_widths: &[Option<isize>],
+    ints: &Option<&[isize]>,
+) {
+    let n = num_tilings as isize;
+    coords.clear();
+    coords.push(tiling as isize);
+    let mut b = tiling as isize;
+    for (q, width) in q_floats.iter().zip(wrap_widths.iter()) {
+        let c = (q + b).div_euclid(n);
+        coords.push(match width {
+            Some(w) => c.rem_euclid(*w),
+            None => c,
+        });
+        b += tiling as isize * 2;
+    }
+    if let Some(ints) = ints {
+        coords.extend_from_slice(ints);
+    }
+}
+
+/// The corrected version of the free `tiles_wrap` function, see [`IHT::tiles_wrap_euclid`]
+pub fn tiles_wrap_euclid(size: usize, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<usize> {
+    let q_floats = calculate_q_floats(floats, num_tilings);
+    let mut tiles: Vec<usize> = Vec::with_capacity(num_tilings);
+
+    let mut coords = InlineCoords::new();
+    for tiling in 0..num_tilings {
+        calculate_coords_wrap_euclid_into(&mut coords, tiling, num_tilings, &q_floats, wrap_widths, &ints);
+        tiles.push(base_hash(&coords[..]) % size);
+    }
+
+    tiles
+}
+
+impl IHT {
+    /// A corrected version of `tiles_wrap`. The tiles3 wrapping math uses Rust's (and C's) remainder rather than a
+    /// modulo, so inputs below zero get negative, discontinuous coordinates, and an angle just below zero doesn't
+    /// generalize to the angles just below the wrap width. This version offsets the tilings exactly as `tiles` does
+    /// and then wraps with a euclidean modulo, so that `x` and `x + width` always share all of their tiles.
+    ///
+    /// The coordinates differ from those of `tiles_wrap`, so an IHT should be filled with one or the other.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tilecoding::IHT;
+    /// let mut iht = IHT::new(1024);
+    /// // an angle in tenths of a turn, just below zero and just below a full turn
+    /// let below_zero = iht.tiles_wrap_euclid(8, &[-0.05], &[Some(10)], None);
+    /// assert_eq!(iht.tiles_wrap_euclid(8, &[9.95], &[Some(10)], None), below_zero);
+    /// assert_ne!(iht.tiles_wrap(8, &[-0.05], &[Some(10)], None), iht.tiles_wrap(8, &[9.95], &[Some(10)], None));
+    /// ```
+    pub fn tiles_wrap_euclid(&mut self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<usize> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut tiles: Vec<usize> = Vec::with_capacity(num_tilings);
+
+        let mut coords = InlineCoords::new();
+        for tiling in 0..num_tilings {
+            calculate_coords_wrap_euclid_into(&mut coords, tiling, num_tilings, &q_floats, wrap_widths, &ints);
+            tiles.push(self.get_index_ref(&coords));
+        }
+
+        tiles
+    }
+
+    /// The read-only version of `tiles_wrap_euclid`
+    pub fn tiles_wrap_euclid_read_only(&self, num_tilings: usize, floats: &[f64], wrap_widths: &[Option<isize>], ints: Option<&[isize]>) -> Vec<Option<usize>> {
+        let q_floats = calculate_q_floats(floats, num_tilings);
+        let mut tiles: Vec<Option<usize>> = Vec::with_capacity(num_tilings);
+
+        let mut coords = InlineCoords::new();
+        for tiling in 0..num_tilings {
+            calculate_coords_wrap_euclid_into(&mut coords, tiling, num_tilings, &q_floats, wrap_widths, &ints);
+            tiles.push(self.get_index_read_only_ref(&coords));
+        }
+
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_continuously_through_zero() {
+        let mut iht = IHT::new(4096);
+        for i in -40..40 {
+            let x = i as f64 * 0.37;
+            let wrapped = iht.tiles_wrap_euclid(8, &[x, 1.5], &[Some(6), None], Some(&[2]));
+            assert_eq!(iht.tiles_wrap_euclid(8, &[x + 6.0, 1.5], &[Some(6), None], Some(&[2])), wrapped);
+            assert_eq!(iht.tiles_wrap_euclid_read_only(8, &[x - 12.0, 1.5], &[Some(6), None], Some(&[2])), wrapped.iter().copied().map(Some).collect::<Vec<_>>());
+            assert_eq!(tiles_wrap_euclid(4096, 8, &[x], &[Some(6)], None), tiles_wrap_euclid(4096, 8, &[x - 6.0], &[Some(6)], None));
+        }
+        // a wrapping dimension only ever has `width` tiles per tiling
+        assert_eq!(iht.count(), 8 * 6);
+    }
+}
@@ -0,0 +1,239 @@
+//! The `#[derive(TileCode)]` macro for the [tilecoding](https://crates.io/crates/tilecoding) crate, which is
+//! re-exported from there with the `derive` feature.
+
+extern crate proc_macro;
+
+use proc_macro::{Delimiter, Group, Spacing, TokenStream, TokenTree};
+use std::iter::Peekable;
+
+/// Implement `tilecoding::Tileable` for a struct with named fields. Every field is a float input (converted with
+/// `as f64`), in declaration order, unless it is marked otherwise with a `#[tile(...)]` attribute:
+///
+/// * `#[tile(scale = 2.5)]`—multiply the float by a factor first, e.g. to convert it to tile units
+/// * `#[tile(wrap = 10)]`—the float wraps around with the given (integer) width, in the same units as the scaled float
+/// * `#[tile(int)]`—the field is an int input instead (converted with `as isize`)
+/// * `#[tile(skip)]`—the field isn't an input at all
+///
+/// # Example
+///
+/// ```
+/// use tilecoding::{TileCode, IHT};
+///
+/// #[derive(TileCode)]
+/// struct Observation {
+///     #[tile(scale = 10.0)]
+///     position: f64,
+///     #[tile(scale = 1.5915494309189535, wrap = 10)]
+///     angle: f32,
+///     #[tile(int)]
+///     gear: u8,
+///     #[tile(skip)]
+///     label: String,
+/// }
+///
+/// let mut iht = IHT::new(1024);
+/// let observation = Observation { position: 0.36, angle: 0.5, gear: 2, label: "start".to_string() };
+/// let expected = iht.tiles_wrap(8, &[3.6, 0.5f32 as f64 * 1.5915494309189535], &[None, Some(10)], Some(&[2]));
+/// assert_eq!(iht.encode(8, &observation), expected);
+/// ```
+#[proc_macro_derive(TileCode, attributes(tile))]
+pub fn derive_tile_code(input: TokenStream) -> TokenStream {
+    match parse_struct(input) {
+        Ok((name, fields)) => generate(&name, &fields).parse().expect("generated code should parse"),
+        Err(message) => format!("compile_error!({:?});", message).parse().expect("compile_error should parse"),
+    }
+}
+
+enum Kind {
+    Float { scale: Option<String>, wrap: Option<String> },
+    Int,
+    Skip,
+}
+
+struct Field {
+    name: String,
+    kind: Kind,
+}
+
+type Tokens = Peekable<proc_macro::token_stream::IntoIter>;
+
+fn is_punct(token: Option<&TokenTree>, c: char) -> bool {
+    matches!(token, Some(TokenTree::Punct(p)) if p.as_char() == c)
+}
+
+// skip `pub`, `pub(crate)`, etc.
+fn skip_visibility(tokens: &mut Tokens) {
+    if matches!(tokens.peek(), Some(TokenTree::Ident(i)) if i.to_string() == "pub") {
+        tokens.next();
+        if matches!(tokens.peek(), Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis) {
+            tokens.next();
+        }
+    }
+}
+
+// the `#[...]` attributes in front of an item or field
+fn take_attributes(tokens: &mut Tokens) -> Result<Vec<Group>, String> {
+    let mut attributes = Vec::new();
+    while is_punct(tokens.peek(), '#') {
+        tokens.next();
+        match tokens.next() {
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Bracket => attributes.push(g),
+            _ => return Err("expected an attribute after `#`".to_string()),
+        }
+    }
+    Ok(attributes)
+}
+
+fn parse_struct(input: TokenStream) -> Result<(String, Vec<Field>), String> {
+    let mut tokens = input.into_iter().peekable();
+    take_attributes(&mut tokens)?;
+    skip_visibility(&mut tokens);
+    match tokens.next() {
+        Some(TokenTree::Ident(i)) if i.to_string() == "struct" => {}
+        _ => return Err("`TileCode` can only be derived for structs".to_string()),
+    }
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(i)) => i.to_string(),
+        _ => return Err("expected the name of the struct".to_string()),
+    };
+    match tokens.next() {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => Ok((name, parse_fields(g.stream())?)),
+        Some(TokenTree::Punct(p)) if p.as_char() == '<' => Err("`TileCode` can't be derived for generic structs".to_string()),
+        _ => Err("`TileCode` can only be derived for structs with named fields".to_string()),
+    }
+}
+
+fn parse_fields(stream: TokenStream) -> Result<Vec<Field>, String> {
+    let mut tokens = stream.into_iter().peekable();
+    let mut fields = Vec::new();
+    while tokens.peek().is_some() {
+        let attributes = take_attributes(&mut tokens)?;
+        skip_visibility(&mut tokens);
+        let name = match tokens.next() {
+            Some(TokenTree::Ident(i)) => i.to_string(),
+            _ => return Err("expected a field name".to_string()),
+        };
+        if !is_punct(tokens.next().as_ref(), ':') {
+            return Err(format!("expected `:` after field `{}`", name));
+        }
+        // skip the type, which ends at the first comma outside of any angle brackets
+        let mut depth = 0;
+        let mut arrow = false;
+        for token in tokens.by_ref() {
+            if let TokenTree::Punct(p) = &token {
+                match p.as_char() {
+                    '<' => depth += 1,
+                    // the `>` of a `->` doesn't close anything
+                    '>' if !arrow => depth -= 1,
+                    ',' if depth == 0 => break,
+                    _ => {}
+                }
+                arrow = p.as_char() == '-' && p.spacing() == Spacing::Joint;
+            } else {
+                arrow = false;
+            }
+        }
+        let mut kind = Kind::Float { scale: None, wrap: None };
+        for attribute in attributes {
+            parse_tile_attribute(attribute, &name, &mut kind)?;
+        }
+        fields.push(Field { name, kind });
+    }
+    Ok(fields)
+}
+
+// update the field's kind from a `#[tile(...)]` attribute, ignoring any other attribute
+fn parse_tile_attribute(attribute: Group, field: &str, kind: &mut Kind) -> Result<(), String> {
+    let mut tokens = attribute.stream().into_iter();
+    match tokens.next() {
+        Some(TokenTree::Ident(i)) if i.to_string() == "tile" => {}
+        _ => return Ok(()),
+    }
+    let arguments = match tokens.next() {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis => g.stream(),
+        _ => return Err(format!("expected `#[tile(...)]` on field `{}`", field)),
+    };
+    let mut tokens = arguments.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let key = match token {
+            TokenTree::Ident(i) => i.to_string(),
+            _ => return Err(format!("expected a `tile` option on field `{}`", field)),
+        };
+        match key.as_str() {
+            "int" => *kind = Kind::Int,
+            "skip" => *kind = Kind::Skip,
+            "scale" | "wrap" => {
+                if !is_punct(tokens.next().as_ref(), '=') {
+                    return Err(format!("expected `{} = ...` on field `{}`", key, field));
+                }
+                let mut value = String::new();
+                if is_punct(tokens.peek(), '-') {
+                    tokens.next();
+                    value.push('-');
+                }
+                match tokens.next() {
+                    Some(TokenTree::Literal(l)) => value.push_str(&l.to_string()),
+                    _ => return Err(format!("expected a number for `{}` on field `{}`", key, field)),
+                }
+                match kind {
+                    Kind::Float { scale, wrap } => {
+                        if key == "scale" {
+                            *scale = Some(value);
+                        } else {
+                            *wrap = Some(value);
+                        }
+                    }
+                    _ => return Err(format!("`{}` only applies to float fields, but field `{}` isn't one", key, field)),
+                }
+            }
+            _ => return Err(format!("unknown `tile` option `{}` on field `{}` (expected `scale`, `wrap`, `int`, or `skip`)", key, field)),
+        }
+        match tokens.next() {
+            None => {}
+            Some(TokenTree::Punct(p)) if p.as_char() == ',' => {}
+            _ => return Err(format!("expected `,` between `tile` options on field `{}`", field)),
+        }
+    }
+    Ok(())
+}
+
+fn generate(name: &str, fields: &[Field]) -> String {
+    let mut floats = String::new();
+    let mut ints = String::new();
+    let mut wrap_widths = String::new();
+    let mut wraps = false;
+    for field in fields {
+        match &field.kind {
+            Kind::Float { scale, wrap } => {
+                match scale {
+                    Some(scale) => floats.push_str(&format!("out.push(self.{} as f64 * ({}) as f64);", field.name, scale)),
+                    None => floats.push_str(&format!("out.push(self.{} as f64);", field.name)),
+                }
+                match wrap {
+                    Some(wrap) => {
+                        wraps = true;
+                        wrap_widths.push_str(&format!("out.push(::std::option::Option::Some({}));", wrap));
+                    }
+                    None => wrap_widths.push_str("out.push(::std::option::Option::None);"),
+                }
+            }
+            Kind::Int => ints.push_str(&format!("out.push(self.{} as isize);", field.name)),
+            Kind::Skip => {}
+        }
+    }
+    let wrap_widths = if wraps {
+        format!("fn wrap_widths(&self, out: &mut ::std::vec::Vec<::std::option::Option<isize>>) {{ {} }}", wrap_widths)
+    } else {
+        String::new()
+    };
+    format!(
+        "impl ::tilecoding::Tileable for {} {{
+            #[allow(unused_variables)]
+            fn floats(&self, out: &mut ::std::vec::Vec<f64>) {{ {} }}
+            #[allow(unused_variables)]
+            fn ints(&self, out: &mut ::std::vec::Vec<isize>) {{ {} }}
+            {}
+        }}",
+        name, floats, ints, wrap_widths
+    )
+}